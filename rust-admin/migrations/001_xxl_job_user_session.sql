@@ -0,0 +1,19 @@
+## —————————————————————— user session ——————————————————
+# 登录会话表：每次登录生成一条记录，仅保存令牌的 SHA-256 摘要
+
+CREATE TABLE IF NOT EXISTS `xxl_job_user_session`
+(
+    `id`           bigint(20)   NOT NULL AUTO_INCREMENT,
+    `user_id`      int(11)      NOT NULL COMMENT '用户ID',
+    `token_hash`   char(64)     NOT NULL COMMENT '登录令牌 SHA-256 摘要',
+    `issued_at`    datetime     NOT NULL COMMENT '签发时间',
+    `expires_at`   datetime     NOT NULL COMMENT '过期时间',
+    `last_used_at` datetime              DEFAULT NULL COMMENT '最近使用时间',
+    `client_ip`    varchar(64)           DEFAULT NULL COMMENT '客户端IP',
+    `user_agent`   varchar(255)          DEFAULT NULL COMMENT '客户端 User-Agent',
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_token_hash` (`token_hash`),
+    KEY `i_user_id` (`user_id`),
+    KEY `i_expires_at` (`expires_at`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,14 @@
+## —————————————————————— user group role ——————————————————
+# 按执行器授予的细粒度角色：viewer（只读）、operator（运维）、developer（开发）
+
+CREATE TABLE IF NOT EXISTS `xxl_job_user_group_role`
+(
+    `id`          int(11)     NOT NULL AUTO_INCREMENT,
+    `user_id`     int(11)     NOT NULL COMMENT '用户ID',
+    `job_group`   int(11)     NOT NULL COMMENT '执行器主键ID',
+    `role`        varchar(20) NOT NULL COMMENT '角色：viewer、operator、developer',
+    `update_time` datetime             DEFAULT NULL,
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_user_group` (`user_id`, `job_group`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,36 @@
+## —————————————————————— service account and api key ——————————————————
+# 服务账号供 CI / 自动化脚本调用 API，API Key 仅保存 SHA-256 摘要
+
+CREATE TABLE IF NOT EXISTS `xxl_job_service_account`
+(
+    `id`          int(11)      NOT NULL AUTO_INCREMENT,
+    `name`        varchar(50)  NOT NULL COMMENT '服务账号名称',
+    `description` varchar(255)          DEFAULT NULL COMMENT '用途说明',
+    `enabled`     tinyint(4)   NOT NULL DEFAULT '1' COMMENT '是否启用：0-停用、1-启用',
+    `created_by`  varchar(50)           DEFAULT NULL COMMENT '创建人',
+    `add_time`    datetime              DEFAULT NULL,
+    `update_time` datetime              DEFAULT NULL,
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_name` (`name`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS `xxl_job_api_key`
+(
+    `id`           bigint(20)   NOT NULL AUTO_INCREMENT,
+    `account_id`   int(11)      NOT NULL COMMENT '服务账号ID',
+    `name`         varchar(50)  NOT NULL COMMENT 'Key 名称',
+    `key_prefix`   varchar(16)  NOT NULL COMMENT 'Key 明文前缀，用于识别',
+    `key_hash`     char(64)     NOT NULL COMMENT 'Key SHA-256 摘要',
+    `job_groups`   varchar(255) NOT NULL COMMENT '授权执行器ID列表，多个逗号分割',
+    `role`         varchar(20)  NOT NULL COMMENT '授权角色：viewer、operator、developer',
+    `expires_at`   datetime              DEFAULT NULL COMMENT '过期时间，为空表示长期有效',
+    `revoked_at`   datetime              DEFAULT NULL COMMENT '吊销时间',
+    `last_used_at` datetime              DEFAULT NULL COMMENT '最近使用时间',
+    `last_used_ip` varchar(64)           DEFAULT NULL COMMENT '最近使用IP',
+    `add_time`     datetime              DEFAULT NULL,
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_key_hash` (`key_hash`),
+    KEY `i_account_id` (`account_id`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
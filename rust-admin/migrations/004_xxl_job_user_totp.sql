@@ -0,0 +1,26 @@
+## —————————————————————— user two-factor authentication ——————————————————
+# TOTP 两步验证：每个用户最多一条密钥记录，恢复码仅保存 SHA-256 摘要
+
+CREATE TABLE IF NOT EXISTS `xxl_job_user_totp`
+(
+    `user_id`        int(11)     NOT NULL COMMENT '用户ID',
+    `secret`         varchar(64) NOT NULL COMMENT 'TOTP 密钥（Base32）',
+    `enabled`        tinyint(4)  NOT NULL DEFAULT '0' COMMENT '是否已完成绑定：0-待确认、1-已启用',
+    `last_used_step` bigint(20)           DEFAULT NULL COMMENT '最近一次通过校验的时间步，防止验证码重放',
+    `add_time`       datetime             DEFAULT NULL,
+    `update_time`    datetime             DEFAULT NULL,
+    PRIMARY KEY (`user_id`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS `xxl_job_user_recovery_code`
+(
+    `id`        bigint(20) NOT NULL AUTO_INCREMENT,
+    `user_id`   int(11)    NOT NULL COMMENT '用户ID',
+    `code_hash` char(64)   NOT NULL COMMENT '恢复码 SHA-256 摘要',
+    `used_at`   datetime            DEFAULT NULL COMMENT '使用时间，为空表示未使用',
+    `add_time`  datetime            DEFAULT NULL,
+    PRIMARY KEY (`id`),
+    KEY `i_user_id` (`user_id`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
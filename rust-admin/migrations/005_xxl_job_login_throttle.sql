@@ -0,0 +1,34 @@
+## —————————————————————— login throttle ——————————————————
+# 登录失败计数：按用户名与来源 IP 分别统计，达到阈值后临时锁定
+
+CREATE TABLE IF NOT EXISTS `xxl_job_login_failure`
+(
+    `id`               bigint(20)   NOT NULL AUTO_INCREMENT,
+    `scope`            varchar(10)  NOT NULL COMMENT '统计维度：user、ip',
+    `subject`          varchar(128) NOT NULL COMMENT '用户名（小写）或来源IP',
+    `failure_count`    int(11)      NOT NULL DEFAULT '0' COMMENT '统计窗口内的失败次数',
+    `first_failure_at` datetime     NOT NULL COMMENT '统计窗口开始时间',
+    `last_failure_at`  datetime     NOT NULL COMMENT '最近失败时间',
+    `locked_until`     datetime              DEFAULT NULL COMMENT '锁定截止时间',
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_scope_subject` (`scope`, `subject`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
+
+# 锁定事件，供审计
+CREATE TABLE IF NOT EXISTS `xxl_job_login_lockout`
+(
+    `id`            bigint(20)   NOT NULL AUTO_INCREMENT,
+    `scope`         varchar(10)  NOT NULL COMMENT '锁定维度：user、ip',
+    `subject`       varchar(128) NOT NULL COMMENT '被锁定的用户名或IP',
+    `client_ip`     varchar(64)           DEFAULT NULL COMMENT '触发锁定的来源IP',
+    `failure_count` int(11)      NOT NULL COMMENT '触发锁定时的失败次数',
+    `locked_at`     datetime     NOT NULL COMMENT '锁定时间',
+    `locked_until`  datetime     NOT NULL COMMENT '锁定截止时间',
+    `unlocked_at`   datetime              DEFAULT NULL COMMENT '管理员解锁时间',
+    `unlocked_by`   varchar(50)           DEFAULT NULL COMMENT '解锁的管理员',
+    PRIMARY KEY (`id`),
+    KEY `i_scope_subject` (`scope`, `subject`),
+    KEY `i_locked_at` (`locked_at`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
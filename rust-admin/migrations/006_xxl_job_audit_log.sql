@@ -0,0 +1,26 @@
+## —————————————————————— audit log ——————————————————
+# 操作审计：记录任务、执行器、用户、GLUE 等数据的增删改以及启停、触发、终止、清理日志等操作
+
+CREATE TABLE IF NOT EXISTS `xxl_job_audit_log`
+(
+    `id`             bigint(20)  NOT NULL AUTO_INCREMENT,
+    `operator_type`  varchar(20) NOT NULL COMMENT '操作人类型：user、service_account',
+    `operator_id`    int(11)     NOT NULL COMMENT '用户ID或服务账号ID',
+    `operator_name`  varchar(64) NOT NULL COMMENT '操作人用户名，服务账号为 svc:<name>',
+    `session_id`     bigint(20)           DEFAULT NULL COMMENT '登录会话ID',
+    `api_key_id`     bigint(20)           DEFAULT NULL COMMENT 'API Key ID',
+    `action`         varchar(32) NOT NULL COMMENT '操作类型，如 create、update、delete、start、stop、trigger',
+    `target_type`    varchar(32) NOT NULL COMMENT '操作对象类型，如 job_info、job_group、job_user、glue',
+    `target_id`      varchar(64)          DEFAULT NULL COMMENT '操作对象ID',
+    `before_data`    mediumtext COMMENT '变更前快照（JSON）',
+    `after_data`     mediumtext COMMENT '变更后快照（JSON）',
+    `diff`           mediumtext COMMENT '变更字段（JSON），形如 {"字段": [变更前, 变更后]}',
+    `client_ip`      varchar(64)          DEFAULT NULL COMMENT '来源IP',
+    `user_agent`     varchar(255)         DEFAULT NULL COMMENT '来源客户端',
+    `created_at`     datetime    NOT NULL COMMENT '操作时间',
+    PRIMARY KEY (`id`),
+    KEY `i_created_at` (`created_at`),
+    KEY `i_target` (`target_type`, `target_id`),
+    KEY `i_operator` (`operator_name`, `created_at`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
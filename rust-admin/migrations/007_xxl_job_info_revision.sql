@@ -0,0 +1,16 @@
+## —————————————————————— job revision ——————————————————
+# 任务定义版本：每次新增、修改、保存 GLUE 或恢复版本时保存 xxl_job_info 的完整快照
+
+CREATE TABLE IF NOT EXISTS `xxl_job_info_revision`
+(
+    `id`          bigint(20)  NOT NULL AUTO_INCREMENT,
+    `job_id`      int(11)     NOT NULL COMMENT '任务，主键ID',
+    `revision`    int(11)     NOT NULL COMMENT '任务内递增的版本号',
+    `action`      varchar(20) NOT NULL COMMENT '变更来源：baseline、create、update、glue、restore',
+    `operator`    varchar(64) NOT NULL COMMENT '操作人',
+    `snapshot`    mediumtext  NOT NULL COMMENT 'xxl_job_info 完整快照（JSON）',
+    `created_at`  datetime    NOT NULL COMMENT '创建时间',
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `i_job_revision` (`job_id`, `revision`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,17 @@
+## —————————————————————— log archive ——————————————————
+# 执行日志归档：任务执行完成后从执行器拉取完整日志，压缩存放到本地磁盘或 S3 兼容存储，此表记录归档位置
+
+CREATE TABLE IF NOT EXISTS `xxl_job_log_archive`
+(
+    `log_id`          bigint(20)   NOT NULL COMMENT '调度日志，主键ID',
+    `storage`         varchar(20)  NOT NULL COMMENT '存储后端：local、s3',
+    `object_key`      varchar(255) NOT NULL COMMENT '归档文件路径或对象 Key',
+    `original_size`   bigint(20)   NOT NULL COMMENT '原始日志字节数',
+    `compressed_size` bigint(20)   NOT NULL COMMENT '压缩后字节数',
+    `line_count`      int(11)      NOT NULL COMMENT '日志行数',
+    `truncated`       tinyint(4)   NOT NULL DEFAULT '0' COMMENT '超过归档大小上限被截断：0-否、1-是',
+    `created_at`      datetime     NOT NULL COMMENT '归档时间',
+    PRIMARY KEY (`log_id`),
+    KEY `i_created_at` (`created_at`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,9 @@
+## —————————————————————— job log full-text search ——————————————————
+# 调度日志全文检索：为调度备注、执行备注以及归档的执行日志建立 ngram 全文索引，支持中英文关键字检索
+
+ALTER TABLE `xxl_job_log`
+    ADD FULLTEXT INDEX `ft_log_msg` (`trigger_msg`, `handle_msg`) WITH PARSER ngram;
+
+ALTER TABLE `xxl_job_log_archive`
+    ADD COLUMN `log_text` mediumtext NULL COMMENT '用于全文检索的执行日志文本，超过 search_max_bytes 时截断' AFTER `truncated`,
+    ADD FULLTEXT INDEX `ft_log_text` (`log_text`) WITH PARSER ngram;
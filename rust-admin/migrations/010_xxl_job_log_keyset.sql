@@ -0,0 +1,5 @@
+## —————————————————————— job log keyset pagination ——————————————————
+# 调度日志游标翻页：按执行器过滤后以 (trigger_time, id) 倒序定位，避免深度翻页时的 OFFSET 扫描
+
+ALTER TABLE `xxl_job_log`
+    ADD INDEX `i_group_trigger_time` (`job_group`, `trigger_time`, `id`);
@@ -0,0 +1,6 @@
+## —————————————————————— job log rerun ——————————————————
+# 调度日志重跑：按原调度日志的参数、分片与 Handler 重新调度，新日志记录其来源
+
+ALTER TABLE `xxl_job_log`
+    ADD COLUMN `rerun_of` bigint(20) NULL COMMENT '重跑来源的调度日志ID' AFTER `alarm_status`,
+    ADD INDEX `i_rerun_of` (`rerun_of`);
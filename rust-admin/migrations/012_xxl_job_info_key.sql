@@ -0,0 +1,12 @@
+## —————————————————————— job key ——————————————————
+# 任务标识：执行器内唯一、跨环境稳定的任务标识，供任务配置导入导出按 app_name + job_key 匹配任务
+
+ALTER TABLE `xxl_job_info`
+    ADD COLUMN `job_key` varchar(64) NULL COMMENT '执行器内唯一的任务标识' AFTER `job_desc`;
+
+UPDATE `xxl_job_info`
+SET `job_key` = CONCAT('job-', `id`)
+WHERE `job_key` IS NULL;
+
+ALTER TABLE `xxl_job_info`
+    ADD UNIQUE KEY `i_group_job_key` (`job_group`, `job_key`);
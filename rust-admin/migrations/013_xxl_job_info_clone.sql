@@ -0,0 +1,5 @@
+## —————————————————————— job clone ——————————————————
+# 复制任务：记录任务复制自哪个任务
+
+ALTER TABLE `xxl_job_info`
+    ADD COLUMN `cloned_from` int(11) NULL COMMENT '复制来源任务ID' AFTER `child_jobid`;
@@ -0,0 +1,17 @@
+## —————————————————————— job trash ——————————————————
+# 任务回收站：删除的任务在保留期内可恢复，调度日志与 GLUE 历史保留到彻底删除时
+
+CREATE TABLE IF NOT EXISTS `xxl_job_info_trash`
+(
+    `job_id`       int(11)      NOT NULL COMMENT '任务，主键ID（恢复时沿用）',
+    `job_group`    int(11)      NOT NULL COMMENT '执行器主键ID',
+    `job_desc`     varchar(255) NOT NULL COMMENT '任务描述',
+    `snapshot`     mediumtext   NOT NULL COMMENT '删除时 xxl_job_info 的完整快照（JSON）',
+    `deleted_by`   varchar(64)  NOT NULL COMMENT '操作人',
+    `deleted_at`   datetime     NOT NULL COMMENT '删除时间',
+    `purge_after`  datetime     NOT NULL COMMENT '超过该时间后彻底删除',
+    PRIMARY KEY (`job_id`),
+    KEY `i_job_group` (`job_group`),
+    KEY `i_purge_after` (`purge_after`)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,7 @@
+## —————————————————————— user oidc identity ——————————————————
+# 单点登录身份：按 ID Token 的签发方（iss）与用户标识（sub）匹配本地账号，不再按用户名匹配
+
+ALTER TABLE `xxl_job_user`
+    ADD COLUMN `oidc_issuer`  varchar(191) NULL COMMENT '关联的 OIDC 签发方（iss）' AFTER `permission`,
+    ADD COLUMN `oidc_subject` varchar(191) NULL COMMENT '关联的 OIDC 用户标识（sub）' AFTER `oidc_issuer`,
+    ADD UNIQUE KEY `i_oidc_identity` (`oidc_issuer`, `oidc_subject`);
@@ -0,0 +1,14 @@
+## —————————————————————— user auth source ——————————————————
+# 账号认证来源：local-本地密码、ldap-LDAP、oidc-单点登录；外部身份源不会接管其它来源的同名账号
+
+ALTER TABLE `xxl_job_user`
+    ADD COLUMN `auth_source` varchar(16) NOT NULL DEFAULT 'local' COMMENT '认证来源：local、ldap、oidc' AFTER `permission`;
+
+# 由 LDAP、单点登录自动创建的账号使用不可校验的占位密码
+UPDATE `xxl_job_user`
+SET `auth_source` = 'ldap'
+WHERE `password` = '!ldap';
+
+UPDATE `xxl_job_user`
+SET `auth_source` = 'oidc'
+WHERE `password` = '!oidc';
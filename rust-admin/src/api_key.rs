@@ -0,0 +1,32 @@
+use crate::session::hash_token;
+
+/// API Key 的固定前缀，`AuthUser` 据此区分 API Key 与登录会话令牌。
+pub const API_KEY_PREFIX: &str = "xjk_";
+
+/// 展示给用户用于识别 Key 的明文前缀长度（含 `API_KEY_PREFIX`）。
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct GeneratedApiKey {
+    pub plaintext: String,
+    pub prefix: String,
+    pub hash: String,
+}
+
+/// 生成新的 API Key，明文只在创建时返回一次，数据库仅保存摘要。
+pub fn generate() -> GeneratedApiKey {
+    let plaintext = format!(
+        "{API_KEY_PREFIX}{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    GeneratedApiKey {
+        prefix: plaintext[..DISPLAY_PREFIX_LEN].to_string(),
+        hash: hash_token(&plaintext),
+        plaintext,
+    }
+}
+
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
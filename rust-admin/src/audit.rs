@@ -0,0 +1,318 @@
+use chrono::Local;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+use crate::auth::AuthUser;
+use crate::entities::audit_log;
+use crate::error::AppResult;
+use crate::session::ClientInfo;
+use crate::state::AppState;
+
+/// 快照中不落库的敏感字段，统一替换为占位符。
+const REDACTED_FIELDS: &[&str] = &["password", "token", "token_hash", "key_hash", "secret"];
+const REDACTED: &str = "******";
+
+/// 审计记录的操作类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Start,
+    Stop,
+    Trigger,
+    Kill,
+    /// 按原参数重跑调度日志
+    Rerun,
+    /// 按声明式配置文件导入任务
+    Import,
+    /// 从回收站恢复任务
+    Restore,
+    Clear,
+    /// 注销会话、吊销 API Key
+    Revoke,
+    /// 重置两步验证
+    Reset,
+    Unlock,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Start => "start",
+            AuditAction::Stop => "stop",
+            AuditAction::Trigger => "trigger",
+            AuditAction::Kill => "kill",
+            AuditAction::Rerun => "rerun",
+            AuditAction::Import => "import",
+            AuditAction::Restore => "restore",
+            AuditAction::Clear => "clear",
+            AuditAction::Revoke => "revoke",
+            AuditAction::Reset => "reset",
+            AuditAction::Unlock => "unlock",
+        }
+    }
+}
+
+/// 审计记录的操作对象类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditTarget {
+    JobInfo,
+    JobGroup,
+    JobUser,
+    Glue,
+    JobLog,
+    ServiceAccount,
+    ApiKey,
+    UserSession,
+    LoginLockout,
+}
+
+impl AuditTarget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditTarget::JobInfo => "job_info",
+            AuditTarget::JobGroup => "job_group",
+            AuditTarget::JobUser => "job_user",
+            AuditTarget::Glue => "glue",
+            AuditTarget::JobLog => "job_log",
+            AuditTarget::ServiceAccount => "service_account",
+            AuditTarget::ApiKey => "api_key",
+            AuditTarget::UserSession => "user_session",
+            AuditTarget::LoginLockout => "login_lockout",
+        }
+    }
+}
+
+/// 一条待写入的审计事件，`before` / `after` 为操作对象在变更前后的快照。
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    action: AuditAction,
+    target: AuditTarget,
+    target_id: Option<String>,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, target: AuditTarget, target_id: impl ToString) -> Self {
+        Self {
+            action,
+            target,
+            target_id: Some(target_id.to_string()),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// 不针对单个对象的操作，例如按条件清理日志。
+    pub fn without_id(action: AuditAction, target: AuditTarget) -> Self {
+        Self {
+            action,
+            target,
+            target_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before = snapshot(value);
+        self
+    }
+
+    pub fn after(mut self, value: &impl Serialize) -> Self {
+        self.after = snapshot(value);
+        self
+    }
+}
+
+fn snapshot(value: &impl Serialize) -> Option<Value> {
+    match serde_json::to_value(value) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!(error = %err, "序列化审计快照失败");
+            None
+        }
+    }
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    if !item.is_null() {
+                        *item = Value::String(REDACTED.into());
+                    }
+                } else {
+                    redact(item);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// 对比两个快照的顶层字段，返回 `{"字段": [变更前, 变更后]}`；两侧不都是对象时返回 `None`。
+pub fn diff(before: &Value, after: &Value) -> Option<Value> {
+    let (Value::Object(before), Value::Object(after)) = (before, after) else {
+        return None;
+    };
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        if changes.contains_key(key) {
+            continue;
+        }
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(key.clone(), Value::Array(vec![old.clone(), new.clone()]));
+        }
+    }
+    Some(Value::Object(changes))
+}
+
+/// 写入一条审计记录。
+///
+/// 应传入执行变更的事务，与变更一同提交或回滚，保证已生效的变更都有审计记录。
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    user: &AuthUser,
+    client: &ClientInfo,
+    event: AuditEvent,
+) -> AppResult<()> {
+    // 先对比原始快照再脱敏，密码等字段发生变化时仍会出现在变更字段中
+    let diff = match (&event.before, &event.after) {
+        (Some(before), Some(after)) => diff(before, after),
+        _ => None,
+    };
+    let to_text = |value: Option<Value>| {
+        value.map(|mut value| {
+            redact(&mut value);
+            value.to_string()
+        })
+    };
+
+    audit_log::Entity::insert(audit_log::ActiveModel {
+        operator_type: Set(if user.api_key_id.is_some() {
+            "service_account".into()
+        } else {
+            "user".into()
+        }),
+        operator_id: Set(user.id),
+        operator_name: Set(user.username.clone()),
+        session_id: Set(user.session_id),
+        api_key_id: Set(user.api_key_id),
+        action: Set(event.action.as_str().into()),
+        target_type: Set(event.target.as_str().into()),
+        target_id: Set(event.target_id),
+        before_data: Set(to_text(event.before)),
+        after_data: Set(to_text(event.after)),
+        diff: Set(to_text(diff)),
+        client_ip: Set(client.ip.clone()),
+        user_agent: Set(client.user_agent.clone()),
+        created_at: Set(Local::now().naive_local()),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+/// 为已无法回滚的操作（例如已下发到执行器的触发、终止）写入审计记录。
+///
+/// 操作已经生效，写入失败只记录告警，不再向调用方返回错误，避免客户端重试导致重复执行。
+pub async fn record_after_dispatch(
+    state: &AppState,
+    user: &AuthUser,
+    client: &ClientInfo,
+    event: AuditEvent,
+) {
+    let action = event.action.as_str();
+    let target = event.target.as_str();
+    let target_id = event.target_id.clone();
+    if let Err(err) = record(state.db(), user, client, event).await {
+        warn!(
+            error = %err,
+            action,
+            target,
+            target_id = target_id.as_deref().unwrap_or("-"),
+            operator = %user.username,
+            "写入审计记录失败，操作已生效"
+        );
+    }
+}
+
+/// 按 `audit.retention_days` 定期清理过期的审计记录。
+pub fn spawn_retention(state: AppState) {
+    let settings = state.settings().audit.clone();
+    let Some(retention) = settings.retention() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(settings.cleanup_interval());
+        loop {
+            interval.tick().await;
+            let cutoff = Local::now().naive_local() - retention;
+            match audit_log::Entity::delete_many()
+                .filter(audit_log::Column::CreatedAt.lt(cutoff))
+                .exec(state.db())
+                .await
+            {
+                Ok(result) if result.rows_affected > 0 => {
+                    info!(deleted = result.rows_affected, %cutoff, "已清理过期审计记录");
+                }
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "清理过期审计记录失败"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_reports_changed_fields_only() {
+        let before = json!({ "jobDesc": "old", "executorTimeout": 0, "author": "a" });
+        let after = json!({ "jobDesc": "new", "executorTimeout": 0, "alarmEmail": "x@y" });
+        assert_eq!(
+            diff(&before, &after),
+            Some(json!({
+                "jobDesc": ["old", "new"],
+                "author": ["a", null],
+                "alarmEmail": [null, "x@y"],
+            }))
+        );
+        assert_eq!(diff(&json!(1), &json!(2)), None);
+    }
+
+    #[test]
+    fn redact_hides_credentials() {
+        let mut value = json!({
+            "username": "admin",
+            "password": "$argon2id$...",
+            "token": null,
+            "keys": [{ "key_hash": "abc" }],
+        });
+        redact(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "username": "admin",
+                "password": REDACTED,
+                "token": null,
+                "keys": [{ "key_hash": REDACTED }],
+            })
+        );
+    }
+}
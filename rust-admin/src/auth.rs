@@ -1,14 +1,24 @@
+use std::collections::BTreeMap;
+
 use axum::extract::{FromRef, FromRequestParts};
 use axum::http::request::Parts;
 use axum_extra::headers::{authorization::Bearer, Authorization};
 use axum_extra::TypedHeader;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use chrono::{Duration, Local};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
 
-use crate::entities::job_user;
+use crate::api_key;
+use crate::entities::{
+    api_key as api_key_entity, job_user, job_user_group_role, job_user_session, service_account,
+};
 use crate::error::{AppError, AppResult};
+use crate::session::{self, hash_token, ClientInfo};
 use crate::state::AppState;
 
+/// 会话与 API Key 最近使用时间的刷新间隔，避免每个请求都写库。
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum UserRole {
@@ -26,23 +36,153 @@ impl UserRole {
     }
 }
 
+/// 按执行器授予的细粒度角色，高级角色包含低级角色的全部能力。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupRole {
+    /// 只读：查看任务、调度日志与 GLUE
+    Viewer,
+    /// 运维：手动触发、启停任务，终止运行中的任务
+    Operator,
+    /// 开发：新增、编辑、删除任务，维护 GLUE，清理日志
+    Developer,
+}
+
+impl GroupRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupRole::Viewer => "viewer",
+            GroupRole::Operator => "operator",
+            GroupRole::Developer => "developer",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value.trim() {
+            "viewer" => Ok(GroupRole::Viewer),
+            "operator" => Ok(GroupRole::Operator),
+            "developer" => Ok(GroupRole::Developer),
+            other => Err(AppError::BadRequest(format!("未知执行器角色: {other}"))),
+        }
+    }
+}
+
+/// 路由处理函数需要校验的操作权限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    View,
+    Operate,
+    Develop,
+    /// 执行器与用户维护，仅管理员拥有
+    Manage,
+}
+
+impl Permission {
+    fn required_role(self) -> Option<GroupRole> {
+        match self {
+            Permission::View => Some(GroupRole::Viewer),
+            Permission::Operate => Some(GroupRole::Operator),
+            Permission::Develop => Some(GroupRole::Developer),
+            Permission::Manage => None,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Permission::View => "查看",
+            Permission::Operate => "运维操作",
+            Permission::Develop => "开发维护",
+            Permission::Manage => "管理",
+        }
+    }
+}
+
+/// 通过认证的调用方，可能是登录用户，也可能是持有 API Key 的服务账号。
+///
+/// 服务账号的 `id` 为服务账号 ID，`username` 形如 `svc:<name>`，且始终是普通用户角色，
+/// 权限范围仅限 API Key 上授权的执行器与角色。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthUser {
     pub id: i32,
     pub username: String,
     pub role: UserRole,
     pub permission: Option<String>,
+    pub session_id: Option<i64>,
+    pub api_key_id: Option<i64>,
+    pub group_roles: BTreeMap<i32, GroupRole>,
 }
 
 impl AuthUser {
-    pub fn require_admin(&self) -> AppResult<()> {
-        if !matches!(self.role, UserRole::Admin) {
-            return Err(AppError::Forbidden("需要管理员权限".into()));
+    pub fn is_admin(&self) -> bool {
+        matches!(self.role, UserRole::Admin)
+    }
+
+    /// 会话相关接口只对登录用户开放，返回当前会话 ID。
+    pub fn require_session(&self) -> AppResult<i64> {
+        self.session_id
+            .ok_or_else(|| AppError::Forbidden("该接口仅支持登录用户调用".into()))
+    }
+
+    /// 校验当前用户是否拥有指定执行器上的操作权限，管理员不受限制。
+    ///
+    /// `group` 为 `None` 时表示与执行器无关的全局操作，仅管理员可执行。
+    pub fn require(&self, permission: Permission, group: Option<i32>) -> AppResult<()> {
+        if self.is_admin() {
+            return Ok(());
+        }
+        let allowed = match (permission.required_role(), group) {
+            (Some(required), Some(group_id)) => self
+                .group_roles
+                .get(&group_id)
+                .map(|role| *role >= required)
+                .unwrap_or(false),
+            _ => false,
+        };
+        if allowed {
+            return Ok(());
+        }
+        Err(match group {
+            Some(group_id) if permission != Permission::Manage => AppError::Forbidden(format!(
+                "无权在执行器 {group_id} 上进行{}",
+                permission.describe()
+            )),
+            _ => AppError::Forbidden("需要管理员权限".into()),
+        })
+    }
+
+    /// 当前用户可查看的执行器 ID 列表；管理员返回 `None` 表示不受限制。
+    pub fn group_scope(&self) -> Option<Vec<i32>> {
+        if self.is_admin() {
+            return None;
         }
-        Ok(())
+        Some(self.group_roles.keys().copied().collect())
     }
 }
 
+/// 合并旧版 `permission` 字段与角色表中的授权。
+///
+/// `permission` 中列出的执行器沿用 Java 版语义，视为运维角色；角色表授予更高角色时以高者为准。
+fn resolve_group_roles(
+    permission: Option<&str>,
+    grants: impl IntoIterator<Item = (i32, GroupRole)>,
+) -> BTreeMap<i32, GroupRole> {
+    let mut roles = BTreeMap::new();
+    for group_id in permission
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|item| item.trim().parse::<i32>().ok())
+    {
+        roles.insert(group_id, GroupRole::Operator);
+    }
+    for (group_id, role) in grants {
+        let entry = roles.entry(group_id).or_insert(role);
+        if role > *entry {
+            *entry = role;
+        }
+    }
+    roles
+}
+
 #[axum::async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
@@ -53,33 +193,188 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let header = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await;
+        let state = AppState::from_ref(state);
         let TypedHeader(Authorization(bearer)) = match header {
             Ok(value) => value,
             Err(rejection) => {
-                if rejection.is_missing() {
+                if !rejection.is_missing() {
+                    return Err(AppError::Unauthorized("无法解析 Authorization 头".into()));
+                }
+                // 管理台页面通过 HttpOnly Cookie 携带会话，写操作需要额外校验 CSRF 令牌
+                let Some(token) = session::cookie_token(&parts.headers) else {
                     return Err(AppError::Unauthorized(
                         "缺少 Authorization Bearer 令牌".into(),
                     ));
-                }
-                return Err(AppError::Unauthorized("无法解析 Authorization 头".into()));
+                };
+                session::verify_csrf(&parts.method, &parts.headers, &token)?;
+                return authenticate_session(&state, &token).await;
             }
         };
 
-        let state = AppState::from_ref(state);
         let token = bearer.token();
 
-        let user = job_user::Entity::find()
-            .filter(job_user::Column::Token.eq(token))
-            .one(state.db())
-            .await?
-            .ok_or_else(|| AppError::Unauthorized("无效的登录令牌".into()))?;
+        if api_key::is_api_key(token) {
+            let client = ClientInfo::from_parts(parts, &state.settings().security);
+            return authenticate_api_key(&state, token, &client).await;
+        }
+        authenticate_session(&state, token).await
+    }
+}
+
+fn needs_touch(last_used_at: Option<chrono::NaiveDateTime>, now: chrono::NaiveDateTime) -> bool {
+    last_used_at
+        .map(|last| now - last >= Duration::seconds(TOUCH_INTERVAL_SECONDS))
+        .unwrap_or(true)
+}
+
+async fn authenticate_session(state: &AppState, token: &str) -> AppResult<AuthUser> {
+    let session = job_user_session::Entity::find()
+        .filter(job_user_session::Column::TokenHash.eq(hash_token(token)))
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("无效的登录令牌".into()))?;
+
+    let now = Local::now().naive_local();
+    if session.expires_at <= now {
+        job_user_session::Entity::delete_by_id(session.id)
+            .exec(state.db())
+            .await?;
+        return Err(AppError::Unauthorized("登录已过期，请重新登录".into()));
+    }
+
+    let user = job_user::Entity::find_by_id(session.user_id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("无效的登录令牌".into()))?;
+
+    let session_id = session.id;
+    if needs_touch(session.last_used_at, now) {
+        let mut active: job_user_session::ActiveModel = session.into();
+        active.last_used_at = Set(Some(now));
+        active.update(state.db()).await?;
+    }
+
+    let role = UserRole::from_i8(user.role)?;
+    let grants = job_user_group_role::Entity::find()
+        .filter(job_user_group_role::Column::UserId.eq(user.id))
+        .all(state.db())
+        .await?
+        .into_iter()
+        .filter_map(|grant| {
+            GroupRole::parse(&grant.role)
+                .ok()
+                .map(|role| (grant.job_group, role))
+        });
+    let group_roles = resolve_group_roles(user.permission.as_deref(), grants);
+    Ok(AuthUser {
+        id: user.id,
+        username: user.username,
+        role,
+        permission: user.permission,
+        session_id: Some(session_id),
+        api_key_id: None,
+        group_roles,
+    })
+}
+
+async fn authenticate_api_key(
+    state: &AppState,
+    token: &str,
+    client: &ClientInfo,
+) -> AppResult<AuthUser> {
+    let (key, account) = api_key_entity::Entity::find()
+        .filter(api_key_entity::Column::KeyHash.eq(hash_token(token)))
+        .find_also_related(service_account::Entity)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("无效的 API Key".into()))?;
+    let account = account.ok_or_else(|| AppError::Unauthorized("无效的 API Key".into()))?;
+
+    let now = Local::now().naive_local();
+    if key.revoked_at.is_some() {
+        return Err(AppError::Unauthorized("API Key 已被吊销".into()));
+    }
+    if key.expires_at.map(|time| time <= now).unwrap_or(false) {
+        return Err(AppError::Unauthorized("API Key 已过期".into()));
+    }
+    if account.enabled == 0 {
+        return Err(AppError::Unauthorized("服务账号已停用".into()));
+    }
+
+    let role = GroupRole::parse(&key.role)?;
+    let group_roles = key
+        .job_groups
+        .split(',')
+        .filter_map(|item| item.trim().parse::<i32>().ok())
+        .map(|group_id| (group_id, role))
+        .collect();
+
+    let key_id = key.id;
+    if needs_touch(key.last_used_at, now) {
+        let mut active: api_key_entity::ActiveModel = key.into();
+        active.last_used_at = Set(Some(now));
+        active.last_used_ip = Set(client.ip.clone());
+        active.update(state.db()).await?;
+    }
+
+    Ok(AuthUser {
+        id: account.id,
+        username: format!("svc:{}", account.name),
+        role: UserRole::User,
+        permission: None,
+        session_id: None,
+        api_key_id: Some(key_id),
+        group_roles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let role = UserRole::from_i8(user.role)?;
-        Ok(AuthUser {
-            id: user.id,
-            username: user.username,
+    fn user(role: UserRole, group_roles: BTreeMap<i32, GroupRole>) -> AuthUser {
+        AuthUser {
+            id: 1,
+            username: "tester".into(),
             role,
-            permission: user.permission,
-        })
+            permission: None,
+            session_id: Some(1),
+            api_key_id: None,
+            group_roles,
+        }
+    }
+
+    #[test]
+    fn legacy_permission_maps_to_operator() {
+        let roles = resolve_group_roles(
+            Some("1, 2,abc"),
+            [(2, GroupRole::Viewer), (3, GroupRole::Developer)],
+        );
+        assert_eq!(roles.get(&1), Some(&GroupRole::Operator));
+        assert_eq!(roles.get(&2), Some(&GroupRole::Operator));
+        assert_eq!(roles.get(&3), Some(&GroupRole::Developer));
+        assert_eq!(roles.len(), 3);
+    }
+
+    #[test]
+    fn require_checks_role_per_group() {
+        let viewer = user(UserRole::User, BTreeMap::from([(1, GroupRole::Viewer)]));
+        assert!(viewer.require(Permission::View, Some(1)).is_ok());
+        assert!(viewer.require(Permission::Operate, Some(1)).is_err());
+        assert!(viewer.require(Permission::View, Some(2)).is_err());
+
+        let developer = user(UserRole::User, BTreeMap::from([(1, GroupRole::Developer)]));
+        assert!(developer.require(Permission::Operate, Some(1)).is_ok());
+        assert!(developer.require(Permission::Develop, Some(1)).is_ok());
+        assert!(developer.require(Permission::Manage, Some(1)).is_err());
+        assert!(developer.require(Permission::Develop, None).is_err());
+    }
+
+    #[test]
+    fn admin_is_unrestricted() {
+        let admin = user(UserRole::Admin, BTreeMap::new());
+        assert!(admin.require(Permission::Manage, None).is_ok());
+        assert!(admin.require(Permission::Develop, Some(42)).is_ok());
+        assert!(admin.group_scope().is_none());
     }
 }
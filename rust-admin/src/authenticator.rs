@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+use tracing::warn;
+
+use crate::auth::{GroupRole, UserRole};
+use crate::config::Settings;
+use crate::entities::{job_group, job_user, job_user_group_role};
+use crate::error::{AppError, AppResult};
+use crate::ldap::{LdapAuthenticator, LdapDirectory};
+use crate::password;
+use crate::state::AppState;
+
+/// 用户名密码登录的认证后端，`/api/auth/login` 按顺序依次尝试。
+#[axum::async_trait]
+pub trait Authenticator: Send + Sync {
+    /// 认证成功返回对应的本地账号；返回 `Ok(None)` 表示该后端无法认证，由下一个后端继续尝试。
+    async fn authenticate(
+        &self,
+        state: &AppState,
+        username: &str,
+        password: &str,
+    ) -> AppResult<Option<job_user::Model>>;
+}
+
+/// 根据配置组装认证链：启用 LDAP 时优先 LDAP，本地账号作为应急登录方式。
+pub fn from_settings(settings: &Settings) -> Vec<Arc<dyn Authenticator>> {
+    let mut chain: Vec<Arc<dyn Authenticator>> = Vec::new();
+    if settings.ldap.enabled {
+        chain.push(Arc::new(LdapAuthenticator::new(
+            settings.ldap.clone(),
+            LdapDirectory::new(settings.ldap.clone()),
+        )));
+    }
+    if !settings.ldap.enabled || settings.ldap.local_fallback {
+        chain.push(Arc::new(LocalAuthenticator));
+    }
+    chain
+}
+
+/// 依次调用认证链，全部失败时统一返回“用户名或密码错误”。
+pub async fn authenticate(
+    state: &AppState,
+    username: &str,
+    password: &str,
+) -> AppResult<job_user::Model> {
+    for authenticator in state.authenticators() {
+        if let Some(user) = authenticator
+            .authenticate(state, username, password)
+            .await?
+        {
+            return Ok(user);
+        }
+    }
+    Err(AppError::Unauthorized("用户名或密码错误".into()))
+}
+
+/// 校验 `xxl_job_user` 中保存的密码摘要。
+pub struct LocalAuthenticator;
+
+#[axum::async_trait]
+impl Authenticator for LocalAuthenticator {
+    async fn authenticate(
+        &self,
+        state: &AppState,
+        username: &str,
+        plain: &str,
+    ) -> AppResult<Option<job_user::Model>> {
+        let Some(user) = job_user::Entity::find()
+            .filter(job_user::Column::Username.eq(username))
+            .one(state.db())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(scheme) = password::verify_password(plain, &user.password) else {
+            return Ok(None);
+        };
+
+        if scheme.is_legacy() {
+            // 历史 MD5 / SHA-256 摘要在登录成功后透明升级为 Argon2id
+            let mut active: job_user::ActiveModel = user.into();
+            active.password = Set(password::hash_password(plain)?);
+            return Ok(Some(active.update(state.db()).await?));
+        }
+        Ok(Some(user))
+    }
+}
+
+/// 外部身份源（OIDC、LDAP）的分组映射结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedAccess {
+    pub role: UserRole,
+    pub group_roles: BTreeMap<i32, GroupRole>,
+}
+
+/// 一条“外部分组 → 执行器角色”的映射规则。
+#[derive(Debug, Clone, Copy)]
+pub struct GroupRule<'a> {
+    pub group: &'a str,
+    pub job_groups: &'a [i32],
+    pub role: &'a str,
+}
+
+/// 按外部分组计算管理台角色与执行器授权，分组名称不区分大小写，同一执行器取最高角色。
+pub fn map_groups<'a>(
+    groups: &[String],
+    admin_groups: &[String],
+    rules: impl IntoIterator<Item = GroupRule<'a>>,
+) -> MappedAccess {
+    let in_group = |name: &str| groups.iter().any(|group| group.eq_ignore_ascii_case(name));
+
+    let role = if admin_groups.iter().any(|name| in_group(name)) {
+        UserRole::Admin
+    } else {
+        UserRole::User
+    };
+
+    let mut group_roles = BTreeMap::new();
+    for rule in rules.into_iter().filter(|rule| in_group(rule.group)) {
+        let Ok(granted) = GroupRole::parse(rule.role) else {
+            warn!(
+                group = rule.group,
+                role = rule.role,
+                "分组映射的角色无效，已忽略"
+            );
+            continue;
+        };
+        for group_id in rule.job_groups {
+            let entry = group_roles.entry(*group_id).or_insert(granted);
+            if granted > *entry {
+                *entry = granted;
+            }
+        }
+    }
+
+    MappedAccess { role, group_roles }
+}
+
+/// 账号的认证来源，记录在 `xxl_job_user.auth_source` 中。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthSource {
+    /// 管理台维护的本地密码账号
+    Local,
+    Ldap,
+    Oidc,
+}
+
+impl AuthSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuthSource::Local => "local",
+            AuthSource::Ldap => "ldap",
+            AuthSource::Oidc => "oidc",
+        }
+    }
+}
+
+/// 外部身份源中的账号。
+#[derive(Debug, Clone, Copy)]
+pub enum ExternalAccount<'a> {
+    /// 按 ID Token 的 `iss` 与 `sub` 匹配本地账号，`username` 只用于首次登录时创建账号
+    Oidc {
+        issuer: &'a str,
+        subject: &'a str,
+        username: &'a str,
+    },
+    Ldap {
+        username: &'a str,
+    },
+}
+
+impl ExternalAccount<'_> {
+    fn username(&self) -> &str {
+        match self {
+            ExternalAccount::Oidc { username, .. } | ExternalAccount::Ldap { username } => username,
+        }
+    }
+
+    fn source(&self) -> AuthSource {
+        match self {
+            ExternalAccount::Oidc { .. } => AuthSource::Oidc,
+            ExternalAccount::Ldap { .. } => AuthSource::Ldap,
+        }
+    }
+}
+
+/// 外部身份源登录后对应的本地账号：首次登录时自动创建，`sync_access` 为真时以外部分组为准同步角色与执行器授权。
+///
+/// 自动创建的账号使用不可校验的占位密码，只能通过对应的外部身份源登录。
+/// 不会按用户名接管其它来源的账号：本地账号需要管理员先为其关联单点登录身份，且不能通过 LDAP 登录。
+pub async fn provision_user(
+    state: &AppState,
+    account: ExternalAccount<'_>,
+    password_placeholder: &str,
+    access: &MappedAccess,
+    sync_access: bool,
+) -> AppResult<job_user::Model> {
+    let role = access.role as i8;
+
+    let txn = state.db().begin().await?;
+    let existing = find_linked_user(&txn, account).await?;
+    let user = match existing {
+        Some(user) if sync_access && user.role != role => {
+            let mut active: job_user::ActiveModel = user.into();
+            active.role = Set(role);
+            active.update(&txn).await?
+        }
+        Some(user) => user,
+        None => {
+            let (oidc_issuer, oidc_subject) = match account {
+                ExternalAccount::Oidc {
+                    issuer, subject, ..
+                } => (Some(issuer.to_string()), Some(subject.to_string())),
+                ExternalAccount::Ldap { .. } => (None, None),
+            };
+            let active = job_user::ActiveModel {
+                username: Set(account.username().to_string()),
+                password: Set(password_placeholder.to_string()),
+                token: Set(None),
+                role: Set(role),
+                permission: Set(None),
+                auth_source: Set(account.source().as_str().to_string()),
+                oidc_issuer: Set(oidc_issuer),
+                oidc_subject: Set(oidc_subject),
+                ..Default::default()
+            };
+            job_user::Entity::insert(active)
+                .exec_with_returning(&txn)
+                .await?
+        }
+    };
+
+    if sync_access && access.role == UserRole::User {
+        let known_groups: Vec<i32> = job_group::Entity::find()
+            .filter(job_group::Column::Id.is_in(access.group_roles.keys().copied()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|group| group.id)
+            .collect();
+
+        job_user_group_role::Entity::delete_many()
+            .filter(job_user_group_role::Column::UserId.eq(user.id))
+            .exec(&txn)
+            .await?;
+        let now = Local::now().naive_local();
+        let models: Vec<_> = access
+            .group_roles
+            .iter()
+            .filter(|(group_id, _)| known_groups.contains(group_id))
+            .map(|(group_id, role)| job_user_group_role::ActiveModel {
+                user_id: Set(user.id),
+                job_group: Set(*group_id),
+                role: Set(role.as_str().to_string()),
+                update_time: Set(Some(now)),
+                ..Default::default()
+            })
+            .collect();
+        if !models.is_empty() {
+            job_user_group_role::Entity::insert_many(models)
+                .exec(&txn)
+                .await?;
+        }
+    }
+    txn.commit().await?;
+
+    Ok(user)
+}
+
+/// 查找外部账号已关联的本地账号，返回 `Ok(None)` 时由调用方创建新账号。
+///
+/// 单点登录按 `iss` + `sub` 匹配；其余情况只能关联同名且由同一身份源创建的账号（单点登录还要求尚未记录身份，
+/// 即升级前创建的账号），其它同名账号一律拒绝，避免外部身份源中的同名用户接管本地账号。
+async fn find_linked_user<C: ConnectionTrait>(
+    conn: &C,
+    account: ExternalAccount<'_>,
+) -> AppResult<Option<job_user::Model>> {
+    if let ExternalAccount::Oidc {
+        issuer, subject, ..
+    } = account
+    {
+        let linked = job_user::Entity::find()
+            .filter(job_user::Column::OidcIssuer.eq(issuer))
+            .filter(job_user::Column::OidcSubject.eq(subject))
+            .one(conn)
+            .await?;
+        if linked.is_some() {
+            return Ok(linked);
+        }
+    }
+
+    let username = account.username();
+    let Some(user) = job_user::Entity::find()
+        .filter(job_user::Column::Username.eq(username))
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if !adoptable(&user, account.source()) {
+        return Err(AppError::Forbidden(match account {
+            ExternalAccount::Oidc { .. } => format!(
+                "本地已存在用户名为 {username} 的账号，需要管理员为其关联单点登录身份后才能使用单点登录"
+            ),
+            ExternalAccount::Ldap { .. } => {
+                format!("本地已存在用户名为 {username} 的账号，不能通过 LDAP 登录")
+            }
+        }));
+    }
+    let ExternalAccount::Oidc {
+        issuer, subject, ..
+    } = account
+    else {
+        return Ok(Some(user));
+    };
+    let mut active: job_user::ActiveModel = user.into();
+    active.oidc_issuer = Set(Some(issuer.to_string()));
+    active.oidc_subject = Set(Some(subject.to_string()));
+    Ok(Some(active.update(conn).await?))
+}
+
+/// 同名账号能否由外部身份源直接使用。
+fn adoptable(user: &job_user::Model, source: AuthSource) -> bool {
+    user.auth_source == source.as_str()
+        && (source != AuthSource::Oidc || user.oidc_subject.is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(auth_source: AuthSource, oidc_subject: Option<&str>) -> job_user::Model {
+        job_user::Model {
+            id: 1,
+            username: "alice".into(),
+            password: "!placeholder".into(),
+            token: None,
+            role: 0,
+            permission: None,
+            auth_source: auth_source.as_str().into(),
+            oidc_issuer: oidc_subject.map(|_| "https://idp.example.com".into()),
+            oidc_subject: oidc_subject.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn never_adopts_accounts_from_other_sources() {
+        let local = user(AuthSource::Local, None);
+        assert!(!adoptable(&local, AuthSource::Ldap));
+        assert!(!adoptable(&local, AuthSource::Oidc));
+        assert!(!adoptable(&user(AuthSource::Oidc, None), AuthSource::Ldap));
+        assert!(!adoptable(&user(AuthSource::Ldap, None), AuthSource::Oidc));
+        assert!(adoptable(&user(AuthSource::Ldap, None), AuthSource::Ldap));
+    }
+
+    #[test]
+    fn oidc_adopts_only_accounts_without_identity() {
+        assert!(adoptable(&user(AuthSource::Oidc, None), AuthSource::Oidc));
+        assert!(!adoptable(
+            &user(AuthSource::Oidc, Some("user-123")),
+            AuthSource::Oidc
+        ));
+    }
+}
@@ -1,6 +1,10 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use anyhow::{anyhow, Context};
+use ipnet::IpNet;
 use serde::Deserialize;
 use url::Url;
 
@@ -14,6 +18,18 @@ pub struct Settings {
     #[serde(default)]
     pub executor: ExecutorSettings,
     pub security: SecuritySettings,
+    #[serde(default)]
+    pub oidc: OidcSettings,
+    #[serde(default)]
+    pub ldap: LdapSettings,
+    #[serde(default)]
+    pub audit: AuditSettings,
+    #[serde(default)]
+    pub glue: GlueSettings,
+    #[serde(default)]
+    pub log_archive: LogArchiveSettings,
+    #[serde(default)]
+    pub job_trash: JobTrashSettings,
 }
 
 impl Settings {
@@ -122,6 +138,292 @@ pub struct DatabaseSettings {
 #[derive(Debug, Clone, Deserialize)]
 pub struct SecuritySettings {
     pub token_ttl_minutes: i64,
+    /// 要求管理员账号必须启用 TOTP 两步验证
+    #[serde(default)]
+    pub require_admin_totp: bool,
+    /// 写入身份验证器应用的签发方名称
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+    #[serde(default)]
+    pub login_throttle: LoginThrottleSettings,
+    #[serde(default)]
+    pub password_policy: PasswordPolicy,
+    /// 管理台会话 Cookie 是否带 `Secure` 属性，仅在纯 HTTP 的本地调试环境中关闭
+    #[serde(default = "default_cookie_secure")]
+    pub cookie_secure: bool,
+    /// 可信反向代理的 IP 或网段，只有来自这些地址的请求才读取 `X-Forwarded-For` / `X-Real-IP`
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
+/// 新密码的强度要求，修改密码与管理员设置密码时均会校验。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_letter: bool,
+    pub require_digit: bool,
+    /// 要求包含字母、数字以外的字符
+    pub require_symbol: bool,
+    /// 禁止密码包含用户名
+    pub reject_username: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_letter: true,
+            require_digit: true,
+            require_symbol: false,
+            reject_username: true,
+        }
+    }
+}
+
+/// 登录失败限流与临时锁定策略。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoginThrottleSettings {
+    /// 同一用户名在统计窗口内允许的连续失败次数，达到后锁定账号
+    pub max_failures_per_user: u32,
+    /// 同一来源 IP 在统计窗口内允许的失败次数，达到后拒绝该 IP 的登录请求
+    pub max_failures_per_ip: u32,
+    /// 失败次数的统计窗口（分钟），超过窗口后重新计数
+    pub failure_window_minutes: i64,
+    /// 锁定时长（分钟）
+    pub lockout_minutes: i64,
+    /// 首次失败后的响应延迟（毫秒），之后每次失败翻倍
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for LoginThrottleSettings {
+    fn default() -> Self {
+        Self {
+            max_failures_per_user: 5,
+            max_failures_per_ip: 20,
+            failure_window_minutes: 15,
+            lockout_minutes: 15,
+            base_delay_ms: 500,
+            max_delay_ms: 8000,
+        }
+    }
+}
+
+impl LoginThrottleSettings {
+    pub fn failure_window(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.failure_window_minutes.max(1))
+    }
+
+    pub fn lockout(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.lockout_minutes.max(1))
+    }
+
+    /// 第 `failures` 次失败后的响应延迟，按 2 的幂增长并封顶。
+    pub fn delay_after(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+        let factor = 1u64 << failures.saturating_sub(1).min(16);
+        Duration::from_millis(
+            self.base_delay_ms
+                .saturating_mul(factor)
+                .min(self.max_delay_ms),
+        )
+    }
+}
+
+fn default_totp_issuer() -> String {
+    "XXL-JOB".into()
+}
+
+fn default_cookie_secure() -> bool {
+    true
+}
+
+impl SecuritySettings {
+    pub fn token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.token_ttl_minutes.max(1))
+    }
+
+    /// `trusted_proxies` 中的条目既可以是单个 IP，也可以是 CIDR 网段，无法解析的条目忽略。
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|entry| {
+            let entry = entry.trim();
+            match entry.parse::<IpNet>() {
+                Ok(net) => net.contains(&ip),
+                Err(_) => entry.parse::<IpAddr>() == Ok(ip),
+            }
+        })
+    }
+}
+
+/// OIDC 单点登录配置，`enabled = false` 时不启用。
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct OidcSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    pub issuer_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub redirect_url: Option<String>,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+    #[serde(default = "default_oidc_username_claim")]
+    pub username_claim: String,
+    #[serde(default = "default_oidc_groups_claim")]
+    pub groups_claim: String,
+    /// 允许的 ID Token 签名算法，留空时取发现文档中声明的非对称算法，默认 RS256；
+    /// HS256/HS384/HS512 只有在此显式配置时才会接受
+    #[serde(default)]
+    pub id_token_signing_algs: Vec<String>,
+    /// ID Token 中属于这些分组的用户映射为管理员
+    #[serde(default)]
+    pub admin_groups: Vec<String>,
+    /// ID Token 分组到执行器角色的映射
+    #[serde(default)]
+    pub group_mappings: Vec<OidcGroupMapping>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcGroupMapping {
+    pub claim: String,
+    pub job_groups: Vec<i32>,
+    pub role: String,
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".into(), "profile".into(), "email".into()]
+}
+
+fn default_oidc_username_claim() -> String {
+    "preferred_username".into()
+}
+
+fn default_oidc_groups_claim() -> String {
+    "groups".into()
+}
+
+impl OidcSettings {
+    pub fn issuer_url(&self) -> anyhow::Result<&str> {
+        required_setting(self.issuer_url.as_deref(), "oidc.issuer_url")
+    }
+
+    pub fn client_id(&self) -> anyhow::Result<&str> {
+        required_setting(self.client_id.as_deref(), "oidc.client_id")
+    }
+
+    pub fn client_secret(&self) -> anyhow::Result<&str> {
+        required_setting(self.client_secret.as_deref(), "oidc.client_secret")
+    }
+
+    pub fn redirect_url(&self) -> anyhow::Result<&str> {
+        required_setting(self.redirect_url.as_deref(), "oidc.redirect_url")
+    }
+}
+
+/// LDAP / Active Directory 登录配置，`enabled = false` 时仅使用本地账号。
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 例如 `ldap://ldap.example.com:389` 或 `ldaps://ad.example.com:636`
+    pub url: Option<String>,
+    #[serde(default)]
+    pub starttls: bool,
+    /// 直接绑定时使用的 DN 模板，如 `uid={username},ou=people,dc=example,dc=com`
+    pub user_dn_template: Option<String>,
+    /// 未配置 DN 模板时，先以该账号绑定搜索用户 DN
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    pub user_base_dn: Option<String>,
+    #[serde(default = "default_ldap_user_filter")]
+    pub user_filter: String,
+    /// 用户条目上记录所属分组的属性，Active Directory 为 `memberOf`
+    #[serde(default = "default_ldap_group_attribute")]
+    pub group_attribute: String,
+    /// 配置后额外在该节点下按 `group_filter` 搜索用户所属分组
+    pub group_base_dn: Option<String>,
+    #[serde(default = "default_ldap_group_filter")]
+    pub group_filter: String,
+    #[serde(default = "default_ldap_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// 属于这些分组（DN 或 CN）的用户映射为管理员
+    #[serde(default)]
+    pub admin_groups: Vec<String>,
+    /// LDAP 分组到执行器角色的映射
+    #[serde(default)]
+    pub group_mappings: Vec<LdapGroupMapping>,
+    /// LDAP 认证失败或不可用时，是否继续校验本地 `xxl_job_user` 账号
+    #[serde(default = "default_true")]
+    pub local_fallback: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapGroupMapping {
+    pub group: String,
+    pub job_groups: Vec<i32>,
+    pub role: String,
+}
+
+impl Default for LdapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            starttls: false,
+            user_dn_template: None,
+            bind_dn: None,
+            bind_password: None,
+            user_base_dn: None,
+            user_filter: default_ldap_user_filter(),
+            group_attribute: default_ldap_group_attribute(),
+            group_base_dn: None,
+            group_filter: default_ldap_group_filter(),
+            timeout_seconds: default_ldap_timeout_seconds(),
+            admin_groups: Vec::new(),
+            group_mappings: Vec::new(),
+            local_fallback: true,
+        }
+    }
+}
+
+fn default_ldap_user_filter() -> String {
+    "(uid={username})".into()
+}
+
+fn default_ldap_group_attribute() -> String {
+    "memberOf".into()
+}
+
+fn default_ldap_group_filter() -> String {
+    "(member={dn})".into()
+}
+
+fn default_ldap_timeout_seconds() -> u64 {
+    5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl LdapSettings {
+    pub fn url(&self) -> anyhow::Result<&str> {
+        required_setting(self.url.as_deref(), "ldap.url")
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.max(1))
+    }
+}
+
+fn required_setting<'a>(value: Option<&'a str>, name: &str) -> anyhow::Result<&'a str> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("缺少配置项 `{name}`"))
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -129,6 +431,12 @@ pub struct ExecutorSettings {
     pub access_token: Option<String>,
     #[serde(default)]
     pub timeout_seconds: Option<u64>,
+    /// 实时查看日志时轮询执行器的间隔（毫秒）
+    #[serde(default)]
+    pub log_tail_interval_millis: Option<u64>,
+    /// 单个实时日志流的最长持续时间（分钟）
+    #[serde(default)]
+    pub log_tail_max_minutes: Option<u64>,
 }
 
 impl ExecutorSettings {
@@ -147,6 +455,188 @@ impl ExecutorSettings {
     pub fn timeout(&self) -> Duration {
         Duration::from_secs(self.timeout_seconds())
     }
+
+    pub fn log_tail_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.log_tail_interval_millis
+                .unwrap_or(1000)
+                .clamp(200, 10_000),
+        )
+    }
+
+    pub fn log_tail_max_duration(&self) -> Duration {
+        Duration::from_secs(self.log_tail_max_minutes.unwrap_or(30).clamp(1, 720) * 60)
+    }
+}
+
+/// 操作审计记录的保留策略。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// 审计记录保留天数，0 表示永久保留
+    pub retention_days: u32,
+    /// 清理过期记录的间隔（小时）
+    pub cleanup_interval_hours: u64,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 180,
+            cleanup_interval_hours: 24,
+        }
+    }
+}
+
+impl AuditSettings {
+    pub fn retention(&self) -> Option<chrono::Duration> {
+        (self.retention_days > 0).then(|| chrono::Duration::days(self.retention_days.into()))
+    }
+
+    pub fn cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.cleanup_interval_hours.max(1) * 3600)
+    }
+}
+
+/// 任务回收站配置。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobTrashSettings {
+    /// 删除的任务在回收站中保留的天数，0 表示删除时立即彻底删除
+    pub retention_days: u32,
+    /// 彻底删除过期任务的间隔（小时）
+    pub purge_interval_hours: u64,
+}
+
+impl Default for JobTrashSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 7,
+            purge_interval_hours: 1,
+        }
+    }
+}
+
+impl JobTrashSettings {
+    pub fn retention(&self) -> Option<chrono::Duration> {
+        (self.retention_days > 0).then(|| chrono::Duration::days(self.retention_days.into()))
+    }
+
+    pub fn purge_interval(&self) -> Duration {
+        Duration::from_secs(self.purge_interval_hours.max(1) * 3600)
+    }
+}
+
+/// GLUE 在线编辑相关配置。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GlueSettings {
+    /// 每个任务保留的 GLUE 历史版本数，超出时删除最早的版本，0 表示不限制
+    pub max_versions: u32,
+    /// 保存前使用本机解释器做语法检查，存在语法错误时拒绝保存
+    pub syntax_check: bool,
+    /// 单次语法检查的超时时间（秒）
+    pub syntax_check_timeout_seconds: u64,
+    /// 是否检查 Groovy 语法，需要本机安装 `groovy`，默认关闭
+    pub groovy_syntax_check: bool,
+}
+
+impl Default for GlueSettings {
+    fn default() -> Self {
+        Self {
+            max_versions: 30,
+            syntax_check: true,
+            syntax_check_timeout_seconds: 10,
+            groovy_syntax_check: false,
+        }
+    }
+}
+
+impl GlueSettings {
+    pub fn syntax_check_timeout(&self) -> Duration {
+        Duration::from_secs(self.syntax_check_timeout_seconds.clamp(1, 60))
+    }
+}
+
+/// 执行日志归档配置。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LogArchiveSettings {
+    pub enabled: bool,
+    pub backend: LogArchiveBackend,
+    /// `local` 后端的归档目录
+    pub local_dir: String,
+    /// 收到执行回调后等待执行器写完日志的时间（秒）
+    pub delay_seconds: u64,
+    /// 单个日志归档的最大原始字节数，超出部分截断
+    pub max_bytes: u64,
+    /// 检查并删除对应调度日志已被清理的归档的间隔（小时）
+    pub cleanup_interval_hours: u64,
+    /// 写入全文索引的日志文本最大字节数，0 表示不索引归档日志
+    pub search_max_bytes: u64,
+    pub s3: S3Settings,
+}
+
+impl Default for LogArchiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            backend: LogArchiveBackend::Local,
+            local_dir: "data/log-archive".into(),
+            delay_seconds: 3,
+            max_bytes: 50 * 1024 * 1024,
+            cleanup_interval_hours: 1,
+            search_max_bytes: 1024 * 1024,
+            s3: S3Settings::default(),
+        }
+    }
+}
+
+impl LogArchiveSettings {
+    pub fn delay(&self) -> Duration {
+        Duration::from_secs(self.delay_seconds.min(300))
+    }
+
+    pub fn cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.cleanup_interval_hours.max(1) * 3600)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogArchiveBackend {
+    Local,
+    S3,
+}
+
+/// S3 兼容对象存储（AWS S3、MinIO 等）。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct S3Settings {
+    /// 服务地址，例如 MinIO 的 `http://127.0.0.1:9000`
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// 对象 Key 前缀
+    pub prefix: String,
+    /// 使用 `endpoint/bucket/key` 路径风格访问，MinIO 需要开启
+    pub path_style: bool,
+}
+
+impl Default for S3Settings {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:9000".into(),
+            region: "us-east-1".into(),
+            bucket: "xxl-job-logs".into(),
+            access_key: String::new(),
+            secret_key: String::new(),
+            prefix: "logs/".into(),
+            path_style: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
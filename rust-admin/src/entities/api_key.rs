@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_api_key")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub account_id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub job_groups: String,
+    pub role: String,
+    #[sea_orm(column_type = "DateTime")]
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    #[sea_orm(column_type = "DateTime")]
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    #[sea_orm(column_type = "DateTime")]
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub last_used_ip: Option<String>,
+    #[sea_orm(column_type = "DateTime")]
+    pub add_time: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::service_account::Entity",
+        from = "Column::AccountId",
+        to = "super::service_account::Column::Id"
+    )]
+    ServiceAccount,
+}
+
+impl Related<super::service_account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ServiceAccount.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
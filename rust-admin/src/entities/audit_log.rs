@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub operator_type: String,
+    pub operator_id: i32,
+    pub operator_name: String,
+    pub session_id: Option<i64>,
+    pub api_key_id: Option<i64>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    #[sea_orm(column_type = "Text")]
+    pub before_data: Option<String>,
+    #[sea_orm(column_type = "Text")]
+    pub after_data: Option<String>,
+    #[sea_orm(column_type = "Text")]
+    pub diff: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[sea_orm(column_type = "DateTime")]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -8,6 +8,7 @@ pub struct Model {
     pub id: i32,
     pub job_group: i32,
     pub job_desc: String,
+    pub job_key: Option<String>,
     #[sea_orm(column_type = "DateTime")]
     pub add_time: Option<chrono::NaiveDateTime>,
     #[sea_orm(column_type = "DateTime")]
@@ -29,12 +30,15 @@ pub struct Model {
     #[sea_orm(column_type = "DateTime")]
     pub glue_updatetime: Option<chrono::NaiveDateTime>,
     pub child_jobid: Option<String>,
+    /// 复制来源任务 ID
+    pub cloned_from: Option<i32>,
     #[sea_orm(column_type = "TinyInteger")]
     pub trigger_status: i8,
     pub trigger_last_time: i64,
     pub trigger_next_time: i64,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
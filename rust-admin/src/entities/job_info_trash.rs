@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_info_trash")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub job_id: i32,
+    pub job_group: i32,
+    pub job_desc: String,
+    #[sea_orm(column_type = "Text")]
+    pub snapshot: String,
+    pub deleted_by: String,
+    #[sea_orm(column_type = "DateTime")]
+    pub deleted_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub purge_after: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
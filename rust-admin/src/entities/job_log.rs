@@ -23,6 +23,7 @@ pub struct Model {
     pub handle_msg: Option<String>,
     #[sea_orm(column_type = "TinyInteger")]
     pub alarm_status: i8,
+    pub rerun_of: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
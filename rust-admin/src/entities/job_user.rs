@@ -12,6 +12,9 @@ pub struct Model {
     #[sea_orm(column_type = "TinyInteger")]
     pub role: i8,
     pub permission: Option<String>,
+    pub auth_source: String,
+    pub oidc_issuer: Option<String>,
+    pub oidc_subject: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
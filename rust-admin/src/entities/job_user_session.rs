@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_user_session")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub user_id: i32,
+    pub token_hash: String,
+    #[sea_orm(column_type = "DateTime")]
+    pub issued_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub expires_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
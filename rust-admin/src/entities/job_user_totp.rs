@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_user_totp")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i32,
+    pub secret: String,
+    #[sea_orm(column_type = "TinyInteger")]
+    pub enabled: i8,
+    pub last_used_step: Option<i64>,
+    #[sea_orm(column_type = "DateTime")]
+    pub add_time: Option<chrono::NaiveDateTime>,
+    #[sea_orm(column_type = "DateTime")]
+    pub update_time: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
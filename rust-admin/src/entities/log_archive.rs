@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_log_archive")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub log_id: i64,
+    pub storage: String,
+    pub object_key: String,
+    pub original_size: i64,
+    pub compressed_size: i64,
+    pub line_count: i32,
+    #[sea_orm(column_type = "TinyInteger")]
+    pub truncated: i8,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub log_text: Option<String>,
+    #[sea_orm(column_type = "DateTime")]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
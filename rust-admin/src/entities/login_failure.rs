@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_login_failure")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub scope: String,
+    pub subject: String,
+    pub failure_count: i32,
+    #[sea_orm(column_type = "DateTime")]
+    pub first_failure_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub last_failure_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub locked_until: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "xxl_job_login_lockout")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub scope: String,
+    pub subject: String,
+    pub client_ip: Option<String>,
+    pub failure_count: i32,
+    #[sea_orm(column_type = "DateTime")]
+    pub locked_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub locked_until: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "DateTime")]
+    pub unlocked_at: Option<chrono::NaiveDateTime>,
+    pub unlocked_by: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
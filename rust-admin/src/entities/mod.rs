@@ -1,7 +1,19 @@
+pub mod api_key;
+pub mod audit_log;
 pub mod job_group;
 pub mod job_info;
+pub mod job_info_revision;
+pub mod job_info_trash;
 pub mod job_log;
 pub mod job_log_report;
 pub mod job_logglue;
 pub mod job_registry;
 pub mod job_user;
+pub mod job_user_group_role;
+pub mod job_user_recovery_code;
+pub mod job_user_session;
+pub mod job_user_totp;
+pub mod log_archive;
+pub mod login_failure;
+pub mod login_lockout;
+pub mod service_account;
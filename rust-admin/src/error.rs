@@ -18,6 +18,8 @@ pub enum AppError {
     NotFound(String),
     #[error("资源冲突: {0}")]
     Conflict(String),
+    #[error("请求过于频繁: {0}")]
+    TooManyRequests(String),
     #[error(transparent)]
     DbError(#[from] DbErr),
     #[error("服务器内部错误")]
@@ -44,6 +46,7 @@ impl IntoResponse for AppError {
             AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::DbError(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -0,0 +1,150 @@
+pub mod syntax;
+
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+use crate::entities::job_logglue;
+use crate::error::AppResult;
+
+/// 统一 diff 中每个变更块前后保留的上下文行数。
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// `equal` / `insert` / `delete`
+    pub tag: &'static str,
+    /// 在旧版本中的行号（从 1 开始），新增行为空
+    pub old_line: Option<usize>,
+    /// 在新版本中的行号（从 1 开始），删除行为空
+    pub new_line: Option<usize>,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineDiff {
+    pub insertions: usize,
+    pub deletions: usize,
+    /// 统一 diff 格式文本，便于直接展示或复制
+    pub unified: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// 按行对比两段 GLUE 源码。
+pub fn line_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> LineDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let mut insertions = 0;
+    let mut deletions = 0;
+    let lines = diff
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Insert => {
+                    insertions += 1;
+                    "insert"
+                }
+                ChangeTag::Delete => {
+                    deletions += 1;
+                    "delete"
+                }
+            };
+            DiffLine {
+                tag,
+                old_line: change.old_index().map(|index| index + 1),
+                new_line: change.new_index().map(|index| index + 1),
+                content: change
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string(),
+            }
+        })
+        .collect();
+    let unified = diff
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(old_name, new_name)
+        .to_string();
+
+    LineDiff {
+        insertions,
+        deletions,
+        unified,
+        lines,
+    }
+}
+
+/// 只保留任务最新的 `keep` 个 GLUE 历史版本，返回删除的数量；`keep` 为 0 时不清理。
+pub async fn prune_versions<C: ConnectionTrait>(
+    conn: &C,
+    job_id: i32,
+    keep: u32,
+) -> AppResult<u64> {
+    if keep == 0 {
+        return Ok(0);
+    }
+    let oldest_kept = job_logglue::Entity::find()
+        .filter(job_logglue::Column::JobId.eq(job_id))
+        .order_by_desc(job_logglue::Column::Id)
+        .offset(u64::from(keep - 1))
+        .one(conn)
+        .await?;
+    let Some(oldest_kept) = oldest_kept else {
+        return Ok(0);
+    };
+    let deleted = job_logglue::Entity::delete_many()
+        .filter(job_logglue::Column::JobId.eq(job_id))
+        .filter(job_logglue::Column::Id.lt(oldest_kept.id))
+        .exec(conn)
+        .await?;
+    Ok(deleted.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_diff_numbers_changed_lines() {
+        let old = "#!/bin/bash\necho start\nexit 0\n";
+        let new = "#!/bin/bash\necho begin\nexit 0\n";
+        let diff = line_diff(old, new, "v1", "current");
+
+        assert_eq!(diff.insertions, 1);
+        assert_eq!(diff.deletions, 1);
+        let changed: Vec<_> = diff
+            .lines
+            .iter()
+            .filter(|line| line.tag != "equal")
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                &DiffLine {
+                    tag: "delete",
+                    old_line: Some(2),
+                    new_line: None,
+                    content: "echo start".into(),
+                },
+                &DiffLine {
+                    tag: "insert",
+                    old_line: None,
+                    new_line: Some(2),
+                    content: "echo begin".into(),
+                },
+            ]
+        );
+        assert!(diff.unified.starts_with("--- v1\n+++ current\n"));
+        assert!(diff.unified.contains("-echo start\n+echo begin\n"));
+    }
+
+    #[test]
+    fn line_diff_of_identical_sources_is_empty() {
+        let diff = line_diff("print(1)\n", "print(1)\n", "a", "b");
+        assert_eq!((diff.insertions, diff.deletions), (0, 0));
+        assert!(diff.unified.is_empty());
+    }
+}
@@ -0,0 +1,526 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::config::GlueSettings;
+use tracing::warn;
+
+/// 支持在线编辑的 GLUE 脚本类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlueType {
+    Groovy,
+    Shell,
+    Python,
+    Php,
+    NodeJs,
+    PowerShell,
+}
+
+impl GlueType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "GLUE_GROOVY" => Some(GlueType::Groovy),
+            "GLUE_SHELL" => Some(GlueType::Shell),
+            "GLUE_PYTHON" => Some(GlueType::Python),
+            "GLUE_PHP" => Some(GlueType::Php),
+            "GLUE_NODEJS" => Some(GlueType::NodeJs),
+            "GLUE_POWERSHELL" => Some(GlueType::PowerShell),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GlueType::Groovy => "GLUE_GROOVY",
+            GlueType::Shell => "GLUE_SHELL",
+            GlueType::Python => "GLUE_PYTHON",
+            GlueType::Php => "GLUE_PHP",
+            GlueType::NodeJs => "GLUE_NODEJS",
+            GlueType::PowerShell => "GLUE_POWERSHELL",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            GlueType::Groovy => "glue.groovy",
+            GlueType::Shell => "glue.sh",
+            GlueType::Python => "glue.py",
+            GlueType::Php => "glue.php",
+            GlueType::NodeJs => "glue.js",
+            GlueType::PowerShell => "glue.ps1",
+        }
+    }
+
+    /// 按优先顺序尝试的检查命令，使用第一个本机可用的。
+    fn checkers(self) -> &'static [Checker] {
+        match self {
+            GlueType::Groovy => &[Checker {
+                program: "groovy",
+                args: groovy_args,
+                parse: parse_tab_separated,
+            }],
+            GlueType::Shell => &[Checker {
+                program: "bash",
+                args: |file, _| vec!["-n".into(), path_arg(file)],
+                parse: parse_bash,
+            }],
+            GlueType::Python => &[
+                Checker {
+                    program: "python3",
+                    args: python_args,
+                    parse: parse_tab_separated,
+                },
+                Checker {
+                    program: "python",
+                    args: python_args,
+                    parse: parse_tab_separated,
+                },
+            ],
+            GlueType::Php => &[Checker {
+                program: "php",
+                args: |file, _| vec!["-l".into(), path_arg(file)],
+                parse: parse_php,
+            }],
+            GlueType::NodeJs => &[Checker {
+                program: "node",
+                args: |file, _| vec!["--check".into(), path_arg(file)],
+                parse: parse_node,
+            }],
+            GlueType::PowerShell => &[
+                Checker {
+                    program: "pwsh",
+                    args: powershell_args,
+                    parse: parse_tab_separated,
+                },
+                Checker {
+                    program: "powershell",
+                    args: powershell_args,
+                    parse: parse_tab_separated,
+                },
+            ],
+        }
+    }
+}
+
+/// 一条语法错误，行列号从 1 开始，检查器无法给出位置时为空。
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxError {
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "第 {line} 行第 {column} 列: {}", self.message),
+            (Some(line), None) => write!(f, "第 {line} 行: {}", self.message),
+            _ => f.write_str(&self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxCheck {
+    pub glue_type: String,
+    /// 是否实际执行了检查；本机缺少对应解释器或检查超时时为 false
+    pub checked: bool,
+    /// 实际使用的检查命令
+    pub checker: Option<String>,
+    /// 未执行检查的原因
+    pub skipped_reason: Option<String>,
+    pub errors: Vec<SyntaxError>,
+}
+
+impl SyntaxCheck {
+    fn skipped(glue_type: GlueType, checker: Option<&str>, reason: String) -> Self {
+        Self {
+            glue_type: glue_type.as_str().into(),
+            checked: false,
+            checker: checker.map(str::to_string),
+            skipped_reason: Some(reason),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+struct Checker {
+    program: &'static str,
+    /// 根据脚本文件与临时工作目录生成命令参数
+    args: fn(&Path, &Path) -> Vec<String>,
+    parse: fn(&str) -> Vec<SyntaxError>,
+}
+
+/// 使用本机解释器对 GLUE 源码做语法检查，不会执行脚本本身。
+///
+/// 源码写入独立的临时目录，检查结束后删除；找不到任何可用解释器时返回未检查的结果。
+/// Groovy 需要通过 `glue.groovy_syntax_check` 显式开启。
+pub async fn check(glue_type: GlueType, source: &str, settings: &GlueSettings) -> SyntaxCheck {
+    if glue_type == GlueType::Groovy && !settings.groovy_syntax_check {
+        return SyntaxCheck::skipped(
+            glue_type,
+            None,
+            "Groovy 语法检查未开启（glue.groovy_syntax_check）".into(),
+        );
+    }
+    let timeout = settings.syntax_check_timeout();
+    let workdir = std::env::temp_dir().join(format!("xxl-glue-{}", uuid::Uuid::new_v4()));
+    let result = run_checkers(glue_type, source, &workdir, timeout).await;
+    if let Err(err) = tokio::fs::remove_dir_all(&workdir).await {
+        if err.kind() != ErrorKind::NotFound {
+            warn!(path = %workdir.display(), error = %err, "清理 GLUE 语法检查临时目录失败");
+        }
+    }
+    result
+}
+
+async fn run_checkers(
+    glue_type: GlueType,
+    source: &str,
+    workdir: &Path,
+    timeout: Duration,
+) -> SyntaxCheck {
+    let file: PathBuf = workdir.join(glue_type.file_name());
+    let prepared = async {
+        tokio::fs::create_dir_all(workdir).await?;
+        tokio::fs::write(&file, source).await
+    };
+    if let Err(err) = prepared.await {
+        warn!(error = %err, "写入 GLUE 语法检查临时文件失败");
+        return SyntaxCheck::skipped(glue_type, None, format!("写入临时文件失败: {err}"));
+    }
+
+    for checker in glue_type.checkers() {
+        let child = Command::new(checker.program)
+            .args((checker.args)(&file, workdir))
+            .current_dir(workdir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let child = match child {
+            Ok(child) => child,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                warn!(program = checker.program, error = %err, "启动 GLUE 语法检查命令失败");
+                return SyntaxCheck::skipped(
+                    glue_type,
+                    Some(checker.program),
+                    format!("启动 {} 失败: {err}", checker.program),
+                );
+            }
+        };
+
+        let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(err)) => {
+                return SyntaxCheck::skipped(
+                    glue_type,
+                    Some(checker.program),
+                    format!("执行 {} 失败: {err}", checker.program),
+                );
+            }
+            Err(_) => {
+                return SyntaxCheck::skipped(
+                    glue_type,
+                    Some(checker.program),
+                    format!("{} 语法检查超时", checker.program),
+                );
+            }
+        };
+
+        let mut errors = Vec::new();
+        if !output.status.success() {
+            let text = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            // 隐藏临时目录路径，只保留脚本文件名
+            let text = text.replace(&format!("{}/", workdir.display()), "");
+            errors = (checker.parse)(&text);
+            if errors.is_empty() {
+                errors.push(SyntaxError {
+                    line: None,
+                    column: None,
+                    message: first_lines(&text, 5),
+                });
+            }
+        }
+        return SyntaxCheck {
+            glue_type: glue_type.as_str().into(),
+            checked: true,
+            checker: Some(checker.program.into()),
+            skipped_reason: None,
+            errors,
+        };
+    }
+
+    let programs: Vec<_> = glue_type.checkers().iter().map(|c| c.program).collect();
+    SyntaxCheck::skipped(
+        glue_type,
+        None,
+        format!("本机未安装 {}，已跳过语法检查", programs.join(" / ")),
+    )
+}
+
+fn path_arg(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn python_args(file: &Path, _: &Path) -> Vec<String> {
+    const SCRIPT: &str = "import io, sys\n\
+source = io.open(sys.argv[1], encoding='utf-8').read()\n\
+try:\n    compile(source, 'glue.py', 'exec')\n\
+except SyntaxError as err:\n    print('%s\\t%s\\t%s' % (err.lineno or '', err.offset or '', err.msg))\n    sys.exit(1)\n";
+    vec!["-c".into(), SCRIPT.into(), path_arg(file)]
+}
+
+fn powershell_args(file: &Path, _: &Path) -> Vec<String> {
+    let path = path_arg(file).replace('\'', "''");
+    let script = format!(
+        "$errors = $null\n\
+[void][System.Management.Automation.Language.Parser]::ParseFile('{path}', [ref]$null, [ref]$errors)\n\
+foreach ($e in $errors) {{ \"{{0}}`t{{1}}`t{{2}}\" -f $e.Extent.StartLineNumber, $e.Extent.StartColumnNumber, $e.Message }}\n\
+if ($errors) {{ exit 1 }}"
+    );
+    vec![
+        "-NoProfile".into(),
+        "-NonInteractive".into(),
+        "-Command".into(),
+        script,
+    ]
+}
+
+/// 只编译到 `CONVERSION` 阶段（语法解析并生成 AST）：不解析类、不运行局部 AST 变换（`@ASTTest` 等），
+/// 变换加载器与 Groovy 自身的类路径隔离，`@Grab` 等全局变换不会被发现，检查过程中不会执行脚本中的代码。
+fn groovy_args(file: &Path, _: &Path) -> Vec<String> {
+    const SCRIPT: &str = r#"import org.codehaus.groovy.control.*
+import org.codehaus.groovy.control.messages.SyntaxErrorMessage
+def config = new CompilerConfiguration()
+config.disabledGlobalASTTransformations = ['groovy.grape.GrabAnnotationTransformation'] as Set
+def isolated = new GroovyClassLoader(ClassLoader.systemClassLoader.parent, config)
+def unit = new CompilationUnit(config, null, isolated, isolated)
+unit.addSource(new File(args[0]))
+try {
+    unit.compile(Phases.CONVERSION)
+} catch (MultipleCompilationErrorsException e) {
+    e.errorCollector.errors.each { message ->
+        if (message instanceof SyntaxErrorMessage) {
+            def cause = message.cause
+            println "${cause.line}\t${cause.startColumn}\t${cause.originalMessage}"
+        } else {
+            def writer = new StringWriter()
+            message.write(new PrintWriter(writer))
+            println "\t\t${writer.toString().trim().replace('\n', ' ')}"
+        }
+    }
+    System.exit(1)
+}
+"#;
+    vec!["-e".into(), SCRIPT.into(), path_arg(file)]
+}
+
+fn first_lines(text: &str, count: usize) -> String {
+    let lines: Vec<_> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .take(count)
+        .collect();
+    if lines.is_empty() {
+        "语法检查未通过".into()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn push_unique(errors: &mut Vec<SyntaxError>, error: SyntaxError) {
+    if !errors.contains(&error) {
+        errors.push(error);
+    }
+}
+
+/// `行号\t列号\t信息`，由内置的 Python / PowerShell 检查脚本输出。
+fn parse_tab_separated(output: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    for line in output.lines() {
+        let mut parts = line.splitn(3, '\t');
+        if let (Some(row), Some(column), Some(message)) = (parts.next(), parts.next(), parts.next())
+        {
+            push_unique(
+                &mut errors,
+                SyntaxError {
+                    line: row.trim().parse().ok(),
+                    column: column.trim().parse().ok(),
+                    message: message.trim().to_string(),
+                },
+            );
+        }
+    }
+    errors
+}
+
+/// `glue.sh: line 3: syntax error near unexpected token ...`；紧随其后回显出错代码的行会被忽略。
+fn parse_bash(output: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    for line in output.lines() {
+        let Some((_, rest)) = line.split_once(": line ") else {
+            continue;
+        };
+        let Some((row, message)) = rest.split_once(": ") else {
+            continue;
+        };
+        let Ok(row) = row.parse() else {
+            continue;
+        };
+        if message.starts_with('`') && message.ends_with('\'') {
+            continue;
+        }
+        push_unique(
+            &mut errors,
+            SyntaxError {
+                line: Some(row),
+                column: None,
+                message: message.trim().to_string(),
+            },
+        );
+    }
+    errors
+}
+
+/// `PHP Parse error:  syntax error, unexpected ... in glue.php on line 4`
+fn parse_php(output: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    for line in output.lines() {
+        let Some((head, row)) = line.rsplit_once(" on line ") else {
+            continue;
+        };
+        let Ok(row) = row.trim().parse() else {
+            continue;
+        };
+        let message = head
+            .rsplit_once(" in ")
+            .map_or(head, |(message, _)| message);
+        let message = message.trim_start_matches("PHP ").trim();
+        push_unique(
+            &mut errors,
+            SyntaxError {
+                line: Some(row),
+                column: None,
+                message: message.split_whitespace().collect::<Vec<_>>().join(" "),
+            },
+        );
+    }
+    errors
+}
+
+/// `node --check` 输出 `glue.js:3`、出错代码、`^` 标记，随后是 `SyntaxError: ...`。
+fn parse_node(output: &str) -> Vec<SyntaxError> {
+    let lines: Vec<&str> = output.lines().collect();
+    let Some(index) = lines.iter().position(|line| line.starts_with("glue.js:")) else {
+        return Vec::new();
+    };
+    let row = lines[index]
+        .trim_start_matches("glue.js:")
+        .trim()
+        .parse()
+        .ok();
+    let column = lines
+        .get(index + 2)
+        .and_then(|marker| marker.find('^'))
+        .map(|offset| offset as u32 + 1);
+    let message = lines
+        .iter()
+        .find(|line| line.contains("Error: "))
+        .map(|line| line.trim().to_string())
+        .unwrap_or_else(|| "语法检查未通过".into());
+    vec![SyntaxError {
+        line: row,
+        column,
+        message,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: u32, column: Option<u32>, message: &str) -> SyntaxError {
+        SyntaxError {
+            line: Some(line),
+            column,
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn parses_bash_errors_without_code_echo() {
+        let output = "glue.sh: line 3: syntax error near unexpected token `fi'\n\
+                      glue.sh: line 3: `fi fi'\n";
+        assert_eq!(
+            parse_bash(output),
+            vec![error(3, None, "syntax error near unexpected token `fi'")]
+        );
+    }
+
+    #[test]
+    fn parses_node_errors_with_column() {
+        let output = "glue.js:2\n  let = 1;\n      ^\n\nSyntaxError: Unexpected token '='\n    at wrapSafe\n";
+        assert_eq!(
+            parse_node(output),
+            vec![error(2, Some(7), "SyntaxError: Unexpected token '='")]
+        );
+    }
+
+    #[test]
+    fn parses_php_errors() {
+        let php = "PHP Parse error:  syntax error, unexpected end of file in glue.php on line 4\n\
+                   Parse error: syntax error, unexpected end of file in glue.php on line 4\n\
+                   Errors parsing glue.php\n";
+        assert_eq!(
+            parse_php(php),
+            vec![error(
+                4,
+                None,
+                "Parse error: syntax error, unexpected end of file"
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_tab_separated_checker_output() {
+        assert_eq!(
+            parse_tab_separated("1\t9\tinvalid syntax\n"),
+            vec![error(1, Some(9), "invalid syntax")]
+        );
+    }
+
+    #[tokio::test]
+    async fn valid_shell_source_passes() {
+        // 本机未安装 bash 时结果为未检查，同样视为通过
+        let result = check(GlueType::Shell, "echo ok\n", &GlueSettings::default()).await;
+        assert!(result.passed());
+        assert_eq!(result.checked, result.skipped_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn groovy_check_is_opt_in() {
+        let source = "@groovy.transform.ASTTest(value = { System.exit(1) })\nclass A {}\n";
+        let result = check(GlueType::Groovy, source, &GlueSettings::default()).await;
+        assert!(!result.checked);
+        assert!(result.checker.is_none());
+        assert!(result.passed());
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::job_info;
+use crate::glue::syntax::GlueType;
+
+/// 当前支持的配置文件版本。
+pub const CONFIG_VERSION: u32 = 1;
+/// 任务标识最大长度，与 `xxl_job_info.job_key` 列一致。
+const MAX_KEY_LEN: usize = 64;
+const BEAN_GLUE_TYPE: &str = "BEAN";
+
+/// 声明式任务配置：按 AppName 与任务标识组织，不包含数据库 ID，可直接纳入版本库管理。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct JobConfig {
+    pub version: u32,
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+}
+
+/// 执行器及其下的任务。导入时按 `appName` 匹配已有执行器，不存在时创建；已有执行器的属性不会被修改。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GroupConfig {
+    pub app_name: String,
+    pub title: String,
+    /// 0 自动注册，1 手动录入
+    #[serde(default)]
+    pub address_type: i8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_list: Option<String>,
+    #[serde(default)]
+    pub jobs: Vec<JobEntry>,
+}
+
+/// 单个任务的定义，字段与任务表单一致；运行状态与调度时间不属于定义，不导出也不导入。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct JobEntry {
+    pub key: String,
+    pub desc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm_email: Option<String>,
+    pub schedule_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_conf: Option<String>,
+    #[serde(default = "default_misfire_strategy")]
+    pub misfire_strategy: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_route_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_handler: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_param: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor_block_strategy: Option<String>,
+    #[serde(default)]
+    pub executor_timeout: i32,
+    #[serde(default)]
+    pub executor_fail_retry_count: i32,
+    #[serde(default = "default_glue_type")]
+    pub glue_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glue_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glue_remark: Option<String>,
+    /// 子任务引用，同一执行器内为 `key`，跨执行器为 `appName/key`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_jobs: Vec<String>,
+}
+
+fn default_misfire_strategy() -> String {
+    "DO_NOTHING".into()
+}
+
+fn default_glue_type() -> String {
+    BEAN_GLUE_TYPE.into()
+}
+
+impl JobEntry {
+    /// 从已有任务生成定义，`child_jobs` 由调用方按子任务 ID 解析。
+    pub fn from_model(job: &job_info::Model, app_name: &str, child_jobs: Vec<String>) -> Self {
+        Self {
+            key: job_key_of(job),
+            desc: job.job_desc.clone(),
+            author: job.author.clone(),
+            alarm_email: job.alarm_email.clone(),
+            schedule_type: job.schedule_type.clone(),
+            schedule_conf: job.schedule_conf.clone(),
+            misfire_strategy: job.misfire_strategy.clone(),
+            executor_route_strategy: job.executor_route_strategy.clone(),
+            executor_handler: job.executor_handler.clone(),
+            executor_param: job.executor_param.clone(),
+            executor_block_strategy: job.executor_block_strategy.clone(),
+            executor_timeout: job.executor_timeout,
+            executor_fail_retry_count: job.executor_fail_retry_count,
+            glue_type: job.glue_type.clone(),
+            glue_source: job.glue_source.clone(),
+            glue_remark: job.glue_remark.clone(),
+            child_jobs,
+        }
+        .normalized(app_name)
+    }
+
+    /// 空字符串视为未设置，子任务引用去掉与所在执行器相同的 AppName 前缀，避免无意义的差异。
+    pub fn normalized(self, app_name: &str) -> Self {
+        let blank = |value: Option<String>| value.filter(|item| !item.trim().is_empty());
+        let mut child_jobs: Vec<String> = self
+            .child_jobs
+            .iter()
+            .filter_map(|raw| JobRef::parse(raw))
+            .map(|item| item.canonical(app_name))
+            .collect();
+        let mut seen = HashSet::new();
+        child_jobs.retain(|item| seen.insert(item.clone()));
+        Self {
+            key: self.key.trim().to_string(),
+            author: blank(self.author),
+            alarm_email: blank(self.alarm_email),
+            schedule_conf: blank(self.schedule_conf),
+            executor_route_strategy: blank(self.executor_route_strategy),
+            executor_handler: blank(self.executor_handler),
+            executor_param: blank(self.executor_param),
+            executor_block_strategy: blank(self.executor_block_strategy),
+            glue_source: blank(self.glue_source),
+            glue_remark: blank(self.glue_remark),
+            child_jobs,
+            ..self
+        }
+    }
+
+    /// 把定义写入任务，不修改子任务（需要先确定全部任务的 ID）与运行状态。
+    pub fn apply(&self, job: &mut job_info::Model) {
+        job.job_key = Some(self.key.clone());
+        job.job_desc = self.desc.clone();
+        job.author = self.author.clone();
+        job.alarm_email = self.alarm_email.clone();
+        job.schedule_type = self.schedule_type.clone();
+        job.schedule_conf = self.schedule_conf.clone();
+        job.misfire_strategy = self.misfire_strategy.clone();
+        job.executor_route_strategy = self.executor_route_strategy.clone();
+        job.executor_handler = self.executor_handler.clone();
+        job.executor_param = self.executor_param.clone();
+        job.executor_block_strategy = self.executor_block_strategy.clone();
+        job.executor_timeout = self.executor_timeout;
+        job.executor_fail_retry_count = self.executor_fail_retry_count;
+        job.glue_type = self.glue_type.clone();
+        job.glue_source = self.glue_source.clone();
+        job.glue_remark = self.glue_remark.clone();
+    }
+
+    /// GLUE 类型或源码是否与任务当前的不同，变化时需要写入 GLUE 历史。
+    pub fn glue_changed(&self, job: &job_info::Model) -> bool {
+        self.glue_type != job.glue_type
+            || self.glue_source.as_deref().unwrap_or("") != job.glue_source.as_deref().unwrap_or("")
+    }
+}
+
+/// 子任务引用。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JobRef {
+    /// 为空时表示与引用方同一执行器
+    pub app_name: Option<String>,
+    pub key: String,
+}
+
+impl JobRef {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let (app_name, key) = match raw.rsplit_once('/') {
+            Some((app_name, key)) => (Some(app_name.trim()), key.trim()),
+            None => (None, raw),
+        };
+        if key.is_empty() || app_name.is_some_and(str::is_empty) {
+            return None;
+        }
+        Some(Self {
+            app_name: app_name.map(str::to_string),
+            key: key.to_string(),
+        })
+    }
+
+    /// 引用方所在执行器为 `current` 时的目标执行器。
+    pub fn app_name_or<'a>(&'a self, current: &'a str) -> &'a str {
+        self.app_name.as_deref().unwrap_or(current)
+    }
+
+    /// 同一执行器内省略 AppName 的规范写法。
+    pub fn canonical(&self, current: &str) -> String {
+        match self.app_name.as_deref() {
+            Some(app_name) if app_name != current => format!("{app_name}/{}", self.key),
+            _ => self.key.clone(),
+        }
+    }
+}
+
+/// 配置文件格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(str::trim).unwrap_or("") {
+            "" | "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Yaml => "application/yaml; charset=utf-8",
+            Self::Json => "application/json; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn render(self, config: &JobConfig) -> Result<String, String> {
+        match self {
+            Self::Yaml => serde_yaml::to_string(config).map_err(|err| err.to_string()),
+            Self::Json => serde_json::to_string_pretty(config).map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn read(self, text: &str) -> Result<JobConfig, String> {
+        match self {
+            Self::Yaml => serde_yaml::from_str(text).map_err(|err| err.to_string()),
+            Self::Json => serde_json::from_str(text).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+/// 未指定任务标识的任务使用的默认值，与迁移脚本的回填规则一致。
+pub fn default_job_key(id: i32) -> String {
+    format!("job-{id}")
+}
+
+/// 任务的标识，迁移前创建且尚未回填的任务使用默认值。
+pub fn job_key_of(job: &job_info::Model) -> String {
+    job.job_key
+        .clone()
+        .unwrap_or_else(|| default_job_key(job.id))
+}
+
+/// 解析 `child_jobid` 中逗号分隔的子任务 ID，忽略无法解析的部分。
+pub fn parse_child_ids(raw: Option<&str>) -> Vec<i32> {
+    let mut ids: Vec<i32> = raw
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|item| item.trim().parse().ok())
+        .collect();
+    ids.dedup();
+    ids
+}
+
+/// 任务标识由字母、数字、`.`、`_`、`-` 组成，以字母或数字开头，且不能包含 `/`。
+pub fn validate_job_key(key: &str) -> Result<(), String> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key
+            .chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphanumeric())
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "任务标识 `{key}` 无效：只能包含字母、数字、`.`、`_`、`-`，以字母或数字开头，且不超过 {MAX_KEY_LEN} 个字符"
+        ))
+    }
+}
+
+impl JobConfig {
+    /// 不依赖数据库的结构校验，返回全部错误。调度表达式与子任务是否存在由调用方继续校验。
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.version != CONFIG_VERSION {
+            errors.push(format!(
+                "不支持的配置版本 {}，当前版本为 {CONFIG_VERSION}",
+                self.version
+            ));
+        }
+        let mut app_names = HashSet::new();
+        for group in &self.groups {
+            let app_name = group.app_name.trim();
+            if app_name.is_empty() {
+                errors.push("执行器 appName 不能为空".into());
+                continue;
+            }
+            if !app_names.insert(app_name) {
+                errors.push(format!("执行器 `{app_name}` 重复出现"));
+            }
+            if group.address_type == 1
+                && group
+                    .address_list
+                    .as_deref()
+                    .unwrap_or("")
+                    .trim()
+                    .is_empty()
+            {
+                errors.push(format!(
+                    "执行器 `{app_name}` 为手动录入时需要提供 addressList"
+                ));
+            }
+            let mut keys = HashSet::new();
+            for job in &group.jobs {
+                let path = format!("{app_name}/{}", job.key);
+                if let Err(err) = validate_job_key(&job.key) {
+                    errors.push(err);
+                } else if !keys.insert(job.key.as_str()) {
+                    errors.push(format!("任务 `{path}` 重复出现"));
+                }
+                if job.desc.trim().is_empty() {
+                    errors.push(format!("任务 `{path}` 的描述不能为空"));
+                }
+                if job.glue_type != BEAN_GLUE_TYPE {
+                    if GlueType::parse(&job.glue_type).is_none() {
+                        errors.push(format!(
+                            "任务 `{path}` 的 GLUE 类型 `{}` 无效",
+                            job.glue_type
+                        ));
+                    } else if job.glue_source.as_deref().unwrap_or("").trim().is_empty() {
+                        errors.push(format!("任务 `{path}` 为 GLUE 模式时需要提供 glueSource"));
+                    }
+                }
+                for child in &job.child_jobs {
+                    if JobRef::parse(child).is_none() {
+                        errors.push(format!("任务 `{path}` 的子任务引用 `{child}` 无效"));
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+version: 1
+groups:
+  - appName: demo-executor
+    title: 示例执行器
+    jobs:
+      - key: daily-report
+        desc: 日报
+        scheduleType: CRON
+        scheduleConf: "0 0 1 * * ?"
+        executorHandler: reportHandler
+        childJobs: [cleanup, "other-executor/notify"]
+      - key: cleanup
+        desc: 清理
+        scheduleType: NONE
+        glueType: GLUE_SHELL
+        glueSource: |
+          #!/bin/bash
+          echo done
+"#;
+
+    #[test]
+    fn reads_yaml_with_defaults_and_round_trips_json() {
+        let config = ConfigFormat::Yaml.read(SAMPLE).unwrap();
+        assert!(config.validate().is_empty());
+        let group = &config.groups[0];
+        assert_eq!(group.address_type, 0);
+        assert_eq!(group.jobs[0].misfire_strategy, "DO_NOTHING");
+        assert_eq!(group.jobs[0].glue_type, "BEAN");
+        assert_eq!(group.jobs[1].glue_type, "GLUE_SHELL");
+
+        let json = ConfigFormat::Json.render(&config).unwrap();
+        assert_eq!(ConfigFormat::Json.read(&json).unwrap(), config);
+        // YAML 是 JSON 的超集，默认格式也能读取 JSON 文件
+        assert_eq!(ConfigFormat::Yaml.read(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let err = ConfigFormat::Yaml
+            .read("version: 1\ngroups: []\nid: 3\n")
+            .unwrap_err();
+        assert!(err.contains("id"), "{err}");
+    }
+
+    #[test]
+    fn reports_structural_errors() {
+        let mut config = ConfigFormat::Yaml.read(SAMPLE).unwrap();
+        config.version = 2;
+        config.groups[0].jobs[1].key = "daily-report".into();
+        config.groups[0].jobs[1].glue_type = "GLUE_RUBY".into();
+        config.groups[0].jobs[0].child_jobs.push("demo/".into());
+        let errors = config.validate();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors[0].contains("版本"));
+        assert!(errors.iter().any(|err| err.contains("重复")));
+        assert!(errors.iter().any(|err| err.contains("GLUE_RUBY")));
+        assert!(errors.iter().any(|err| err.contains("demo/")));
+    }
+
+    #[test]
+    fn validates_job_keys() {
+        assert!(validate_job_key("daily-report.v2_a").is_ok());
+        assert!(validate_job_key("job-12").is_ok());
+        assert!(validate_job_key("").is_err());
+        assert!(validate_job_key("-leading").is_err());
+        assert!(validate_job_key("a/b").is_err());
+        assert!(validate_job_key(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn parses_child_ids() {
+        assert_eq!(parse_child_ids(Some("3, 5,x,,5")), vec![3, 5]);
+        assert!(parse_child_ids(Some("")).is_empty());
+        assert!(parse_child_ids(None).is_empty());
+    }
+
+    #[test]
+    fn parses_and_canonicalizes_job_refs() {
+        let local = JobRef::parse(" cleanup ").unwrap();
+        assert_eq!(local.app_name, None);
+        assert_eq!(local.app_name_or("demo"), "demo");
+
+        let remote = JobRef::parse("other/notify").unwrap();
+        assert_eq!(remote.app_name_or("demo"), "other");
+        assert_eq!(remote.canonical("demo"), "other/notify");
+        assert_eq!(remote.canonical("other"), "notify");
+
+        assert!(JobRef::parse("").is_none());
+        assert!(JobRef::parse("/notify").is_none());
+        assert!(JobRef::parse("other/").is_none());
+    }
+
+    #[test]
+    fn normalizes_blank_fields_and_same_group_refs() {
+        let config = ConfigFormat::Yaml.read(SAMPLE).unwrap();
+        let mut entry = config.groups[0].jobs[0].clone();
+        entry.author = Some("  ".into());
+        entry.child_jobs = vec![
+            "demo-executor/cleanup".into(),
+            "other-executor/notify".into(),
+            "cleanup".into(),
+        ];
+        let entry = entry.normalized("demo-executor");
+        assert_eq!(entry.author, None);
+        // 不相邻的重复引用同样去重，并保持首次出现的顺序
+        assert_eq!(entry.child_jobs, vec!["cleanup", "other-executor/notify"]);
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::Local;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde_json::Value;
+
+use crate::audit;
+use crate::entities::{job_info, job_info_revision};
+use crate::error::{AppError, AppResult};
+
+/// 对比版本时忽略的调度运行期字段，这些字段随调度自动变化，不属于任务定义。
+const RUNTIME_FIELDS: &[&str] = &[
+    "add_time",
+    "update_time",
+    "glue_updatetime",
+    "trigger_status",
+    "trigger_last_time",
+    "trigger_next_time",
+];
+
+/// 任务定义版本的来源。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionAction {
+    /// 启用版本记录前已存在的任务，首次修改前补记的原始定义
+    Baseline,
+    Create,
+    Update,
+    Glue,
+    Restore,
+}
+
+impl RevisionAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RevisionAction::Baseline => "baseline",
+            RevisionAction::Create => "create",
+            RevisionAction::Update => "update",
+            RevisionAction::Glue => "glue",
+            RevisionAction::Restore => "restore",
+        }
+    }
+}
+
+/// 以 `SELECT … FOR UPDATE` 锁定任务行后读取最新版本号。
+///
+/// 同一任务的并发修改在此排队，直到持有锁的事务提交，避免分配到相同的版本号。
+async fn lock_latest<C: ConnectionTrait>(conn: &C, job_id: i32) -> AppResult<Option<i32>> {
+    job_info::Entity::find_by_id(job_id)
+        .lock_exclusive()
+        .one(conn)
+        .await?;
+    Ok(job_info_revision::Entity::find()
+        .filter(job_info_revision::Column::JobId.eq(job_id))
+        .order_by_desc(job_info_revision::Column::Revision)
+        .one(conn)
+        .await?
+        .map(|item| item.revision))
+}
+
+/// 保存任务当前定义的完整快照，返回新版本。需要与任务的修改在同一事务中调用。
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    job: &job_info::Model,
+    action: RevisionAction,
+    operator: &str,
+) -> AppResult<job_info_revision::Model> {
+    let latest = lock_latest(conn, job.id).await?.unwrap_or(0);
+    let snapshot = serde_json::to_string(job).map_err(AppError::internal)?;
+
+    let inserted = job_info_revision::Entity::insert(job_info_revision::ActiveModel {
+        job_id: Set(job.id),
+        revision: Set(latest + 1),
+        action: Set(action.as_str().to_string()),
+        operator: Set(operator.to_string()),
+        snapshot: Set(snapshot),
+        created_at: Set(Local::now().naive_local()),
+        ..Default::default()
+    })
+    .exec_with_returning(conn)
+    .await?;
+    Ok(inserted)
+}
+
+/// 修改前调用：任务还没有任何版本时先把当前定义记为基线，保证修改前的内容可以恢复。
+pub async fn ensure_baseline<C: ConnectionTrait>(conn: &C, job: &job_info::Model) -> AppResult<()> {
+    if lock_latest(conn, job.id).await?.is_none() {
+        record(conn, job, RevisionAction::Baseline, "system").await?;
+    }
+    Ok(())
+}
+
+pub async fn find<C: ConnectionTrait>(
+    conn: &C,
+    job_id: i32,
+    revision: i32,
+) -> AppResult<job_info_revision::Model> {
+    job_info_revision::Entity::find()
+        .filter(job_info_revision::Column::JobId.eq(job_id))
+        .filter(job_info_revision::Column::Revision.eq(revision))
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("任务版本 {revision} 不存在")))
+}
+
+/// 解析版本中保存的任务快照。
+pub fn snapshot(revision: &job_info_revision::Model) -> AppResult<job_info::Model> {
+    serde_json::from_str(&revision.snapshot)
+        .map_err(|err| AppError::internal(anyhow::anyhow!("任务版本快照损坏: {err}")))
+}
+
+/// 对比两个任务定义，返回 `{"字段": [旧值, 新值]}`，忽略调度运行期字段。
+pub fn diff(from: &job_info::Model, to: &job_info::Model) -> AppResult<Value> {
+    let definition = |job: &job_info::Model| -> AppResult<Value> {
+        let mut value = serde_json::to_value(job).map_err(AppError::internal)?;
+        if let Value::Object(map) = &mut value {
+            map.retain(|key, _| !RUNTIME_FIELDS.contains(&key.as_str()));
+        }
+        Ok(value)
+    };
+    Ok(audit::diff(&definition(from)?, &definition(to)?).unwrap_or_default())
+}
+
+/// 把快照中的任务定义写回当前任务，保留 ID、创建时间与调度状态。
+pub fn apply(job: &mut job_info::Model, snapshot: job_info::Model) {
+    job.job_group = snapshot.job_group;
+    job.job_desc = snapshot.job_desc;
+    job.author = snapshot.author;
+    job.alarm_email = snapshot.alarm_email;
+    job.schedule_type = snapshot.schedule_type;
+    job.schedule_conf = snapshot.schedule_conf;
+    job.misfire_strategy = snapshot.misfire_strategy;
+    job.executor_route_strategy = snapshot.executor_route_strategy;
+    job.executor_handler = snapshot.executor_handler;
+    job.executor_param = snapshot.executor_param;
+    job.executor_block_strategy = snapshot.executor_block_strategy;
+    job.executor_timeout = snapshot.executor_timeout;
+    job.executor_fail_retry_count = snapshot.executor_fail_retry_count;
+    job.glue_type = snapshot.glue_type;
+    job.glue_source = snapshot.glue_source;
+    job.glue_remark = snapshot.glue_remark;
+    job.child_jobid = snapshot.child_jobid;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn job() -> job_info::Model {
+        job_info::Model {
+            id: 7,
+            job_group: 1,
+            job_desc: "nightly report".into(),
+            job_key: None,
+            add_time: None,
+            update_time: None,
+            author: Some("ops".into()),
+            alarm_email: None,
+            schedule_type: "CRON".into(),
+            schedule_conf: Some("0 0 1 * * ?".into()),
+            misfire_strategy: "DO_NOTHING".into(),
+            executor_route_strategy: Some("FIRST".into()),
+            executor_handler: Some("reportHandler".into()),
+            executor_param: None,
+            executor_block_strategy: Some("SERIAL_EXECUTION".into()),
+            executor_timeout: 0,
+            executor_fail_retry_count: 0,
+            glue_type: "BEAN".into(),
+            glue_source: None,
+            glue_remark: None,
+            glue_updatetime: None,
+            child_jobid: None,
+            cloned_from: None,
+            trigger_status: 0,
+            trigger_last_time: 0,
+            trigger_next_time: 0,
+        }
+    }
+
+    #[test]
+    fn diff_ignores_runtime_fields() {
+        let before = job();
+        let mut after = job();
+        after.schedule_conf = Some("0 30 1 * * ?".into());
+        after.trigger_status = 1;
+        after.trigger_next_time = 1_700_000_000_000;
+        assert_eq!(
+            diff(&before, &after).unwrap(),
+            json!({ "schedule_conf": ["0 0 1 * * ?", "0 30 1 * * ?"] })
+        );
+    }
+
+    #[test]
+    fn apply_keeps_schedule_state() {
+        let mut current = job();
+        current.trigger_status = 1;
+        current.trigger_next_time = 42;
+        current.executor_param = Some("new".into());
+
+        let mut old = job();
+        old.executor_param = Some("old".into());
+        old.trigger_status = 0;
+        apply(&mut current, old);
+
+        assert_eq!(current.executor_param.as_deref(), Some("old"));
+        assert_eq!(current.trigger_status, 1);
+        assert_eq!(current.trigger_next_time, 42);
+    }
+}
@@ -0,0 +1,295 @@
+use chrono::{Local, NaiveDateTime};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::config::JobTrashSettings;
+use crate::entities::{job_info, job_info_revision, job_info_trash, job_log, job_logglue};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// 引用提示中最多列出的任务数。
+const MAX_LISTED_REFERRERS: usize = 5;
+
+/// 删除任务的结果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "mode")]
+pub enum DeleteOutcome {
+    /// 移入回收站，`purge_after` 之前可恢复
+    Trashed { purge_after: NaiveDateTime },
+    /// 连同调度日志、GLUE 历史与定义版本彻底删除
+    Purged(PurgeCounts),
+}
+
+impl DeleteOutcome {
+    pub fn message(&self) -> String {
+        match self {
+            DeleteOutcome::Trashed { purge_after } => format!(
+                "任务已移入回收站，{} 前可恢复",
+                purge_after.format("%Y-%m-%d %H:%M")
+            ),
+            DeleteOutcome::Purged(counts) => format!(
+                "任务已彻底删除，同时删除 {} 条调度日志、{} 个 GLUE 历史版本",
+                counts.logs, counts.glue_versions
+            ),
+        }
+    }
+}
+
+/// 彻底删除时一并删除的关联记录数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeCounts {
+    pub logs: u64,
+    pub glue_versions: u64,
+    pub revisions: u64,
+}
+
+/// 删除任务：被其它任务配置为子任务时拒绝；配置了保留期时移入回收站，否则立即彻底删除。
+///
+/// 涉及多条语句，调用方应在事务中执行。
+pub async fn delete<C: ConnectionTrait>(
+    conn: &C,
+    settings: &JobTrashSettings,
+    job: &job_info::Model,
+    operator: &str,
+    purge: bool,
+) -> AppResult<DeleteOutcome> {
+    ensure_not_referenced(conn, job).await?;
+    let outcome = match settings.retention().filter(|_| !purge) {
+        Some(retention) => {
+            let now = Local::now().naive_local();
+            let purge_after = now + retention;
+            job_info_trash::Entity::insert(job_info_trash::ActiveModel {
+                job_id: Set(job.id),
+                job_group: Set(job.job_group),
+                job_desc: Set(job.job_desc.clone()),
+                snapshot: Set(serde_json::to_string(job).map_err(AppError::internal)?),
+                deleted_by: Set(operator.to_string()),
+                deleted_at: Set(now),
+                purge_after: Set(purge_after),
+            })
+            .exec(conn)
+            .await?;
+            DeleteOutcome::Trashed { purge_after }
+        }
+        None => DeleteOutcome::Purged(purge_related(conn, job.id).await?),
+    };
+    let deleted = job_info::Entity::delete_by_id(job.id).exec(conn).await?;
+    if deleted.rows_affected == 0 {
+        return Err(AppError::NotFound("任务不存在".into()));
+    }
+    Ok(outcome)
+}
+
+/// 其它任务的 `child_jobid` 中包含该任务时拒绝删除，避免留下失效的子任务引用。
+async fn ensure_not_referenced<C: ConnectionTrait>(
+    conn: &C,
+    job: &job_info::Model,
+) -> AppResult<()> {
+    let referrers = job_info::Entity::find()
+        .filter(job_info::Column::Id.ne(job.id))
+        .filter(Expr::cust_with_values(
+            "FIND_IN_SET(?, REPLACE(`child_jobid`, ' ', '')) > 0",
+            [job.id.to_string()],
+        ))
+        .order_by_asc(job_info::Column::Id)
+        .all(conn)
+        .await?;
+    if referrers.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::Conflict(referrer_message(job.id, &referrers)))
+}
+
+fn referrer_message(job_id: i32, referrers: &[job_info::Model]) -> String {
+    let mut listed: Vec<String> = referrers
+        .iter()
+        .take(MAX_LISTED_REFERRERS)
+        .map(|job| format!("#{} {}", job.id, job.job_desc))
+        .collect();
+    if referrers.len() > MAX_LISTED_REFERRERS {
+        listed.push(format!("等 {} 个任务", referrers.len()));
+    }
+    format!(
+        "任务 #{job_id} 被以下任务配置为子任务，请先解除引用后再删除：{}",
+        listed.join("、")
+    )
+}
+
+/// 删除任务的调度日志、GLUE 历史与定义版本；执行日志归档随调度日志由归档清理任务删除。
+async fn purge_related<C: ConnectionTrait>(conn: &C, job_id: i32) -> AppResult<PurgeCounts> {
+    let logs = job_log::Entity::delete_many()
+        .filter(job_log::Column::JobId.eq(job_id))
+        .exec(conn)
+        .await?
+        .rows_affected;
+    let glue_versions = job_logglue::Entity::delete_many()
+        .filter(job_logglue::Column::JobId.eq(job_id))
+        .exec(conn)
+        .await?
+        .rows_affected;
+    let revisions = job_info_revision::Entity::delete_many()
+        .filter(job_info_revision::Column::JobId.eq(job_id))
+        .exec(conn)
+        .await?
+        .rows_affected;
+    Ok(PurgeCounts {
+        logs,
+        glue_versions,
+        revisions,
+    })
+}
+
+/// 彻底删除回收站中的任务及其关联记录。
+pub async fn purge<C: ConnectionTrait>(
+    conn: &C,
+    entry: &job_info_trash::Model,
+) -> AppResult<PurgeCounts> {
+    let counts = purge_related(conn, entry.job_id).await?;
+    job_info_trash::Entity::delete_by_id(entry.job_id)
+        .exec(conn)
+        .await?;
+    Ok(counts)
+}
+
+/// 回收站中任务的快照，恢复时沿用原 ID 并处于停止状态。
+pub fn restored_job(entry: &job_info_trash::Model) -> AppResult<job_info::Model> {
+    let mut job: job_info::Model =
+        serde_json::from_str(&entry.snapshot).map_err(AppError::internal)?;
+    job.trigger_status = 0;
+    job.trigger_next_time = 0;
+    job.update_time = Some(Local::now().naive_local());
+    Ok(job)
+}
+
+/// 按 `job_trash.purge_interval_hours` 定期彻底删除超过保留期的任务。
+pub fn spawn_purge(state: AppState) {
+    let interval = state.settings().job_trash.purge_interval();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(err) = purge_expired(&state).await {
+                warn!(error = %err, "清理回收站中过期的任务失败");
+            }
+        }
+    });
+}
+
+async fn purge_expired(state: &AppState) -> AppResult<()> {
+    let expired = job_info_trash::Entity::find()
+        .filter(job_info_trash::Column::PurgeAfter.lt(Local::now().naive_local()))
+        .order_by_asc(job_info_trash::Column::PurgeAfter)
+        .all(state.db())
+        .await?;
+    for entry in expired {
+        let txn = state.db().begin().await?;
+        let counts = purge(&txn, &entry).await?;
+        txn.commit().await?;
+        info!(
+            job_id = entry.job_id,
+            logs = counts.logs,
+            glue_versions = counts.glue_versions,
+            "已彻底删除回收站中过期的任务"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn job(id: i32, desc: &str) -> job_info::Model {
+        job_info::Model {
+            id,
+            job_group: 1,
+            job_desc: desc.into(),
+            job_key: Some(format!("job-{id}")),
+            add_time: None,
+            update_time: None,
+            author: None,
+            alarm_email: None,
+            schedule_type: "CRON".into(),
+            schedule_conf: Some("0 0 1 * * ?".into()),
+            misfire_strategy: "DO_NOTHING".into(),
+            executor_route_strategy: None,
+            executor_handler: Some("demoHandler".into()),
+            executor_param: None,
+            executor_block_strategy: None,
+            executor_timeout: 0,
+            executor_fail_retry_count: 0,
+            glue_type: "BEAN".into(),
+            glue_source: None,
+            glue_remark: None,
+            glue_updatetime: None,
+            child_jobid: None,
+            cloned_from: None,
+            trigger_status: 1,
+            trigger_last_time: 100,
+            trigger_next_time: 200,
+        }
+    }
+
+    #[test]
+    fn lists_referrers_in_message() {
+        let referrers: Vec<_> = (1..=7).map(|id| job(id, &format!("任务{id}"))).collect();
+        assert_eq!(
+            referrer_message(9, &referrers[..2]),
+            "任务 #9 被以下任务配置为子任务，请先解除引用后再删除：#1 任务1、#2 任务2"
+        );
+        let message = referrer_message(9, &referrers);
+        assert!(message.contains("#5 任务5、等 7 个任务"), "{message}");
+        assert!(!message.contains("#6"));
+    }
+
+    #[test]
+    fn restores_job_stopped_with_original_id() {
+        let deleted_at = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let original = job(12, "日报");
+        let entry = job_info_trash::Model {
+            job_id: 12,
+            job_group: 1,
+            job_desc: "日报".into(),
+            snapshot: serde_json::to_string(&original).unwrap(),
+            deleted_by: "admin".into(),
+            deleted_at,
+            purge_after: deleted_at + chrono::Duration::days(7),
+        };
+        let restored = restored_job(&entry).unwrap();
+        assert_eq!(restored.id, 12);
+        assert_eq!(restored.job_key.as_deref(), Some("job-12"));
+        assert_eq!(restored.trigger_status, 0);
+        assert_eq!(restored.trigger_next_time, 0);
+        assert_eq!(restored.schedule_conf, original.schedule_conf);
+    }
+
+    #[test]
+    fn describes_delete_outcome() {
+        let purge_after = NaiveDate::from_ymd_opt(2024, 5, 8)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        assert_eq!(
+            DeleteOutcome::Trashed { purge_after }.message(),
+            "任务已移入回收站，2024-05-08 08:00 前可恢复"
+        );
+        let purged = DeleteOutcome::Purged(PurgeCounts {
+            logs: 3,
+            glue_versions: 2,
+            revisions: 4,
+        });
+        assert_eq!(
+            purged.message(),
+            "任务已彻底删除，同时删除 3 条调度日志、2 个 GLUE 历史版本"
+        );
+    }
+}
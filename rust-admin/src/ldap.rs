@@ -0,0 +1,374 @@
+use anyhow::{anyhow, Context};
+use ldap3::{dn_escape, ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use tracing::warn;
+
+use crate::authenticator::{self, Authenticator, ExternalAccount, GroupRule, MappedAccess};
+use crate::config::LdapSettings;
+use crate::entities::job_user;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// LDAP 创建的账号不设置可用密码，只能通过目录服务登录。
+const LDAP_PASSWORD_PLACEHOLDER: &str = "!ldap";
+
+/// LDAP 结果码 49：invalidCredentials
+const RC_INVALID_CREDENTIALS: u32 = 49;
+
+/// 目录服务中认证通过的用户。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryUser {
+    pub dn: String,
+    /// 用户所属分组的 DN
+    pub groups: Vec<String>,
+}
+
+/// 目录服务的最小抽象，便于在测试中替换为进程内实现。
+#[axum::async_trait]
+pub trait Directory: Send + Sync {
+    /// 以用户凭据绑定目录服务；用户不存在或密码错误时返回 `Ok(None)`。
+    async fn bind_user(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<Option<DirectoryUser>>;
+}
+
+/// 基于 ldap3 的目录服务实现，兼容 OpenLDAP 与 Active Directory。
+pub struct LdapDirectory {
+    settings: LdapSettings,
+}
+
+impl LdapDirectory {
+    pub fn new(settings: LdapSettings) -> Self {
+        Self { settings }
+    }
+
+    async fn connect(&self) -> anyhow::Result<Ldap> {
+        let url = self.settings.url()?;
+        let conn_settings = LdapConnSettings::new()
+            .set_conn_timeout(self.settings.timeout())
+            .set_starttls(self.settings.starttls);
+        let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, url)
+            .await
+            .with_context(|| format!("连接 LDAP 服务失败: {url}"))?;
+        ldap3::drive!(conn);
+        Ok(ldap)
+    }
+
+    /// 按 DN 模板或“服务账号搜索”定位用户 DN。
+    async fn find_user_dn(
+        &self,
+        ldap: &mut Ldap,
+        username: &str,
+    ) -> anyhow::Result<Option<String>> {
+        if let Some(template) = self.settings.user_dn_template.as_deref() {
+            return Ok(Some(template.replace("{username}", &dn_escape(username))));
+        }
+
+        let timeout = self.settings.timeout();
+        if let Some(bind_dn) = self.settings.bind_dn.as_deref() {
+            ldap.with_timeout(timeout)
+                .simple_bind(
+                    bind_dn,
+                    self.settings.bind_password.as_deref().unwrap_or_default(),
+                )
+                .await?
+                .success()
+                .context("LDAP 服务账号绑定失败")?;
+        }
+        let base = self.settings.user_base_dn.as_deref().ok_or_else(|| {
+            anyhow!("未配置 `ldap.user_dn_template` 时需要配置 `ldap.user_base_dn`")
+        })?;
+        let filter = self
+            .settings
+            .user_filter
+            .replace("{username}", &ldap_escape(username));
+        let (entries, _) = ldap
+            .with_timeout(timeout)
+            .search(base, Scope::Subtree, &filter, vec!["1.1"])
+            .await?
+            .success()
+            .context("LDAP 搜索用户失败")?;
+        if entries.len() > 1 {
+            warn!(
+                username,
+                count = entries.len(),
+                "LDAP 用户过滤条件匹配到多个条目"
+            );
+            return Ok(None);
+        }
+        Ok(entries
+            .into_iter()
+            .next()
+            .map(|entry| SearchEntry::construct(entry).dn))
+    }
+
+    async fn load_groups(
+        &self,
+        ldap: &mut Ldap,
+        dn: &str,
+        username: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let timeout = self.settings.timeout();
+        let attribute = self.settings.group_attribute.as_str();
+        let (entries, _) = ldap
+            .with_timeout(timeout)
+            .search(dn, Scope::Base, "(objectClass=*)", vec![attribute])
+            .await?
+            .success()
+            .context("LDAP 读取用户分组失败")?;
+        let mut groups: Vec<String> = entries
+            .into_iter()
+            .flat_map(|entry| SearchEntry::construct(entry).attrs)
+            .filter(|(name, _)| name.eq_ignore_ascii_case(attribute))
+            .flat_map(|(_, values)| values)
+            .collect();
+
+        if let Some(base) = self.settings.group_base_dn.as_deref() {
+            let filter = self
+                .settings
+                .group_filter
+                .replace("{dn}", &ldap_escape(dn))
+                .replace("{username}", &ldap_escape(username));
+            let (entries, _) = ldap
+                .with_timeout(timeout)
+                .search(base, Scope::Subtree, &filter, vec!["1.1"])
+                .await?
+                .success()
+                .context("LDAP 搜索分组失败")?;
+            groups.extend(
+                entries
+                    .into_iter()
+                    .map(|entry| SearchEntry::construct(entry).dn),
+            );
+        }
+        Ok(groups)
+    }
+}
+
+#[axum::async_trait]
+impl Directory for LdapDirectory {
+    async fn bind_user(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<Option<DirectoryUser>> {
+        let mut ldap = self.connect().await?;
+        let result = async {
+            let Some(dn) = self.find_user_dn(&mut ldap, username).await? else {
+                return Ok(None);
+            };
+            let bind = ldap
+                .with_timeout(self.settings.timeout())
+                .simple_bind(&dn, password)
+                .await?;
+            if bind.rc == RC_INVALID_CREDENTIALS {
+                return Ok(None);
+            }
+            bind.success().context("LDAP 用户绑定失败")?;
+            let groups = self.load_groups(&mut ldap, &dn, username).await?;
+            Ok(Some(DirectoryUser { dn, groups }))
+        }
+        .await;
+        let _ = ldap.unbind().await;
+        result
+    }
+}
+
+/// 以目录服务绑定校验密码，并按分组映射同步本地账号的角色与执行器授权。
+pub struct LdapAuthenticator<D> {
+    settings: LdapSettings,
+    directory: D,
+}
+
+impl<D: Directory> LdapAuthenticator<D> {
+    pub fn new(settings: LdapSettings, directory: D) -> Self {
+        Self {
+            settings,
+            directory,
+        }
+    }
+
+    /// 校验凭据并计算分组映射，凭据无效时返回 `Ok(None)`。
+    pub async fn resolve(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<Option<MappedAccess>> {
+        // 空密码会被多数目录服务当作匿名绑定而“成功”，必须提前拒绝
+        if username.is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+        let Some(user) = self.directory.bind_user(username, password).await? else {
+            return Ok(None);
+        };
+        let groups = group_names(&user.groups);
+        Ok(Some(authenticator::map_groups(
+            &groups,
+            &self.settings.admin_groups,
+            self.settings
+                .group_mappings
+                .iter()
+                .map(|mapping| GroupRule {
+                    group: &mapping.group,
+                    job_groups: &mapping.job_groups,
+                    role: &mapping.role,
+                }),
+        )))
+    }
+}
+
+#[axum::async_trait]
+impl<D: Directory> Authenticator for LdapAuthenticator<D> {
+    async fn authenticate(
+        &self,
+        state: &AppState,
+        username: &str,
+        password: &str,
+    ) -> AppResult<Option<job_user::Model>> {
+        let access = match self.resolve(username, password).await {
+            Ok(Some(access)) => access,
+            Ok(None) => return Ok(None),
+            Err(err) if self.settings.local_fallback => {
+                warn!(error = %format!("{err:#}"), username, "LDAP 认证不可用，回退到本地账号");
+                return Ok(None);
+            }
+            Err(err) => return Err(AppError::internal(err)),
+        };
+        let sync_access =
+            !self.settings.admin_groups.is_empty() || !self.settings.group_mappings.is_empty();
+        let user = authenticator::provision_user(
+            state,
+            ExternalAccount::Ldap { username },
+            LDAP_PASSWORD_PLACEHOLDER,
+            &access,
+            sync_access,
+        )
+        .await?;
+        Ok(Some(user))
+    }
+}
+
+/// 分组既可按完整 DN 也可按首个 RDN 的值（通常是 CN）配置映射。
+fn group_names(dns: &[String]) -> Vec<String> {
+    let mut names = Vec::with_capacity(dns.len() * 2);
+    for dn in dns {
+        names.push(dn.clone());
+        if let Some((_, value)) = dn.split(',').next().and_then(|rdn| rdn.split_once('=')) {
+            names.push(value.trim().to_string());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{GroupRole, UserRole};
+    use crate::config::LdapGroupMapping;
+    use std::collections::{BTreeMap, HashMap};
+
+    /// 进程内的目录服务替身，按用户名保存密码与分组 DN。
+    struct InMemoryDirectory {
+        users: HashMap<&'static str, (&'static str, Vec<String>)>,
+    }
+
+    #[axum::async_trait]
+    impl Directory for InMemoryDirectory {
+        async fn bind_user(
+            &self,
+            username: &str,
+            password: &str,
+        ) -> anyhow::Result<Option<DirectoryUser>> {
+            Ok(self
+                .users
+                .get(username)
+                .filter(|(expected, _)| *expected == password)
+                .map(|(_, groups)| DirectoryUser {
+                    dn: format!("uid={username},ou=people,dc=example,dc=com"),
+                    groups: groups.clone(),
+                }))
+        }
+    }
+
+    fn authenticator() -> LdapAuthenticator<InMemoryDirectory> {
+        let settings = LdapSettings {
+            enabled: true,
+            admin_groups: vec!["cn=job-admins,ou=groups,dc=example,dc=com".into()],
+            group_mappings: vec![
+                LdapGroupMapping {
+                    group: "payments".into(),
+                    job_groups: vec![1],
+                    role: "operator".into(),
+                },
+                LdapGroupMapping {
+                    group: "cn=payments-dev,ou=groups,dc=example,dc=com".into(),
+                    job_groups: vec![1, 3],
+                    role: "developer".into(),
+                },
+            ],
+            ..Default::default()
+        };
+        let directory = InMemoryDirectory {
+            users: HashMap::from([
+                (
+                    "alice",
+                    (
+                        "alice-pass",
+                        vec![
+                            "CN=Payments,OU=Groups,DC=example,DC=com".to_string(),
+                            "cn=payments-dev,ou=groups,dc=example,dc=com".to_string(),
+                        ],
+                    ),
+                ),
+                (
+                    "root",
+                    (
+                        "root-pass",
+                        vec!["cn=job-admins,ou=groups,dc=example,dc=com".to_string()],
+                    ),
+                ),
+            ]),
+        };
+        LdapAuthenticator::new(settings, directory)
+    }
+
+    #[tokio::test]
+    async fn maps_directory_groups_to_roles() {
+        let authenticator = authenticator();
+
+        let alice = authenticator
+            .resolve("alice", "alice-pass")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(alice.role, UserRole::User);
+        assert_eq!(
+            alice.group_roles,
+            BTreeMap::from([(1, GroupRole::Developer), (3, GroupRole::Developer)])
+        );
+
+        let root = authenticator
+            .resolve("root", "root-pass")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.role, UserRole::Admin);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_or_empty_credentials() {
+        let authenticator = authenticator();
+        assert!(authenticator
+            .resolve("alice", "wrong")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(authenticator.resolve("alice", "").await.unwrap().is_none());
+        assert!(authenticator
+            .resolve("nobody", "x")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,313 @@
+pub mod store;
+
+use std::io::{Read, Write};
+
+use anyhow::Context;
+use chrono::Local;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, EntityTrait, QueryFilter, QuerySelect,
+};
+use tracing::{info, warn};
+
+use crate::entities::{job_log, log_archive};
+use crate::executor_log::{self, LogContent};
+use crate::state::AppState;
+
+pub use store::LogStore;
+
+/// 单个日志最多向执行器拉取的页数，防止执行器持续返回内容时无限循环。
+const MAX_PAGES: usize = 10_000;
+/// 每轮清理的归档数量。
+const CLEANUP_BATCH: u64 = 500;
+
+/// 收到执行回调后在后台归档执行日志；归档失败只记录告警，不影响回调处理。
+pub fn spawn_archive(state: AppState, log_id: i64) {
+    if state.log_store().is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        tokio::time::sleep(state.settings().log_archive.delay()).await;
+        match archive(&state, log_id).await {
+            Ok(Some(archived)) => info!(
+                log_id,
+                key = archived.object_key.as_str(),
+                original_size = archived.original_size,
+                compressed_size = archived.compressed_size,
+                "已归档执行日志"
+            ),
+            Ok(None) => {}
+            Err(err) => warn!(log_id, error = ?err, "归档执行日志失败"),
+        }
+    });
+}
+
+/// 从执行器拉取完整日志并压缩保存，执行器没有可用日志时返回 `Ok(None)`。
+pub async fn archive(state: &AppState, log_id: i64) -> anyhow::Result<Option<log_archive::Model>> {
+    let Some(store) = state.log_store() else {
+        return Ok(None);
+    };
+    let Some(log) = job_log::Entity::find_by_id(log_id).one(state.db()).await? else {
+        return Ok(None);
+    };
+    let max_bytes = usize::try_from(state.settings().log_archive.max_bytes).unwrap_or(usize::MAX);
+
+    let mut text = String::new();
+    let mut from = 1;
+    let mut truncated = false;
+    for _ in 0..MAX_PAGES {
+        let content = match executor_log::fetch(state, &log, from).await? {
+            Ok(content) => content,
+            Err(reason) if text.is_empty() => {
+                warn!(log_id, reason = reason.as_str(), "执行日志不可用，跳过归档");
+                return Ok(None);
+            }
+            // 已拉取到部分内容时保留已有部分
+            Err(reason) => {
+                warn!(
+                    log_id,
+                    reason = reason.as_str(),
+                    "拉取执行日志中断，归档已拉取的部分"
+                );
+                break;
+            }
+        };
+        if content.to_line_num < from || content.log_content.is_empty() {
+            break;
+        }
+        text.push_str(&content.log_content);
+        from = content.to_line_num + 1;
+        if text.len() > max_bytes {
+            let mut end = max_bytes;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push_str(&format!(
+                "\n[日志超过归档上限 {max_bytes} 字节，其余内容未归档]\n"
+            ));
+            truncated = true;
+            break;
+        }
+        if content.end {
+            break;
+        }
+    }
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let original_size = text.len() as i64;
+    let line_count = text.lines().count() as i32;
+    let log_text = search_text(&text, state.settings().log_archive.search_max_bytes);
+    let compressed = tokio::task::spawn_blocking(move || compress(&text))
+        .await
+        .context("压缩执行日志失败")??;
+    let day = log
+        .trigger_time
+        .unwrap_or_else(|| Local::now().naive_local())
+        .format("%Y-%m-%d");
+    let key = format!("{day}/{log_id}.log.gz");
+    let compressed_size = compressed.len() as i64;
+    store.put(&key, compressed).await?;
+
+    let model = log_archive::Model {
+        log_id,
+        storage: store.name().to_string(),
+        object_key: key,
+        original_size,
+        compressed_size,
+        line_count,
+        truncated: i8::from(truncated),
+        log_text,
+        created_at: Local::now().naive_local(),
+    };
+    let active: log_archive::ActiveModel = model.clone().into();
+    log_archive::Entity::insert(active)
+        .on_conflict(
+            OnConflict::column(log_archive::Column::LogId)
+                .update_columns([
+                    log_archive::Column::Storage,
+                    log_archive::Column::ObjectKey,
+                    log_archive::Column::OriginalSize,
+                    log_archive::Column::CompressedSize,
+                    log_archive::Column::LineCount,
+                    log_archive::Column::Truncated,
+                    log_archive::Column::LogText,
+                    log_archive::Column::CreatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(state.db())
+        .await?;
+    Ok(Some(model))
+}
+
+/// 读取归档的执行日志，从 `from` 行开始返回；没有归档或归档不可读时返回 `None`。
+pub async fn load(state: &AppState, log_id: i64, from: i64) -> Option<LogContent> {
+    let text = match read_text(state, log_id).await {
+        Ok(text) => text?,
+        Err(err) => {
+            warn!(log_id, error = ?err, "读取归档执行日志失败");
+            return None;
+        }
+    };
+    Some(slice_lines(&text, from))
+}
+
+async fn read_text(state: &AppState, log_id: i64) -> anyhow::Result<Option<String>> {
+    let Some(store) = state.log_store() else {
+        return Ok(None);
+    };
+    let Some((storage, object_key)) = log_archive::Entity::find_by_id(log_id)
+        .select_only()
+        .columns([log_archive::Column::Storage, log_archive::Column::ObjectKey])
+        .into_tuple::<(String, String)>()
+        .one(state.db())
+        .await?
+    else {
+        return Ok(None);
+    };
+    if storage != store.name() {
+        warn!(
+            log_id,
+            storage = storage.as_str(),
+            "归档存放在其它存储后端，当前配置无法读取"
+        );
+        return Ok(None);
+    }
+    let Some(body) = store.get(&object_key).await? else {
+        return Ok(None);
+    };
+    let text = tokio::task::spawn_blocking(move || decompress(&body))
+        .await
+        .context("解压归档执行日志失败")??;
+    Ok(Some(text))
+}
+
+/// 定期删除对应调度日志已被清理的归档。
+pub fn spawn_cleanup(state: AppState) {
+    if state.log_store().is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(state.settings().log_archive.cleanup_interval());
+        loop {
+            interval.tick().await;
+            match cleanup_orphans(&state).await {
+                Ok(deleted) if deleted > 0 => info!(deleted, "已清理失效的执行日志归档"),
+                Ok(_) => {}
+                Err(err) => warn!(error = ?err, "清理失效的执行日志归档失败"),
+            }
+        }
+    });
+}
+
+async fn cleanup_orphans(state: &AppState) -> anyhow::Result<u64> {
+    let Some(store) = state.log_store() else {
+        return Ok(0);
+    };
+    let mut deleted = 0;
+    loop {
+        let orphans = log_archive::Entity::find()
+            .filter(Expr::cust(
+                "NOT EXISTS (SELECT 1 FROM xxl_job_log l WHERE l.id = xxl_job_log_archive.log_id)",
+            ))
+            .filter(log_archive::Column::Storage.eq(store.name()))
+            .select_only()
+            .columns([log_archive::Column::LogId, log_archive::Column::ObjectKey])
+            .limit(CLEANUP_BATCH)
+            .into_tuple::<(i64, String)>()
+            .all(state.db())
+            .await?;
+        let batch = orphans.len() as u64;
+        for (log_id, object_key) in orphans {
+            store.delete(&object_key).await?;
+            log_archive::Entity::delete_by_id(log_id)
+                .exec(state.db())
+                .await?;
+            deleted += 1;
+        }
+        if batch < CLEANUP_BATCH {
+            return Ok(deleted);
+        }
+    }
+}
+
+/// 截取写入全文索引的日志文本，`max_bytes` 为 0 时不索引。
+fn search_text(text: &str, max_bytes: u64) -> Option<String> {
+    if max_bytes == 0 {
+        return None;
+    }
+    let mut end = usize::try_from(max_bytes)
+        .unwrap_or(usize::MAX)
+        .min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(text[..end].to_string())
+}
+
+fn compress(text: &str) -> anyhow::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(body: &[u8]) -> anyhow::Result<String> {
+    let mut text = String::new();
+    GzDecoder::new(body).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// 按执行器 `/log` 的行号语义截取：返回第 `from` 行到末尾，`to_line_num` 为最后一行的行号。
+fn slice_lines(text: &str, from: i64) -> LogContent {
+    let from = from.max(1);
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let skip = usize::try_from(from - 1).unwrap_or(usize::MAX);
+    let log_content: String = lines.iter().skip(skip).copied().collect();
+    LogContent {
+        from_line_num: from,
+        to_line_num: (lines.len() as i64).max(from - 1),
+        end: true,
+        log_content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_round_trip() {
+        let text = "2024-05-01 10:00:00 [main] start\n执行完成\n".repeat(100);
+        let compressed = compress(&text).unwrap();
+        assert!(compressed.len() < text.len());
+        assert_eq!(decompress(&compressed).unwrap(), text);
+    }
+
+    #[test]
+    fn search_text_respects_limit_and_char_boundary() {
+        assert_eq!(search_text("abc", 0), None);
+        assert_eq!(search_text("abc", 10).as_deref(), Some("abc"));
+        assert_eq!(search_text("执行完成", 4).as_deref(), Some("执"));
+    }
+
+    #[test]
+    fn slices_from_requested_line() {
+        let text = "line1\nline2\nline3\n";
+        let all = slice_lines(text, 1);
+        assert_eq!((all.from_line_num, all.to_line_num), (1, 3));
+        assert_eq!(all.log_content, text);
+
+        let tail = slice_lines(text, 3);
+        assert_eq!(tail.log_content, "line3\n");
+        assert_eq!(tail.to_line_num, 3);
+
+        let beyond = slice_lines(text, 5);
+        assert!(beyond.log_content.is_empty());
+        assert_eq!(beyond.to_line_num, 4);
+        assert!(beyond.end);
+    }
+}
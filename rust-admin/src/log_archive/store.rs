@@ -0,0 +1,333 @@
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::header::AUTHORIZATION;
+use reqwest::{Method, StatusCode};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::{LogArchiveBackend, LogArchiveSettings, S3Settings};
+
+const S3_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 归档日志的存储后端。
+#[axum::async_trait]
+pub trait LogStore: Send + Sync {
+    /// 写入 `xxl_job_log_archive.storage` 的后端名称
+    fn name(&self) -> &'static str;
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> anyhow::Result<()>;
+
+    /// 对象不存在时返回 `Ok(None)`。
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// 对象不存在时视为删除成功。
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// 根据配置创建存储后端，未启用归档时返回 `None`。
+pub fn from_settings(settings: &LogArchiveSettings) -> Option<Arc<dyn LogStore>> {
+    if !settings.enabled {
+        return None;
+    }
+    Some(match settings.backend {
+        LogArchiveBackend::Local => Arc::new(LocalStore::new(&settings.local_dir)),
+        LogArchiveBackend::S3 => Arc::new(S3Store::new(settings.s3.clone())),
+    })
+}
+
+/// 存放在管理端本地磁盘，适合单实例部署。
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("非法的归档路径: {key}");
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[axum::async_trait]
+impl LogStore for LocalStore {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("创建归档目录 {} 失败", parent.display()))?;
+        }
+        // 先写临时文件再改名，避免读取到写了一半的归档
+        let temp = path.with_extension("tmp");
+        tokio::fs::write(&temp, body)
+            .await
+            .with_context(|| format!("写入归档文件 {} 失败", temp.display()))?;
+        tokio::fs::rename(&temp, &path)
+            .await
+            .with_context(|| format!("保存归档文件 {} 失败", path.display()))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(body) => Ok(Some(body)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("读取归档文件 {} 失败", path.display())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err).with_context(|| format!("删除归档文件 {} 失败", path.display())),
+        }
+    }
+}
+
+/// S3 兼容对象存储，使用 AWS Signature V4 签名，MinIO 可作为本地替代。
+pub struct S3Store {
+    client: reqwest::Client,
+    settings: S3Settings,
+}
+
+impl S3Store {
+    pub fn new(settings: S3Settings) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(S3_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { client, settings }
+    }
+
+    fn object_url(&self, key: &str) -> anyhow::Result<Url> {
+        let mut url = Url::parse(self.settings.endpoint.trim())
+            .with_context(|| format!("S3 endpoint 无效: {}", self.settings.endpoint))?;
+        let object = uri_encode(&format!("{}{}", self.settings.prefix, key));
+        let base = url.path().trim_end_matches('/').to_string();
+        if self.settings.path_style {
+            url.set_path(&format!("{base}/{}/{object}", self.settings.bucket));
+        } else {
+            let host = url
+                .host_str()
+                .ok_or_else(|| anyhow!("S3 endpoint 缺少主机名"))?;
+            let host = format!("{}.{host}", self.settings.bucket);
+            url.set_host(Some(&host))
+                .with_context(|| format!("S3 bucket 名称无效: {}", self.settings.bucket))?;
+            url.set_path(&format!("{base}/{object}"));
+        }
+        Ok(url)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        body: Vec<u8>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let url = self.object_url(key)?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{SIGNED_HEADERS}\n{payload_hash}",
+            path = url.path(),
+        );
+        let signature = sign(
+            &self.settings.secret_key,
+            &self.settings.region,
+            &amz_date,
+            &canonical_request,
+        );
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}/{}/s3/aws4_request, SignedHeaders={SIGNED_HEADERS}, Signature={signature}",
+            self.settings.access_key,
+            &amz_date[..8],
+            self.settings.region,
+        );
+
+        self.client
+            .request(method.clone(), url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(AUTHORIZATION, authorization)
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("请求 S3 {method} {key} 失败"))
+    }
+}
+
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+#[axum::async_trait]
+impl LogStore for S3Store {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> anyhow::Result<()> {
+        let response = self.send(Method::PUT, key, body).await?;
+        ensure_success(response, key).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = self.send(Method::GET, key, Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = ensure_success(response, key).await?;
+        Ok(Some(response.bytes().await?.to_vec()))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let response = self.send(Method::DELETE, key, Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        ensure_success(response, key).await.map(|_| ())
+    }
+}
+
+async fn ensure_success(
+    response: reqwest::Response,
+    key: &str,
+) -> anyhow::Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!("S3 返回状态码 {status}（{key}）: {}", body.trim())
+}
+
+/// 按 RFC 3986 编码对象 Key，保留路径分隔符 `/`。
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// 对规范请求做 Signature V4 签名，`amz_date` 形如 `20130524T000000Z`。
+fn sign(secret_key: &str, region: &str, amz_date: &str, canonical_request: &str) -> String {
+    let date = &amz_date[..8];
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{date}/{region}/s3/aws4_request\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    hex::encode(hmac_sha256(
+        &signing_key(secret_key, date, region, "s3"),
+        &string_to_sign,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_aws_signing_key() {
+        // AWS Signature V4 文档中的示例
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn signs_s3_get_object_example() {
+        // AWS S3 文档 “GET Object” 签名示例
+        let canonical_request = "GET\n/test.txt\n\nhost:examplebucket.s3.amazonaws.com\nrange:bytes=0-9\nx-amz-content-sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\nx-amz-date:20130524T000000Z\n\nhost;range;x-amz-content-sha256;x-amz-date\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(
+            sign(
+                "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+                "us-east-1",
+                "20130524T000000Z",
+                canonical_request,
+            ),
+            "f0e8bdb87c964420e857bd35b5d6ed310bd44f0170aba48dd91039c6036bdb41"
+        );
+    }
+
+    #[test]
+    fn builds_path_style_and_virtual_host_urls() {
+        let mut settings = S3Settings {
+            endpoint: "http://127.0.0.1:9000".into(),
+            bucket: "xxl-job-logs".into(),
+            prefix: "logs/".into(),
+            ..S3Settings::default()
+        };
+        let store = S3Store::new(settings.clone());
+        assert_eq!(
+            store.object_url("2024-05-01/42 a.log.gz").unwrap().as_str(),
+            "http://127.0.0.1:9000/xxl-job-logs/logs/2024-05-01/42%20a.log.gz"
+        );
+
+        settings.endpoint = "https://s3.amazonaws.com".into();
+        settings.path_style = false;
+        let store = S3Store::new(settings);
+        assert_eq!(
+            store.object_url("2024-05-01/42.log.gz").unwrap().as_str(),
+            "https://xxl-job-logs.s3.amazonaws.com/logs/2024-05-01/42.log.gz"
+        );
+    }
+
+    #[test]
+    fn local_store_rejects_escaping_paths() {
+        let store = LocalStore::new("/tmp/archive");
+        assert!(store.path("2024-05-01/1.log.gz").is_ok());
+        assert!(store.path("../etc/passwd").is_err());
+        assert!(store.path("/etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, NaiveDateTime};
+
+/// 调度日志按 `(trigger_time, id)` 倒序翻页的游标，指向上一页的最后一条日志。
+///
+/// 编码为 `{触发时间毫秒}_{id}`，触发时间为空时为 `-_{id}`；对调用方而言是不透明字符串。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCursor {
+    pub trigger_time: Option<NaiveDateTime>,
+    pub id: i64,
+}
+
+impl LogCursor {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (time, id) = raw.trim().split_once('_')?;
+        let id = id.parse().ok()?;
+        let trigger_time = match time {
+            "-" => None,
+            millis => Some(DateTime::from_timestamp_millis(millis.parse().ok()?)?.naive_utc()),
+        };
+        Some(Self { trigger_time, id })
+    }
+
+    pub fn encode(&self) -> String {
+        match self.trigger_time {
+            Some(time) => format!("{}_{}", time.and_utc().timestamp_millis(), self.id),
+            None => format!("-_{}", self.id),
+        }
+    }
+}
+
+/// 游标翻页时总数的统计方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotalMode {
+    /// 不统计，默认值
+    None,
+    /// 使用 `EXPLAIN` 的估算行数，代价与翻页深度无关
+    Approx,
+    /// 精确 `COUNT(*)`
+    Exact,
+}
+
+impl TotalMode {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(str::trim).unwrap_or("") {
+            "" | "none" => Some(Self::None),
+            "approx" => Some(Self::Approx),
+            "exact" => Some(Self::Exact),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn cursor_round_trip() {
+        let time = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(10, 30, 15)
+            .unwrap();
+        let cursor = LogCursor {
+            trigger_time: Some(time),
+            id: 42,
+        };
+        assert_eq!(LogCursor::parse(&cursor.encode()), Some(cursor));
+
+        let without_time = LogCursor {
+            trigger_time: None,
+            id: 7,
+        };
+        assert_eq!(without_time.encode(), "-_7");
+        assert_eq!(LogCursor::parse("-_7"), Some(without_time));
+    }
+
+    #[test]
+    fn rejects_malformed_cursor() {
+        assert_eq!(LogCursor::parse(""), None);
+        assert_eq!(LogCursor::parse("123"), None);
+        assert_eq!(LogCursor::parse("abc_1"), None);
+        assert_eq!(LogCursor::parse("123_x"), None);
+    }
+
+    #[test]
+    fn parses_total_mode() {
+        assert_eq!(TotalMode::parse(None), Some(TotalMode::None));
+        assert_eq!(TotalMode::parse(Some("approx")), Some(TotalMode::Approx));
+        assert_eq!(TotalMode::parse(Some("exact")), Some(TotalMode::Exact));
+        assert_eq!(TotalMode::parse(Some("all")), None);
+    }
+}
@@ -0,0 +1,187 @@
+use serde::Serialize;
+
+/// ngram 全文索引的最小词长（MySQL 默认 `ngram_token_size = 2`）。
+const MIN_KEYWORD_CHARS: usize = 2;
+/// 关键字最大长度，超过的部分忽略。
+const MAX_KEYWORD_CHARS: usize = 100;
+/// 每个字段最多返回的命中片段数。
+const MAX_FRAGMENTS: usize = 3;
+/// 命中片段前后保留的字符数。
+const CONTEXT_CHARS: usize = 40;
+
+/// 高亮片段所在的字段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchField {
+    TriggerMsg,
+    HandleMsg,
+    Log,
+}
+
+/// 搜索命中的片段，`fragment` 为已转义的 HTML，命中部分以 `<mark>` 包裹。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchHighlight {
+    pub field: SearchField,
+    pub fragment: String,
+}
+
+/// 规范化后的搜索关键字。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchKeyword {
+    text: String,
+}
+
+impl SearchKeyword {
+    /// 去除引号与多余空白；关键字过短时返回 `None`，调用方应拒绝请求。
+    pub fn parse(raw: &str) -> Option<Self> {
+        let text = raw
+            .chars()
+            .map(|ch| {
+                if ch == '"' || ch.is_control() {
+                    ' '
+                } else {
+                    ch
+                }
+            })
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let text: String = text.chars().take(MAX_KEYWORD_CHARS).collect();
+        let text = text.trim_end().to_string();
+        if text.chars().count() < MIN_KEYWORD_CHARS {
+            return None;
+        }
+        Some(Self { text })
+    }
+
+    /// `MATCH ... AGAINST (? IN BOOLEAN MODE)` 使用的短语查询，关键字作为整体匹配。
+    pub fn boolean_query(&self) -> String {
+        format!("\"{}\"", self.text)
+    }
+
+    /// 在文本中查找关键字（忽略大小写），返回带上下文的高亮片段。
+    pub fn highlight(&self, field: SearchField, text: &str) -> Vec<SearchHighlight> {
+        let haystack: Vec<char> = match field {
+            SearchField::TriggerMsg => strip_tags(text).chars().collect(),
+            SearchField::HandleMsg | SearchField::Log => text.chars().collect(),
+        };
+        let needle: Vec<char> = self.text.chars().collect();
+        let folded: Vec<char> = haystack.iter().map(|ch| fold(*ch)).collect();
+        let needle_folded: Vec<char> = needle.iter().map(|ch| fold(*ch)).collect();
+
+        let mut highlights = Vec::new();
+        let mut pos = 0;
+        while highlights.len() < MAX_FRAGMENTS && pos + needle.len() <= folded.len() {
+            if folded[pos..pos + needle.len()] != needle_folded[..] {
+                pos += 1;
+                continue;
+            }
+            let end = pos + needle.len();
+            let from = pos.saturating_sub(CONTEXT_CHARS);
+            let to = (end + CONTEXT_CHARS).min(haystack.len());
+            let mut fragment = String::new();
+            if from > 0 {
+                fragment.push('…');
+            }
+            fragment.push_str(&escape_html(&haystack[from..pos]));
+            fragment.push_str("<mark>");
+            fragment.push_str(&escape_html(&haystack[pos..end]));
+            fragment.push_str("</mark>");
+            fragment.push_str(&escape_html(&haystack[end..to]));
+            if to < haystack.len() {
+                fragment.push('…');
+            }
+            highlights.push(SearchHighlight { field, fragment });
+            pos = to.max(end);
+        }
+        highlights
+    }
+}
+
+fn fold(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+/// 调度备注中包含 `<br>`、`<span>` 等标签，高亮前按纯文本处理。
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for ch in text.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                out.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(ch),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape_html(chars: &[char]) -> String {
+    let mut out = String::with_capacity(chars.len());
+    for ch in chars {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' | '\r' | '\t' => out.push(' '),
+            _ => out.push(*ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keyword_into_phrase_query() {
+        let keyword = SearchKeyword::parse("  connection \"reset\"\n").unwrap();
+        assert_eq!(keyword.boolean_query(), "\"connection reset\"");
+        assert!(SearchKeyword::parse(" a ").is_none());
+        assert!(SearchKeyword::parse("\"\"").is_none());
+        assert!(SearchKeyword::parse("超时").is_some());
+    }
+
+    #[test]
+    fn highlights_case_insensitive_matches_with_context() {
+        let keyword = SearchKeyword::parse("connection reset").unwrap();
+        let text = format!("{}Connection Reset by peer <x>", "a".repeat(50));
+        let highlights = keyword.highlight(SearchField::HandleMsg, &text);
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(
+            highlights[0].fragment,
+            format!(
+                "…{}<mark>Connection Reset</mark> by peer &lt;x&gt;",
+                "a".repeat(40)
+            )
+        );
+    }
+
+    #[test]
+    fn strips_tags_from_trigger_msg_and_caps_fragments() {
+        let keyword = SearchKeyword::parse("调度").unwrap();
+        let highlights = keyword.highlight(
+            SearchField::TriggerMsg,
+            "任务触发类型：<br>手动触发<br>调度机器",
+        );
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(
+            highlights[0].fragment,
+            "任务触发类型： 手动触发 <mark>调度</mark>机器"
+        );
+
+        let repeated = "调度 ".repeat(200);
+        assert_eq!(
+            keyword.highlight(SearchField::Log, &repeated).len(),
+            MAX_FRAGMENTS
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use tracing::warn;
+
+use crate::config::LoginThrottleSettings;
+use crate::entities::{login_failure, login_lockout};
+use crate::error::{AppError, AppResult};
+use crate::session::ClientInfo;
+use crate::state::AppState;
+
+/// 登录失败的统计维度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockScope {
+    User,
+    Ip,
+}
+
+impl LockScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LockScope::User => "user",
+            LockScope::Ip => "ip",
+        }
+    }
+}
+
+/// 用户名统一转为小写，避免通过大小写变化绕过计数。
+fn user_subject(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+/// 来源 IP 只在请求经过 `security.trusted_proxies` 中的代理时才取自转发头，客户端无法通过伪造请求头绕过按 IP 的锁定。
+fn subjects(username: &str, client: &ClientInfo) -> Vec<(LockScope, String)> {
+    let mut subjects = vec![(LockScope::User, user_subject(username))];
+    if let Some(ip) = client.ip.as_deref() {
+        subjects.push((LockScope::Ip, ip.to_string()));
+    }
+    subjects
+}
+
+fn subject_condition(subjects: &[(LockScope, String)]) -> Condition {
+    subjects
+        .iter()
+        .fold(Condition::any(), |condition, (scope, subject)| {
+            condition.add(
+                Condition::all()
+                    .add(login_failure::Column::Scope.eq(scope.as_str()))
+                    .add(login_failure::Column::Subject.eq(subject.as_str())),
+            )
+        })
+}
+
+/// 校验用户名与来源 IP 是否处于锁定期，锁定时返回 429。
+pub async fn ensure_allowed(
+    state: &AppState,
+    username: &str,
+    client: &ClientInfo,
+) -> AppResult<()> {
+    let now = Local::now().naive_local();
+    let locked_until = login_failure::Entity::find()
+        .filter(subject_condition(&subjects(username, client)))
+        .filter(login_failure::Column::LockedUntil.gt(now))
+        .all(state.db())
+        .await?
+        .into_iter()
+        .filter_map(|row| row.locked_until)
+        .max();
+
+    match locked_until {
+        Some(until) => {
+            let minutes = ((until - now).num_seconds() + 59) / 60;
+            Err(AppError::TooManyRequests(format!(
+                "登录失败次数过多，请在 {} 分钟后重试",
+                minutes.max(1)
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// 记录一次登录失败，达到阈值时锁定并写入锁定事件，返回本次响应应延迟的时长。
+///
+/// 计数通过 `INSERT … ON DUPLICATE KEY UPDATE` 在数据库中原子累加，并发的失败请求不会互相覆盖；
+/// 累加后的行在事务结束前保持锁定，同一时刻只有一个请求会触发锁定。
+pub async fn record_failure(
+    state: &AppState,
+    username: &str,
+    client: &ClientInfo,
+) -> AppResult<Duration> {
+    let policy = &state.settings().security.login_throttle;
+    let now = Local::now().naive_local();
+    let mut max_failures = 0u32;
+
+    let txn = state.db().begin().await?;
+    for (scope, subject) in subjects(username, client) {
+        let row = increment(&txn, scope, &subject, now, now - policy.failure_window()).await?;
+        let failures = row.failure_count.max(0) as u32;
+        max_failures = max_failures.max(failures);
+        if should_lock(threshold(policy, scope), &row, now) {
+            lock(&txn, row, client, now, now + policy.lockout()).await?;
+        }
+    }
+    txn.commit().await?;
+
+    Ok(policy.delay_after(max_failures))
+}
+
+/// 失败次数加一并返回累加后的记录；上次失败早于 `window_start` 且未处于锁定期时重新开始计数。
+async fn increment<C: ConnectionTrait>(
+    conn: &C,
+    scope: LockScope,
+    subject: &str,
+    now: NaiveDateTime,
+    window_start: NaiveDateTime,
+) -> AppResult<login_failure::Model> {
+    const EXPIRED: &str =
+        "`last_failure_at` < ? AND (`locked_until` IS NULL OR `locked_until` <= ?)";
+    let expired = |then: &str, otherwise: &str| {
+        Expr::cust_with_values(
+            format!("IF({EXPIRED}, {then}, {otherwise})"),
+            [window_start, now],
+        )
+    };
+    // MySQL 按书写顺序依次赋值，`last_failure_at` 必须最后更新，前面的判断才会读到原值
+    login_failure::Entity::insert(login_failure::ActiveModel {
+        scope: Set(scope.as_str().to_string()),
+        subject: Set(subject.to_string()),
+        failure_count: Set(1),
+        first_failure_at: Set(now),
+        last_failure_at: Set(now),
+        locked_until: Set(None),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([login_failure::Column::Scope, login_failure::Column::Subject])
+            .value(
+                login_failure::Column::FailureCount,
+                expired("1", "`failure_count` + 1"),
+            )
+            .value(
+                login_failure::Column::FirstFailureAt,
+                expired("VALUES(`first_failure_at`)", "`first_failure_at`"),
+            )
+            .value(
+                login_failure::Column::LockedUntil,
+                expired("NULL", "`locked_until`"),
+            )
+            .value(
+                login_failure::Column::LastFailureAt,
+                Expr::cust("VALUES(`last_failure_at`)"),
+            )
+            .to_owned(),
+    )
+    .exec_without_returning(conn)
+    .await?;
+
+    login_failure::Entity::find()
+        .filter(login_failure::Column::Scope.eq(scope.as_str()))
+        .filter(login_failure::Column::Subject.eq(subject))
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::internal(anyhow::anyhow!("登录失败计数写入后未找到记录")))
+}
+
+/// 各统计维度触发锁定的失败次数。
+fn threshold(policy: &LoginThrottleSettings, scope: LockScope) -> u32 {
+    match scope {
+        LockScope::User => policy.max_failures_per_user,
+        LockScope::Ip => policy.max_failures_per_ip,
+    }
+}
+
+/// 达到阈值且尚未处于锁定期时锁定；阈值为 0 表示不锁定该维度。
+fn should_lock(threshold: u32, row: &login_failure::Model, now: NaiveDateTime) -> bool {
+    let already_locked = row.locked_until.is_some_and(|until| until > now);
+    threshold > 0 && row.failure_count.max(0) as u32 >= threshold && !already_locked
+}
+
+async fn lock<C: ConnectionTrait>(
+    conn: &C,
+    row: login_failure::Model,
+    client: &ClientInfo,
+    now: NaiveDateTime,
+    locked_until: NaiveDateTime,
+) -> AppResult<()> {
+    warn!(
+        scope = row.scope.as_str(),
+        subject = row.subject.as_str(),
+        client_ip = client.ip.as_deref().unwrap_or("-"),
+        failures = row.failure_count,
+        %locked_until,
+        "登录失败次数过多，已临时锁定"
+    );
+    login_lockout::Entity::insert(login_lockout::ActiveModel {
+        scope: Set(row.scope.clone()),
+        subject: Set(row.subject.clone()),
+        client_ip: Set(client.ip.clone()),
+        failure_count: Set(row.failure_count),
+        locked_at: Set(now),
+        locked_until: Set(locked_until),
+        unlocked_at: Set(None),
+        unlocked_by: Set(None),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?;
+
+    let mut active: login_failure::ActiveModel = row.into();
+    active.locked_until = Set(Some(locked_until));
+    active.update(conn).await?;
+    Ok(())
+}
+
+/// 登录成功后清除该用户名的失败计数；来源 IP 的计数保留到窗口结束，避免交替登录绕过限制。
+pub async fn record_success(state: &AppState, username: &str) -> AppResult<()> {
+    login_failure::Entity::delete_many()
+        .filter(login_failure::Column::Scope.eq(LockScope::User.as_str()))
+        .filter(login_failure::Column::Subject.eq(user_subject(username)))
+        .exec(state.db())
+        .await?;
+    Ok(())
+}
+
+/// 管理员解除锁定，返回是否存在需要解除的失败记录。
+pub async fn unlock<C: ConnectionTrait + TransactionTrait>(
+    conn: &C,
+    scope: LockScope,
+    subject: &str,
+    operator: &str,
+) -> AppResult<bool> {
+    let subject = match scope {
+        LockScope::User => user_subject(subject),
+        LockScope::Ip => subject.trim().to_string(),
+    };
+    let now = Local::now().naive_local();
+
+    let txn = conn.begin().await?;
+    let cleared = login_failure::Entity::delete_many()
+        .filter(login_failure::Column::Scope.eq(scope.as_str()))
+        .filter(login_failure::Column::Subject.eq(subject.as_str()))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    login_lockout::Entity::update_many()
+        .col_expr(login_lockout::Column::UnlockedAt, Expr::value(now))
+        .col_expr(login_lockout::Column::UnlockedBy, Expr::value(operator))
+        .filter(login_lockout::Column::Scope.eq(scope.as_str()))
+        .filter(login_lockout::Column::Subject.eq(subject.as_str()))
+        .filter(login_lockout::Column::LockedUntil.gt(now))
+        .filter(login_lockout::Column::UnlockedAt.is_null())
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(cleared > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(8, minute, 0)
+            .unwrap()
+    }
+
+    fn row(failure_count: i32, locked_until: Option<NaiveDateTime>) -> login_failure::Model {
+        login_failure::Model {
+            id: 1,
+            scope: LockScope::User.as_str().into(),
+            subject: "alice".into(),
+            failure_count,
+            first_failure_at: at(0),
+            last_failure_at: at(1),
+            locked_until,
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max() {
+        let policy = LoginThrottleSettings::default();
+        assert_eq!(policy.delay_after(0), Duration::ZERO);
+        assert_eq!(policy.delay_after(1), Duration::from_millis(500));
+        assert_eq!(policy.delay_after(2), Duration::from_millis(1000));
+        assert_eq!(policy.delay_after(4), Duration::from_millis(4000));
+        assert_eq!(policy.delay_after(5), Duration::from_millis(8000));
+        assert_eq!(policy.delay_after(u32::MAX), Duration::from_millis(8000));
+    }
+
+    #[test]
+    fn locks_once_threshold_reached() {
+        let policy = LoginThrottleSettings::default();
+        let user = threshold(&policy, LockScope::User);
+        let ip = threshold(&policy, LockScope::Ip);
+        assert_eq!((user, ip), (5, 20));
+
+        let now = at(2);
+        assert!(!should_lock(user, &row(4, None), now));
+        assert!(should_lock(user, &row(5, None), now));
+        assert!(!should_lock(ip, &row(5, None), now));
+        assert!(should_lock(ip, &row(20, None), now));
+        // 锁定期内继续失败不会重复锁定，锁定过期后再次达到阈值会重新锁定
+        assert!(!should_lock(user, &row(6, Some(at(10))), now));
+        assert!(should_lock(user, &row(6, Some(at(1))), now));
+        assert!(!should_lock(0, &row(100, None), now));
+    }
+}
@@ -1,11 +1,31 @@
+mod api_key;
+mod audit;
 mod auth;
+mod authenticator;
 mod config;
 mod entities;
 mod error;
+mod executor_log;
+mod glue;
+mod job_config;
+mod job_revision;
+mod job_trash;
+mod ldap;
+mod log_archive;
+mod log_page;
+mod log_search;
+mod login_guard;
+mod oidc;
+mod password;
 mod request_preview;
 mod routes;
+mod session;
 mod state;
 mod templates;
+#[cfg(test)]
+mod test_support;
+mod totp;
+mod trigger;
 
 use std::net::SocketAddr;
 
@@ -30,11 +50,18 @@ async fn main() -> anyhow::Result<()> {
     let http_client = build_http_client(&settings)?;
 
     let state = AppState::new(db, settings, http_client);
+    audit::spawn_retention(state.clone());
+    log_archive::spawn_cleanup(state.clone());
+    job_trash::spawn_purge(state.clone());
     let app = routes::create_router(state);
 
     let listener = TcpListener::bind(addr).await?;
     tracing::info!("Rust admin 服务启动: {}", listener.local_addr()?);
-    serve(listener, app.into_make_service()).await?;
+    serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
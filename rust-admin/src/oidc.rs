@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet, PublicKeyUse};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::authenticator::{self, GroupRule, MappedAccess};
+use crate::config::OidcSettings;
+
+/// 授权请求从发起到回调的最长等待时间。
+const PENDING_LOGIN_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: Option<String>,
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// 通过 `/.well-known/openid-configuration` 获取身份提供方的端点信息。
+pub async fn discover(
+    client: &reqwest::Client,
+    issuer_url: &str,
+) -> anyhow::Result<ProviderMetadata> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer_url.trim_end_matches('/')
+    );
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("请求 OIDC 发现文档失败: {url}"))?;
+    if !response.status().is_success() {
+        return Err(anyhow!("OIDC 发现文档返回状态码 {}", response.status()));
+    }
+    response
+        .json::<ProviderMetadata>()
+        .await
+        .context("解析 OIDC 发现文档失败")
+}
+
+#[derive(Debug)]
+struct PendingLogin {
+    nonce: String,
+    pkce_verifier: String,
+    created_at: Instant,
+}
+
+/// 保存尚未完成回调的授权请求，`state` 参数作为键，回调时一次性取出。
+#[derive(Debug, Default)]
+pub struct PendingLogins {
+    inner: Mutex<HashMap<String, PendingLogin>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: Url,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletedLogin {
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+impl PendingLogins {
+    /// 生成 state、nonce 与 PKCE 参数，并返回需要跳转的授权地址。
+    pub fn begin(
+        &self,
+        metadata: &ProviderMetadata,
+        settings: &OidcSettings,
+    ) -> anyhow::Result<AuthorizationRequest> {
+        let state = random_token();
+        let nonce = random_token();
+        let pkce_verifier = format!("{}{}", random_token(), random_token());
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(pkce_verifier.as_bytes()));
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .context("OIDC authorization_endpoint 不是合法的 URL")?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", settings.client_id()?)
+            .append_pair("redirect_uri", settings.redirect_url()?)
+            .append_pair("scope", &settings.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        let mut pending = self.inner.lock().expect("OIDC 登录状态锁已损坏");
+        pending.retain(|_, item| item.created_at.elapsed() < PENDING_LOGIN_TTL);
+        pending.insert(
+            state,
+            PendingLogin {
+                nonce,
+                pkce_verifier,
+                created_at: Instant::now(),
+            },
+        );
+        Ok(AuthorizationRequest { url })
+    }
+
+    pub fn complete(&self, state: &str) -> Option<CompletedLogin> {
+        let mut pending = self.inner.lock().expect("OIDC 登录状态锁已损坏");
+        pending
+            .remove(state)
+            .filter(|item| item.created_at.elapsed() < PENDING_LOGIN_TTL)
+            .map(|item| CompletedLogin {
+                nonce: item.nonce,
+                pkce_verifier: item.pkce_verifier,
+            })
+    }
+}
+
+fn random_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// 使用授权码向 token_endpoint 换取 ID Token。
+pub async fn exchange_code(
+    client: &reqwest::Client,
+    metadata: &ProviderMetadata,
+    settings: &OidcSettings,
+    code: &str,
+    pkce_verifier: &str,
+) -> anyhow::Result<String> {
+    let params = [
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", settings.redirect_url()?),
+        ("client_id", settings.client_id()?),
+        ("client_secret", settings.client_secret()?),
+        ("code_verifier", pkce_verifier),
+    ];
+    let response = client
+        .post(&metadata.token_endpoint)
+        .form(&params)
+        .send()
+        .await
+        .context("请求 OIDC token_endpoint 失败")?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("OIDC token_endpoint 返回状态码 {status}: {body}"));
+    }
+    response
+        .json::<TokenResponse>()
+        .await
+        .context("解析 OIDC 令牌响应失败")?
+        .id_token
+        .ok_or_else(|| anyhow!("OIDC 令牌响应中缺少 id_token"))
+}
+
+/// 从 ID Token 中提取的用户身份。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub username: String,
+    pub groups: Vec<String>,
+}
+
+fn is_hmac(alg: Algorithm) -> bool {
+    matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+/// 允许的 ID Token 签名算法：优先使用 `oidc.id_token_signing_algs`，否则取发现文档
+/// 声明的非对称算法，都没有时只接受 RS256。HS 系列只有显式配置时才会接受。
+fn allowed_algorithms(
+    metadata: &ProviderMetadata,
+    settings: &OidcSettings,
+) -> anyhow::Result<Vec<Algorithm>> {
+    if !settings.id_token_signing_algs.is_empty() {
+        return settings
+            .id_token_signing_algs
+            .iter()
+            .map(|value| {
+                value
+                    .parse::<Algorithm>()
+                    .map_err(|_| anyhow!("oidc.id_token_signing_algs 包含不支持的算法 `{value}`"))
+            })
+            .collect();
+    }
+    let discovered: Vec<Algorithm> = metadata
+        .id_token_signing_alg_values_supported
+        .iter()
+        .filter_map(|value| value.parse::<Algorithm>().ok())
+        .filter(|alg| !is_hmac(*alg))
+        .collect();
+    if discovered.is_empty() {
+        Ok(vec![Algorithm::RS256])
+    } else {
+        Ok(discovered)
+    }
+}
+
+/// 公钥的用途、声明的算法与密钥类型都与 `alg` 相符时才可用于校验。
+fn jwk_matches(jwk: &Jwk, alg: Algorithm) -> bool {
+    let usable = jwk
+        .common
+        .public_key_use
+        .as_ref()
+        .is_none_or(|usage| *usage == PublicKeyUse::Signature);
+    let same_alg = jwk
+        .common
+        .key_algorithm
+        .is_none_or(|key_alg| key_alg.to_string() == format!("{alg:?}"));
+    let same_type = match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => matches!(
+            alg,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+        ),
+        AlgorithmParameters::EllipticCurve(_) => {
+            matches!(alg, Algorithm::ES256 | Algorithm::ES384)
+        }
+        AlgorithmParameters::OctetKeyPair(_) => alg == Algorithm::EdDSA,
+        AlgorithmParameters::OctetKey(_) => false,
+    };
+    usable && same_alg && same_type
+}
+
+/// 按 `kid` 选择签名公钥；ID Token 未携带 `kid` 时要求 JWKS 中恰好有一个匹配的公钥。
+fn select_jwk<'a>(jwks: &'a JwkSet, kid: Option<&str>, alg: Algorithm) -> anyhow::Result<&'a Jwk> {
+    match kid {
+        Some(kid) => jwks
+            .find(kid)
+            .filter(|jwk| jwk_matches(jwk, alg))
+            .ok_or_else(|| anyhow!("JWKS 中未找到 ID Token 对应的签名公钥")),
+        None => {
+            let mut candidates = jwks.keys.iter().filter(|jwk| jwk_matches(jwk, alg));
+            match (candidates.next(), candidates.next()) {
+                (Some(jwk), None) => Ok(jwk),
+                (None, _) => Err(anyhow!("JWKS 中未找到 ID Token 对应的签名公钥")),
+                (Some(_), Some(_)) => Err(anyhow!("ID Token 缺少 kid，且 JWKS 中有多个可用公钥")),
+            }
+        }
+    }
+}
+
+/// 校验 ID Token 的签名、签发方、受众、有效期与 nonce，并提取用户身份。
+///
+/// 签名算法必须在 [`allowed_algorithms`] 之内：RS/ES/PS 系列使用 `jwks_uri` 中的公钥校验，
+/// 显式配置的 HS 系列使用 client_secret 校验。
+pub async fn verify_id_token(
+    client: &reqwest::Client,
+    metadata: &ProviderMetadata,
+    settings: &OidcSettings,
+    id_token: &str,
+    expected_nonce: &str,
+) -> anyhow::Result<OidcIdentity> {
+    let header = decode_header(id_token).context("解析 ID Token 头失败")?;
+    if !allowed_algorithms(metadata, settings)?.contains(&header.alg) {
+        return Err(anyhow!("ID Token 签名算法 {:?} 不在允许范围内", header.alg));
+    }
+    let key = if is_hmac(header.alg) {
+        DecodingKey::from_secret(settings.client_secret()?.as_bytes())
+    } else {
+        let jwks_uri = metadata
+            .jwks_uri
+            .as_deref()
+            .ok_or_else(|| anyhow!("OIDC 发现文档缺少 jwks_uri"))?;
+        let jwks = client
+            .get(jwks_uri)
+            .send()
+            .await
+            .context("请求 OIDC JWKS 失败")?
+            .json::<JwkSet>()
+            .await
+            .context("解析 OIDC JWKS 失败")?;
+        let jwk = select_jwk(&jwks, header.kid.as_deref(), header.alg)?;
+        DecodingKey::from_jwk(jwk).context("加载 JWKS 公钥失败")?
+    };
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[settings.client_id()?]);
+    validation.set_issuer(&[metadata.issuer.as_str()]);
+    let claims = decode::<serde_json::Map<String, serde_json::Value>>(id_token, &key, &validation)
+        .context("ID Token 校验失败")?
+        .claims;
+
+    if claims.get("nonce").and_then(|value| value.as_str()) != Some(expected_nonce) {
+        return Err(anyhow!("ID Token nonce 不匹配"));
+    }
+
+    let subject = claims
+        .get("sub")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| anyhow!("ID Token 缺少 sub"))?
+        .to_string();
+    let username = claims
+        .get(settings.username_claim.as_str())
+        .and_then(|value| value.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("ID Token 缺少用户名声明 `{}`", settings.username_claim))?
+        .to_string();
+    let groups = match claims.get(settings.groups_claim.as_str()) {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_json::Value::String(value)) => value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(OidcIdentity {
+        subject,
+        username,
+        groups,
+    })
+}
+
+/// 根据配置把身份提供方的分组映射为管理台角色与执行器授权。
+pub fn map_access(settings: &OidcSettings, identity: &OidcIdentity) -> MappedAccess {
+    authenticator::map_groups(
+        &identity.groups,
+        &settings.admin_groups,
+        settings.group_mappings.iter().map(|mapping| GroupRule {
+            group: &mapping.claim,
+            job_groups: &mapping.job_groups,
+            role: &mapping.role,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{GroupRole, UserRole};
+    use crate::config::OidcGroupMapping;
+    use axum::{extract::State, routing::get, routing::post, Form, Json, Router};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    const CLIENT_ID: &str = "xxl-job-admin";
+    const CLIENT_SECRET: &str = "mock-secret";
+
+    fn settings(issuer: &str) -> OidcSettings {
+        OidcSettings {
+            enabled: true,
+            issuer_url: Some(issuer.to_string()),
+            client_id: Some(CLIENT_ID.into()),
+            client_secret: Some(CLIENT_SECRET.into()),
+            redirect_url: Some("http://127.0.0.1:8080/api/auth/oidc/callback".into()),
+            scopes: vec!["openid".into(), "groups".into()],
+            username_claim: "preferred_username".into(),
+            groups_claim: "groups".into(),
+            id_token_signing_algs: vec!["HS256".into()],
+            admin_groups: vec!["ops-admins".into()],
+            group_mappings: vec![
+                OidcGroupMapping {
+                    claim: "team-a".into(),
+                    job_groups: vec![1, 2],
+                    role: "viewer".into(),
+                },
+                OidcGroupMapping {
+                    claim: "team-a-dev".into(),
+                    job_groups: vec![2],
+                    role: "developer".into(),
+                },
+            ],
+        }
+    }
+
+    #[derive(Clone)]
+    struct MockIdp {
+        issuer: String,
+        nonce: Arc<Mutex<String>>,
+    }
+
+    /// 进程内的模拟身份提供方，提供发现文档与 token_endpoint，使用 HS256 签发 ID Token。
+    async fn start_mock_idp() -> MockIdp {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let idp = MockIdp {
+            issuer: issuer.clone(),
+            nonce: Arc::new(Mutex::new(String::new())),
+        };
+
+        async fn discovery(State(idp): State<MockIdp>) -> Json<serde_json::Value> {
+            Json(serde_json::json!({
+                "issuer": idp.issuer,
+                "authorization_endpoint": format!("{}/authorize", idp.issuer),
+                "token_endpoint": format!("{}/token", idp.issuer),
+            }))
+        }
+
+        async fn token(
+            State(idp): State<MockIdp>,
+            Form(form): Form<HashMap<String, String>>,
+        ) -> Json<serde_json::Value> {
+            assert_eq!(form.get("code").map(String::as_str), Some("mock-code"));
+            assert_eq!(
+                form.get("client_secret").map(String::as_str),
+                Some(CLIENT_SECRET)
+            );
+            assert!(form.contains_key("code_verifier"));
+            let now = chrono::Utc::now().timestamp();
+            let claims = serde_json::json!({
+                "iss": idp.issuer,
+                "aud": CLIENT_ID,
+                "sub": "user-123",
+                "exp": now + 300,
+                "iat": now,
+                "nonce": idp.nonce.lock().unwrap().clone(),
+                "preferred_username": "alice",
+                "groups": ["team-a", "team-a-dev"],
+            });
+            let id_token = encode(
+                &Header::new(Algorithm::HS256),
+                &claims,
+                &EncodingKey::from_secret(CLIENT_SECRET.as_bytes()),
+            )
+            .unwrap();
+            Json(serde_json::json!({ "access_token": "mock", "id_token": id_token }))
+        }
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/token", post(token))
+            .with_state(idp.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        idp
+    }
+
+    #[tokio::test]
+    async fn authorization_code_flow_against_mock_idp() {
+        let idp = start_mock_idp().await;
+        let settings = settings(&idp.issuer);
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let metadata = discover(&client, &idp.issuer).await.unwrap();
+        let pending = PendingLogins::default();
+        let request = pending.begin(&metadata, &settings).unwrap();
+        let query: HashMap<_, _> = request.url.query_pairs().into_owned().collect();
+        assert_eq!(query.get("client_id").map(String::as_str), Some(CLIENT_ID));
+        assert_eq!(
+            query.get("code_challenge_method").map(String::as_str),
+            Some("S256")
+        );
+
+        let login = pending.complete(&query["state"]).unwrap();
+        assert!(
+            pending.complete(&query["state"]).is_none(),
+            "state 只能使用一次"
+        );
+        *idp.nonce.lock().unwrap() = login.nonce.clone();
+
+        let id_token = exchange_code(
+            &client,
+            &metadata,
+            &settings,
+            "mock-code",
+            &login.pkce_verifier,
+        )
+        .await
+        .unwrap();
+        let identity = verify_id_token(&client, &metadata, &settings, &id_token, &login.nonce)
+            .await
+            .unwrap();
+        assert_eq!(identity.subject, "user-123");
+        assert_eq!(identity.username, "alice");
+
+        let wrong_nonce =
+            verify_id_token(&client, &metadata, &settings, &id_token, "other-nonce").await;
+        assert!(wrong_nonce.is_err());
+
+        let access = map_access(&settings, &identity);
+        assert_eq!(access.role, UserRole::User);
+        assert_eq!(
+            access.group_roles,
+            BTreeMap::from([(1, GroupRole::Viewer), (2, GroupRole::Developer)])
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_hs256_token_unless_configured() {
+        let idp = start_mock_idp().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let mut metadata = discover(&client, &idp.issuer).await.unwrap();
+        let mut settings = settings(&idp.issuer);
+        settings.id_token_signing_algs.clear();
+        *idp.nonce.lock().unwrap() = "n-1".into();
+        let id_token = exchange_code(&client, &metadata, &settings, "mock-code", "verifier")
+            .await
+            .unwrap();
+
+        // 默认只接受 RS256，用 client_secret 签名的 HS256 令牌必须被拒绝
+        let err = verify_id_token(&client, &metadata, &settings, &id_token, "n-1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("不在允许范围内"), "{err}");
+
+        // 发现文档中声明的 HS 系列算法同样不会被接受
+        metadata.id_token_signing_alg_values_supported = vec!["RS256".into(), "HS256".into()];
+        assert_eq!(
+            allowed_algorithms(&metadata, &settings).unwrap(),
+            vec![Algorithm::RS256]
+        );
+        assert!(
+            verify_id_token(&client, &metadata, &settings, &id_token, "n-1")
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn requires_single_matching_key_without_kid() {
+        let rsa = |kid: &str, usage: &str| serde_json::json!({ "kty": "RSA", "kid": kid, "use": usage, "n": "AQAB", "e": "AQAB" });
+        let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [rsa("sig-1", "sig"), rsa("enc-1", "enc")]
+        }))
+        .unwrap();
+        assert_eq!(
+            select_jwk(&jwks, None, Algorithm::RS256)
+                .unwrap()
+                .common
+                .key_id
+                .as_deref(),
+            Some("sig-1")
+        );
+        assert!(select_jwk(&jwks, Some("enc-1"), Algorithm::RS256).is_err());
+        assert!(select_jwk(&jwks, None, Algorithm::ES256).is_err());
+
+        let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [rsa("sig-1", "sig"), rsa("sig-2", "sig")]
+        }))
+        .unwrap();
+        assert!(select_jwk(&jwks, None, Algorithm::RS256).is_err());
+        assert!(select_jwk(&jwks, Some("sig-2"), Algorithm::RS256).is_ok());
+    }
+
+    #[test]
+    fn admin_group_maps_to_admin_role() {
+        let settings = settings("http://idp");
+        let identity = OidcIdentity {
+            subject: "1".into(),
+            username: "root".into(),
+            groups: vec!["ops-admins".into()],
+        };
+        assert_eq!(map_access(&settings, &identity).role, UserRole::Admin);
+    }
+}
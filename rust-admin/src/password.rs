@@ -0,0 +1,180 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, SaltString};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::config::PasswordPolicy;
+use crate::error::{AppError, AppResult};
+
+/// 数据库中已存在的密码格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordScheme {
+    /// Java 版 Admin 使用的无盐 MD5（32 位十六进制）。
+    LegacyMd5,
+    /// 早期 Rust 版 Admin 使用的无盐 SHA-256（64 位十六进制）。
+    LegacySha256,
+    /// 当前使用的 Argon2id PHC 字符串。
+    Argon2,
+}
+
+impl PasswordScheme {
+    pub fn detect(stored: &str) -> Option<Self> {
+        let stored = stored.trim();
+        if stored.starts_with("$argon2") {
+            return Some(PasswordScheme::Argon2);
+        }
+        if !stored.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        match stored.len() {
+            32 => Some(PasswordScheme::LegacyMd5),
+            64 => Some(PasswordScheme::LegacySha256),
+            _ => None,
+        }
+    }
+
+    pub fn is_legacy(self) -> bool {
+        !matches!(self, PasswordScheme::Argon2)
+    }
+}
+
+/// 使用 Argon2id 生成新的密码摘要，新设置的密码一律使用该格式。
+pub fn hash_password(password: &str) -> AppResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| AppError::internal(anyhow::anyhow!("生成密码摘要失败: {err}")))
+}
+
+/// 校验明文密码与数据库中的摘要是否匹配，同时兼容历史的 MD5 / SHA-256 格式。
+///
+/// 返回匹配时识别到的格式，调用方可据此判断是否需要升级摘要。
+pub fn verify_password(password: &str, stored: &str) -> Option<PasswordScheme> {
+    let stored = stored.trim();
+    let scheme = PasswordScheme::detect(stored)?;
+    let matched = match scheme {
+        PasswordScheme::LegacyMd5 => {
+            constant_time_eq(&hex::encode(Md5::digest(password.as_bytes())), stored)
+        }
+        PasswordScheme::LegacySha256 => {
+            constant_time_eq(&hex::encode(Sha256::digest(password.as_bytes())), stored)
+        }
+        PasswordScheme::Argon2 => PasswordHash::new(stored)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false),
+    };
+    matched.then_some(scheme)
+}
+
+/// 按密码策略校验新密码，不满足时返回可直接展示给用户的说明。
+pub fn check_strength(policy: &PasswordPolicy, username: &str, password: &str) -> AppResult<()> {
+    let mut problems = Vec::new();
+    if password.chars().count() < policy.min_length {
+        problems.push(format!("长度至少 {} 位", policy.min_length));
+    }
+    if policy.require_letter && !password.chars().any(|c| c.is_alphabetic()) {
+        problems.push("需包含字母".to_string());
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        problems.push("需包含数字".to_string());
+    }
+    if policy.require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+        problems.push("需包含特殊字符".to_string());
+    }
+    let username = username.trim().to_lowercase();
+    if policy.reject_username && !username.is_empty() && password.to_lowercase().contains(&username)
+    {
+        problems.push("不能包含用户名".to_string());
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "密码不符合要求：{}",
+            problems.join("，")
+        )))
+    }
+}
+
+/// 逐字节比较摘要，耗时与差异位置无关；`stored` 不区分大小写。
+pub fn constant_time_eq(computed: &str, stored: &str) -> bool {
+    let stored = stored.to_ascii_lowercase();
+    if computed.len() != stored.len() {
+        return false;
+    }
+    computed
+        .bytes()
+        .zip(stored.bytes())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_java_admin_md5() {
+        // Java 版 Admin 默认账号 admin / 123456
+        let stored = "e10adc3949ba59abbe56e057f20f883e";
+        assert_eq!(
+            verify_password("123456", stored),
+            Some(PasswordScheme::LegacyMd5)
+        );
+        assert_eq!(
+            verify_password("123456", &stored.to_uppercase()),
+            Some(PasswordScheme::LegacyMd5)
+        );
+        assert_eq!(verify_password("1234567", stored), None);
+    }
+
+    #[test]
+    fn accepts_legacy_sha256() {
+        let stored = "8d969eef6ecad3c29a3a629280e686cf0c3f5d5a86aff3ca12020c923adc6c92";
+        assert_eq!(
+            verify_password("123456", stored),
+            Some(PasswordScheme::LegacySha256)
+        );
+        assert_eq!(verify_password("654321", stored), None);
+    }
+
+    #[test]
+    fn argon2_round_trip() {
+        let stored = hash_password("s3cret-pass").unwrap();
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(stored.len() <= 100, "摘要需能写入 varchar(100) 列");
+        assert_eq!(
+            verify_password("s3cret-pass", &stored),
+            Some(PasswordScheme::Argon2)
+        );
+        assert_eq!(verify_password("wrong", &stored), None);
+    }
+
+    #[test]
+    fn strength_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(check_strength(&policy, "alice", "s3cret-pass").is_ok());
+        assert!(check_strength(&policy, "alice", "short1").is_err());
+        assert!(check_strength(&policy, "alice", "onlyletters").is_err());
+        assert!(check_strength(&policy, "alice", "Alice2024!").is_err());
+
+        let strict = PasswordPolicy {
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+        assert!(check_strength(&strict, "alice", "s3cretpass").is_err());
+        assert!(check_strength(&strict, "alice", "s3cret-pass").is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        assert_eq!(PasswordScheme::detect("plain-text"), None);
+        assert_eq!(verify_password("plain-text", "plain-text"), None);
+    }
+}
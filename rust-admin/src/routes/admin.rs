@@ -1,6 +1,13 @@
 use askama::Template;
-use axum::{response::Html, routing::get, Router};
+use axum::{
+    extract::{FromRequestParts, State},
+    http::request::Parts,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::get,
+    Router,
+};
 
+use crate::auth::AuthUser;
 use crate::error::{AppError, AppResult};
 use crate::state::AppState;
 use crate::templates::{
@@ -24,44 +31,73 @@ pub fn router() -> Router<AppState> {
         .route("/logs", get(job_logs_page))
 }
 
-async fn login_page() -> AppResult<Html<String>> {
-    render_template(LoginTemplate {
+/// 管理台页面的登录用户，未登录或会话失效时跳转到登录页。
+struct ConsoleUser(AuthUser);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ConsoleUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        match AuthUser::from_request_parts(parts, state).await {
+            Ok(user) => Ok(ConsoleUser(user)),
+            Err(AppError::Unauthorized(_)) => Err(Redirect::to("/admin").into_response()),
+            Err(err) => Err(err.into_response()),
+        }
+    }
+}
+
+/// 已登录的会话直接进入首页。
+async fn login_page(State(state): State<AppState>, user: Option<AuthUser>) -> AppResult<Response> {
+    if user.is_some_and(|user| user.session_id.is_some()) {
+        return Ok(Redirect::to("/admin/dashboard").into_response());
+    }
+    Ok(render_template(LoginTemplate {
         app_name: APP_NAME,
         tagline: "轻量级分布式任务调度中心",
-    })
+        sso_enabled: state.settings().oidc.enabled,
+    })?
+    .into_response())
 }
 
-async fn dashboard_page() -> AppResult<Html<String>> {
+async fn dashboard_page(ConsoleUser(user): ConsoleUser) -> AppResult<Html<String>> {
     render_template(DashboardTemplate {
         app_name: APP_NAME,
         active_nav: "dashboard",
+        username: &user.username,
         summary_endpoint: SUMMARY_ENDPOINT,
         chart_endpoint: CHART_ENDPOINT,
     })
 }
 
-async fn job_info_page() -> AppResult<Html<String>> {
+async fn job_info_page(ConsoleUser(user): ConsoleUser) -> AppResult<Html<String>> {
     render_template(JobInfoTemplate {
         app_name: APP_NAME,
         active_nav: "jobs",
+        username: &user.username,
         job_groups_endpoint: JOB_GROUPS_ENDPOINT,
         job_info_endpoint: JOB_INFO_ENDPOINT,
         job_info_next_trigger_endpoint: JOB_INFO_NEXT_TRIGGER_ENDPOINT,
     })
 }
 
-async fn job_groups_page() -> AppResult<Html<String>> {
+async fn job_groups_page(ConsoleUser(user): ConsoleUser) -> AppResult<Html<String>> {
     render_template(JobGroupsTemplate {
         app_name: APP_NAME,
         active_nav: "groups",
+        username: &user.username,
         job_groups_endpoint: JOB_GROUPS_ENDPOINT,
     })
 }
 
-async fn job_logs_page() -> AppResult<Html<String>> {
+async fn job_logs_page(ConsoleUser(user): ConsoleUser) -> AppResult<Html<String>> {
     render_template(JobLogsTemplate {
         app_name: APP_NAME,
         active_nav: "logs",
+        username: &user.username,
         job_groups_endpoint: JOB_GROUPS_ENDPOINT,
         job_logs_endpoint: JOB_LOGS_ENDPOINT,
     })
@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{query::*, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{AuthUser, Permission};
+use crate::entities::audit_log;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const MAX_LENGTH: u64 = 500;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(page_list))
+        .route("/:id", get(detail))
+}
+
+#[derive(Debug, Deserialize)]
+struct PageParams {
+    start: Option<u64>,
+    length: Option<u64>,
+    /// 操作人用户名，服务账号为 `svc:<name>`
+    operator: Option<String>,
+    action: Option<String>,
+    target_type: Option<String>,
+    target_id: Option<String>,
+    client_ip: Option<String>,
+    /// 起止时间，格式 `yyyy-MM-dd HH:mm:ss` 或 `yyyy-MM-dd`
+    start_time: Option<String>,
+    end_time: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PageResult<T> {
+    records_total: u64,
+    records_filtered: u64,
+    data: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLogDto {
+    id: i64,
+    operator_type: String,
+    operator_id: i32,
+    operator_name: String,
+    session_id: Option<i64>,
+    api_key_id: Option<i64>,
+    action: String,
+    target_type: String,
+    target_id: Option<String>,
+    /// 列表接口不返回完整快照，需要时通过详情接口查看
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<serde_json::Value>,
+    diff: Option<serde_json::Value>,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+impl AuditLogDto {
+    fn new(value: audit_log::Model, with_snapshots: bool) -> Self {
+        let parse = |text: Option<String>| {
+            text.and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        };
+        let (before, after) = if with_snapshots {
+            (parse(value.before_data), parse(value.after_data))
+        } else {
+            (None, None)
+        };
+        Self {
+            id: value.id,
+            operator_type: value.operator_type,
+            operator_id: value.operator_id,
+            operator_name: value.operator_name,
+            session_id: value.session_id,
+            api_key_id: value.api_key_id,
+            action: value.action,
+            target_type: value.target_type,
+            target_id: value.target_id,
+            before,
+            after,
+            diff: parse(value.diff),
+            client_ip: value.client_ip,
+            user_agent: value.user_agent,
+            created_at: value.created_at,
+        }
+    }
+}
+
+/// 审计记录查询，按操作时间倒序，仅管理员可用。
+async fn page_list(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(params): Query<PageParams>,
+) -> AppResult<Json<PageResult<AuditLogDto>>> {
+    user.require(Permission::Manage, None)?;
+
+    let text = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let mut query = audit_log::Entity::find();
+    if let Some(operator) = text(params.operator) {
+        query = query.filter(audit_log::Column::OperatorName.eq(operator));
+    }
+    if let Some(action) = text(params.action) {
+        query = query.filter(audit_log::Column::Action.eq(action));
+    }
+    if let Some(target_type) = text(params.target_type) {
+        query = query.filter(audit_log::Column::TargetType.eq(target_type));
+    }
+    if let Some(target_id) = text(params.target_id) {
+        query = query.filter(audit_log::Column::TargetId.eq(target_id));
+    }
+    if let Some(client_ip) = text(params.client_ip) {
+        query = query.filter(audit_log::Column::ClientIp.eq(client_ip));
+    }
+    if let Some(start_time) = text(params.start_time) {
+        query = query.filter(audit_log::Column::CreatedAt.gte(parse_time(&start_time, false)?));
+    }
+    if let Some(end_time) = text(params.end_time) {
+        query = query.filter(audit_log::Column::CreatedAt.lte(parse_time(&end_time, true)?));
+    }
+
+    let start = params.start.unwrap_or(0);
+    let length = params.length.unwrap_or(20).clamp(1, MAX_LENGTH);
+
+    let total = query.clone().count(state.db()).await?;
+    let data = query
+        .order_by_desc(audit_log::Column::CreatedAt)
+        .order_by_desc(audit_log::Column::Id)
+        .offset(start)
+        .limit(length)
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(|item| AuditLogDto::new(item, false))
+        .collect();
+
+    Ok(Json(PageResult {
+        records_total: total,
+        records_filtered: total,
+        data,
+    }))
+}
+
+/// 单条审计记录，包含变更前后的完整快照。
+async fn detail(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> AppResult<Json<AuditLogDto>> {
+    user.require(Permission::Manage, None)?;
+    let model = audit_log::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("审计记录不存在".into()))?;
+    Ok(Json(AuditLogDto::new(model, true)))
+}
+
+/// 只给出日期时，开始时间取当天零点，结束时间取当天最后一秒。
+fn parse_time(value: &str, end_of_day: bool) -> AppResult<NaiveDateTime> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Ok(time);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| {
+            if end_of_day {
+                date.and_hms_opt(23, 59, 59)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+        })
+        .ok_or_else(|| AppError::BadRequest(format!("时间格式错误: {value}")))
+}
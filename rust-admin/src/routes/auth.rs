@@ -1,18 +1,35 @@
-use axum::{extract::State, routing::post, Json, Router};
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use validator::Validate;
 
-use crate::auth::AuthUser;
-use crate::entities::job_user;
+use crate::auth::{AuthUser, GroupRole, UserRole};
+use crate::authenticator::{self, AuthSource};
+use crate::entities::{job_user, job_user_session};
 use crate::error::{AppError, AppResult};
+use crate::login_guard;
+use crate::password;
+use crate::session::{self, ClientInfo};
 use crate::state::AppState;
+use crate::totp;
+
+use super::totp::TotpSetupDto;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/login", post(login))
+        .route("/login/totp", post(login_totp))
+        .route("/login/totp/enroll", post(login_totp_enroll))
         .route("/logout", post(logout))
+        .route("/me", get(me))
+        .route("/password", post(change_password))
+        .route("/sessions", get(my_sessions))
+        .route("/sessions/:id", delete(revoke_my_session))
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -26,64 +43,382 @@ struct LoginRequest {
 #[derive(Debug, Serialize)]
 struct LoginResponse {
     token: String,
+    expires_at: chrono::NaiveDateTime,
     user_id: i32,
     username: String,
     role: i8,
     permission: Option<String>,
+    /// 在登录过程中完成两步验证绑定时返回的恢复码，仅返回一次
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recovery_codes: Option<Vec<String>>,
+}
+
+/// 密码校验通过但需要两步验证时的响应，凭 `challenge` 提交验证码完成登录。
+#[derive(Debug, Serialize)]
+struct TotpChallengeResponse {
+    totp_required: bool,
+    /// 策略要求启用两步验证但尚未绑定，需要先调用 `/login/totp/enroll` 获取密钥
+    enrollment_required: bool,
+    challenge: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum LoginOutcome {
+    Session(LoginResponse),
+    TotpRequired(TotpChallengeResponse),
 }
 
+/// 登录成功时除返回令牌外同时写入管理台会话 Cookie。
 async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
     Json(payload): Json<LoginRequest>,
-) -> AppResult<Json<LoginResponse>> {
+) -> AppResult<(CookieJar, Json<LoginOutcome>)> {
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
 
-    let user = job_user::Entity::find()
-        .filter(job_user::Column::Username.eq(payload.username.trim()))
+    let username = payload.username.trim();
+    login_guard::ensure_allowed(&state, username, &client).await?;
+    let user = match authenticator::authenticate(&state, username, payload.password.trim()).await {
+        Ok(user) => user,
+        Err(AppError::Unauthorized(message)) => {
+            let delay = login_guard::record_failure(&state, username, &client).await?;
+            tokio::time::sleep(delay).await;
+            return Err(AppError::Unauthorized(message));
+        }
+        Err(err) => return Err(err),
+    };
+
+    let totp_enabled = totp::is_enabled(&state, user.id).await?;
+    if totp_enabled || totp::is_required(&state, &user) {
+        let (challenge, expires_in) = state.totp_challenges().begin(user.id, !totp_enabled);
+        let outcome = LoginOutcome::TotpRequired(TotpChallengeResponse {
+            totp_required: true,
+            enrollment_required: !totp_enabled,
+            challenge,
+            expires_in,
+        });
+        return Ok((jar, Json(outcome)));
+    }
+
+    let (jar, response) = issue_login(&state, &client, jar, user, None).await?;
+    Ok((jar, Json(LoginOutcome::Session(response))))
+}
+
+async fn issue_login(
+    state: &AppState,
+    client: &ClientInfo,
+    jar: CookieJar,
+    user: job_user::Model,
+    recovery_codes: Option<Vec<String>>,
+) -> AppResult<(CookieJar, LoginResponse)> {
+    // 密码与两步验证都通过后才清除失败计数，避免借助正确密码无限次猜测验证码
+    login_guard::record_success(state, &user.username).await?;
+    let issued = session::issue(state, user.id, client).await?;
+    let jar = session::set_cookies(jar, &state.settings().security, &issued);
+    let response = LoginResponse {
+        token: issued.token,
+        expires_at: issued.expires_at,
+        user_id: user.id,
+        username: user.username,
+        role: user.role,
+        permission: user.permission,
+        recovery_codes,
+    };
+    Ok((jar, response))
+}
+
+#[derive(Debug, Deserialize)]
+struct TotpChallengeRequest {
+    challenge: String,
+}
+
+/// 登录过程中为尚未绑定的管理员生成密钥，提交验证码后才会生效。
+async fn login_totp_enroll(
+    State(state): State<AppState>,
+    Json(payload): Json<TotpChallengeRequest>,
+) -> AppResult<Json<TotpSetupDto>> {
+    let challenge = state
+        .totp_challenges()
+        .get(&payload.challenge)
+        .ok_or_else(|| AppError::Unauthorized("验证已过期，请重新登录".into()))?;
+    if !challenge.enrollment_required {
+        return Err(AppError::BadRequest("该账号已绑定两步验证".into()));
+    }
+    let user = job_user::Entity::find_by_id(challenge.user_id)
         .one(state.db())
         .await?
-        .ok_or_else(|| AppError::Unauthorized("用户名或密码错误".into()))?;
+        .ok_or_else(|| AppError::Unauthorized("用户不存在".into()))?;
 
-    let hashed = hash_password(payload.password.trim());
-    if hashed != user.password {
-        return Err(AppError::Unauthorized("用户名或密码错误".into()));
+    let secret = totp::generate_secret();
+    let setup = TotpSetupDto::new(&state, &user.username, &secret)?;
+    state
+        .totp_challenges()
+        .set_enrollment_secret(&payload.challenge, &secret);
+    Ok(Json(setup))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct TotpLoginRequest {
+    challenge: String,
+    #[validate(length(min = 1, message = "验证码不能为空"))]
+    code: String,
+}
+
+/// 两步登录的第二步：提交验证码或恢复码，通过后签发会话。
+async fn login_totp(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(payload): Json<TotpLoginRequest>,
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+    let challenges = state.totp_challenges();
+    let challenge = challenges
+        .get(&payload.challenge)
+        .ok_or_else(|| AppError::Unauthorized("验证已过期，请重新登录".into()))?;
+    let user = job_user::Entity::find_by_id(challenge.user_id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("用户不存在".into()))?;
+    login_guard::ensure_allowed(&state, &user.username, &client).await?;
+
+    // 登录过程中绑定时校验待确认的密钥，否则校验已启用的密钥或恢复码
+    let enrollment = match challenge.enrollment_secret.as_deref() {
+        Some(secret) if challenge.enrollment_required => {
+            let now = chrono::Local::now().timestamp();
+            totp::verify_code(secret, &payload.code, now, None).map(|step| (secret, step))
+        }
+        _ if challenge.enrollment_required => {
+            return Err(AppError::BadRequest("请先获取两步验证密钥".into()));
+        }
+        _ => None,
+    };
+    let verified = enrollment.is_some()
+        || (!challenge.enrollment_required
+            && totp::verify_user_code(&state, user.id, &payload.code).await?);
+    if !verified {
+        challenges.record_failure(&payload.challenge);
+        let delay = login_guard::record_failure(&state, &user.username, &client).await?;
+        tokio::time::sleep(delay).await;
+        return Err(AppError::Unauthorized("验证码错误".into()));
     }
+    let recovery_codes = match enrollment {
+        Some((secret, step)) => Some(totp::enable(&state, user.id, secret, step).await?),
+        None => None,
+    };
+
+    if !challenges.finish(&payload.challenge) {
+        return Err(AppError::Unauthorized("验证已过期，请重新登录".into()));
+    }
+    let (jar, response) = issue_login(&state, &client, jar, user, recovery_codes).await?;
+    Ok((jar, Json(response)))
+}
+
+async fn logout(
+    State(state): State<AppState>,
+    user: AuthUser,
+    jar: CookieJar,
+) -> AppResult<(CookieJar, Json<serde_json::Value>)> {
+    let session_id = user.require_session()?;
+    job_user_session::Entity::delete_by_id(session_id)
+        .exec(state.db())
+        .await?;
 
-    let token = uuid::Uuid::new_v4().to_string();
+    Ok((
+        session::clear_cookies(jar),
+        Json(serde_json::json!({ "message": "已退出登录" })),
+    ))
+}
 
-    let mut active: job_user::ActiveModel = user.into();
-    active.token = Set(Some(token.clone()));
-    let updated = active.update(state.db()).await?;
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupRoleDto {
+    job_group: i32,
+    role: GroupRole,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileDto {
+    id: i32,
+    username: String,
+    role: UserRole,
+    permission: Option<String>,
+    /// `user` 为登录用户，`service_account` 为使用 API Key 的服务账号
+    principal_type: &'static str,
+    group_roles: Vec<GroupRoleDto>,
+    session_id: Option<i64>,
+    api_key_id: Option<i64>,
+    /// 仅登录用户返回
+    totp_enabled: Option<bool>,
+}
 
-    Ok(Json(LoginResponse {
-        token,
-        user_id: updated.id,
-        username: updated.username,
-        role: updated.role,
-        permission: updated.permission,
+/// 当前调用方的身份与权限范围。
+async fn me(State(state): State<AppState>, user: AuthUser) -> AppResult<Json<ProfileDto>> {
+    let totp_enabled = match user.session_id {
+        Some(_) => Some(totp::is_enabled(&state, user.id).await?),
+        None => None,
+    };
+    Ok(Json(ProfileDto {
+        principal_type: if user.api_key_id.is_some() {
+            "service_account"
+        } else {
+            "user"
+        },
+        group_roles: user
+            .group_roles
+            .iter()
+            .map(|(job_group, role)| GroupRoleDto {
+                job_group: *job_group,
+                role: *role,
+            })
+            .collect(),
+        id: user.id,
+        username: user.username,
+        role: user.role,
+        permission: user.permission,
+        session_id: user.session_id,
+        api_key_id: user.api_key_id,
+        totp_enabled,
     }))
 }
 
-async fn logout(
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "原密码不能为空"))]
+    old_password: String,
+    #[validate(length(min = 1, message = "新密码不能为空"))]
+    new_password: String,
+}
+
+/// 修改自己的密码，成功后注销当前会话以外的所有会话。
+///
+/// 原密码错误与登录失败一样计入失败次数并受锁定约束，避免借助被盗用的会话猜测密码。
+async fn change_password(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangePasswordRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let record = job_user::Entity::find_by_id(user.id)
+    let session_id = user.require_session()?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+
+    let account = job_user::Entity::find_by_id(user.id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
+    if account.auth_source != AuthSource::Local.as_str() {
+        return Err(AppError::BadRequest(format!(
+            "该账号通过 {} 登录，请在对应的身份源修改密码",
+            account.auth_source.to_uppercase()
+        )));
+    }
+    let old_password = payload.old_password.trim();
+    let new_password = payload.new_password.trim();
+    login_guard::ensure_allowed(&state, &account.username, &client).await?;
+    if password::verify_password(old_password, &account.password).is_none() {
+        let delay = login_guard::record_failure(&state, &account.username, &client).await?;
+        tokio::time::sleep(delay).await;
+        return Err(AppError::BadRequest("原密码错误".into()));
+    }
+    login_guard::record_success(&state, &account.username).await?;
+    if old_password == new_password {
+        return Err(AppError::BadRequest("新密码不能与原密码相同".into()));
+    }
+    password::check_strength(
+        &state.settings().security.password_policy,
+        &account.username,
+        new_password,
+    )?;
 
-    let mut active: job_user::ActiveModel = record.into();
-    active.token = Set(None);
+    let mut active: job_user::ActiveModel = account.into();
+    active.password = Set(password::hash_password(new_password)?);
     active.update(state.db()).await?;
+    let revoked = session::revoke_all(state.db(), user.id, Some(session_id)).await?;
 
-    Ok(Json(serde_json::json!({ "message": "已退出登录" })))
+    Ok(Json(serde_json::json!({
+        "message": "密码已修改",
+        "revokedSessions": revoked
+    })))
 }
 
-fn hash_password(input: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(input.as_bytes());
-    hex::encode(hasher.finalize())
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SessionDto {
+    id: i64,
+    issued_at: chrono::NaiveDateTime,
+    expires_at: chrono::NaiveDateTime,
+    last_used_at: Option<chrono::NaiveDateTime>,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+    current: bool,
+}
+
+impl SessionDto {
+    fn new(value: job_user_session::Model, current_session: Option<i64>) -> Self {
+        Self {
+            current: current_session == Some(value.id),
+            id: value.id,
+            issued_at: value.issued_at,
+            expires_at: value.expires_at,
+            last_used_at: value.last_used_at,
+            client_ip: value.client_ip,
+            user_agent: value.user_agent,
+        }
+    }
+}
+
+/// 列出用户仍然有效的会话，`current_session` 用于标记发起请求的会话。
+pub(super) async fn list_sessions(
+    state: &AppState,
+    user_id: i32,
+    current_session: Option<i64>,
+) -> AppResult<Vec<SessionDto>> {
+    let now = chrono::Local::now().naive_local();
+    let sessions = job_user_session::Entity::find()
+        .filter(job_user_session::Column::UserId.eq(user_id))
+        .filter(job_user_session::Column::ExpiresAt.gt(now))
+        .order_by_desc(job_user_session::Column::LastUsedAt)
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(|item| SessionDto::new(item, current_session))
+        .collect();
+    Ok(sessions)
+}
+
+async fn my_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> AppResult<Json<Vec<SessionDto>>> {
+    let session_id = user.require_session()?;
+    let sessions = list_sessions(&state, user.id, Some(session_id)).await?;
+    Ok(Json(sessions))
+}
+
+async fn revoke_my_session(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require_session()?;
+    let result = job_user_session::Entity::delete_many()
+        .filter(job_user_session::Column::Id.eq(id))
+        .filter(job_user_session::Column::UserId.eq(user.id))
+        .exec(state.db())
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("会话不存在".into()));
+    }
+    Ok(Json(serde_json::json!({ "message": "会话已注销" })))
 }
@@ -1,6 +1,7 @@
 use axum::{extract::State, routing::get, Json, Router};
-use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
-use sea_orm::{query::*, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use sea_orm::sea_query::Expr;
+use sea_orm::{query::*, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use serde::Serialize;
 
 use crate::auth::AuthUser;
@@ -25,7 +26,11 @@ struct SummaryDto {
     log_fail_count: i64,
 }
 
-async fn summary(State(state): State<AppState>, _user: AuthUser) -> AppResult<Json<SummaryDto>> {
+async fn summary(State(state): State<AppState>, user: AuthUser) -> AppResult<Json<SummaryDto>> {
+    if let Some(groups) = user.group_scope() {
+        return scoped_summary(&state, groups).await.map(Json);
+    }
+
     let group_count = job_group::Entity::find().count(state.db()).await? as u64;
     let job_count = job_info::Entity::find().count(state.db()).await? as u64;
     let log_total_count = job_log::Entity::find().count(state.db()).await? as u64;
@@ -62,10 +67,24 @@ struct ChartPoint {
     fail_count: i32,
 }
 
-async fn chart(State(state): State<AppState>, _user: AuthUser) -> AppResult<Json<Vec<ChartPoint>>> {
+async fn chart(State(state): State<AppState>, user: AuthUser) -> AppResult<Json<Vec<ChartPoint>>> {
     let today = Local::now().date_naive();
     let start_day = today - Duration::days(7);
 
+    if let Some(groups) = user.group_scope() {
+        let result = daily_log_stats(&state, groups, start_day)
+            .await?
+            .into_iter()
+            .map(|(day, running, suc, fail)| ChartPoint {
+                trigger_day: day.format("%Y-%m-%d").to_string(),
+                running_count: running as i32,
+                suc_count: suc as i32,
+                fail_count: fail as i32,
+            })
+            .collect();
+        return Ok(Json(result));
+    }
+
     let rows = job_log_report::Entity::find()
         .filter(job_log_report::Column::TriggerDay.gte(start_day.and_hms_opt(0, 0, 0).unwrap()))
         .order_by_asc(job_log_report::Column::TriggerDay)
@@ -87,3 +106,76 @@ async fn chart(State(state): State<AppState>, _user: AuthUser) -> AppResult<Json
 
     Ok(Json(result))
 }
+
+/// 普通用户只统计授权执行器下的数据，`xxl_job_log_report` 为全局汇总，无法按执行器拆分。
+async fn scoped_summary(state: &AppState, groups: Vec<i32>) -> AppResult<SummaryDto> {
+    let group_count = job_group::Entity::find()
+        .filter(job_group::Column::Id.is_in(groups.clone()))
+        .count(state.db())
+        .await? as u64;
+    let job_count = job_info::Entity::find()
+        .filter(job_info::Column::JobGroup.is_in(groups.clone()))
+        .count(state.db())
+        .await? as u64;
+    let log_total_count = job_log::Entity::find()
+        .filter(job_log::Column::JobGroup.is_in(groups.clone()))
+        .count(state.db())
+        .await? as u64;
+
+    let today = Local::now().date_naive();
+    let (running, suc, fail) = daily_log_stats(state, groups, today)
+        .await?
+        .into_iter()
+        .find(|(day, ..)| *day == today)
+        .map(|(_, running, suc, fail)| (running, suc, fail))
+        .unwrap_or_default();
+
+    Ok(SummaryDto {
+        group_count,
+        job_count,
+        log_total_count,
+        log_running_count: running,
+        log_success_count: suc,
+        log_fail_count: fail,
+    })
+}
+
+/// 按天统计运行中 / 成功 / 失败的调度日志数量，口径与 Java 版日志报表一致。
+async fn daily_log_stats(
+    state: &AppState,
+    groups: Vec<i32>,
+    start_day: NaiveDate,
+) -> AppResult<Vec<(NaiveDate, i64, i64, i64)>> {
+    const RUNNING: &str = "trigger_code IN (0, 200) AND handle_code = 0";
+    const SUCCESS: &str = "handle_code = 200";
+
+    let rows = job_log::Entity::find()
+        .select_only()
+        .column_as(Expr::cust("DATE(trigger_time)"), "trigger_day")
+        .column_as(
+            Expr::cust(format!(
+                "CAST(SUM(CASE WHEN {RUNNING} THEN 1 ELSE 0 END) AS SIGNED)"
+            )),
+            "running_count",
+        )
+        .column_as(
+            Expr::cust(format!(
+                "CAST(SUM(CASE WHEN {SUCCESS} THEN 1 ELSE 0 END) AS SIGNED)"
+            )),
+            "suc_count",
+        )
+        .column_as(
+            Expr::cust(format!(
+                "CAST(SUM(CASE WHEN {SUCCESS} OR ({RUNNING}) THEN 0 ELSE 1 END) AS SIGNED)"
+            )),
+            "fail_count",
+        )
+        .filter(job_log::Column::JobGroup.is_in(groups))
+        .filter(job_log::Column::TriggerTime.gte(start_day.and_hms_opt(0, 0, 0).unwrap()))
+        .group_by(Expr::cust("DATE(trigger_time)"))
+        .order_by_asc(Expr::cust("DATE(trigger_time)"))
+        .into_tuple::<(NaiveDate, i64, i64, i64)>()
+        .all(state.db())
+        .await?;
+    Ok(rows)
+}
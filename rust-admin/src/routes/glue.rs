@@ -1,22 +1,32 @@
 use axum::{
-    extract::{Path, State},
-    routing::get,
+    extract::{Path, Query, State},
+    routing::{get, post},
     Json, Router,
 };
 use chrono::Local;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::auth::AuthUser;
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
 use crate::entities::{job_info, job_logglue};
 use crate::error::{AppError, AppResult};
+use crate::glue::syntax::{self, GlueType, SyntaxCheck};
+use crate::glue::{self, LineDiff};
+use crate::job_revision::{self, RevisionAction};
+use crate::session::ClientInfo;
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/:job_id", get(load_glue).post(save_glue))
         .route("/:job_id/versions", get(list_versions))
+        .route("/:job_id/versions/:id/rollback", post(rollback_version))
+        .route("/:job_id/diff", get(diff_versions))
+        .route("/:job_id/check", post(check_syntax))
 }
 
 #[derive(Debug, Serialize)]
@@ -43,19 +53,18 @@ impl From<job_info::Model> for GlueDto {
 
 async fn load_glue(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Path(job_id): Path<i32>,
 ) -> AppResult<Json<GlueDto>> {
-    let job = job_info::Entity::find_by_id(job_id)
-        .one(state.db())
-        .await?
-        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    let job = find_job(&state, &user, job_id, Permission::View).await?;
     Ok(Json(GlueDto::from(job)))
 }
 
 #[derive(Debug, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 struct SaveGlueRequest {
+    /// 不传时沿用任务当前的 GLUE 类型
+    glue_type: Option<String>,
     #[validate(length(min = 1, message = "GLUE 内容不能为空"))]
     glue_source: String,
     #[validate(length(min = 1, max = 128, message = "备注需在 1-128 字符之间"))]
@@ -65,40 +74,94 @@ struct SaveGlueRequest {
 async fn save_glue(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(job_id): Path<i32>,
     Json(payload): Json<SaveGlueRequest>,
 ) -> AppResult<Json<GlueDto>> {
-    user.require_admin()?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
 
-    let mut job = job_info::Entity::find_by_id(job_id)
-        .one(state.db())
-        .await?
-        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    let job = find_job(&state, &user, job_id, Permission::Develop).await?;
+    let glue_type = match payload.glue_type {
+        Some(glue_type) => parse_glue_type(&glue_type)?.as_str().to_string(),
+        None => job.glue_type.clone(),
+    };
+    if state.settings().glue.syntax_check {
+        if let Some(result) = check_source(&state, &glue_type, &payload.glue_source).await {
+            if !result.passed() {
+                let errors: Vec<String> = result.errors.iter().map(ToString::to_string).collect();
+                return Err(AppError::BadRequest(format!(
+                    "GLUE 语法检查未通过: {}",
+                    errors.join("；")
+                )));
+            }
+        }
+    }
+
+    let after = store_glue(
+        &state,
+        &user,
+        &client,
+        job,
+        glue_type,
+        payload.glue_source,
+        payload.glue_remark,
+    )
+    .await?;
+    Ok(Json(after))
+}
+
+/// 将 GLUE 写入任务并追加一条历史版本，同时记录任务版本与审计，并按配置清理过旧的历史版本。
+async fn store_glue(
+    state: &AppState,
+    user: &AuthUser,
+    client: &ClientInfo,
+    mut job: job_info::Model,
+    glue_type: String,
+    glue_source: String,
+    glue_remark: String,
+) -> AppResult<GlueDto> {
+    let job_id = job.id;
+    let txn = state.db().begin().await?;
+    job_revision::ensure_baseline(&txn, &job).await?;
+    let before = GlueDto::from(job.clone());
 
     let now = Local::now().naive_local();
-    job.glue_source = Some(payload.glue_source.clone());
-    job.glue_remark = Some(payload.glue_remark.clone());
+    job.glue_type = glue_type;
+    job.glue_source = Some(glue_source.clone());
+    job.glue_remark = Some(glue_remark.clone());
     job.glue_updatetime = Some(now);
     job.update_time = Some(now);
 
-    let active: job_info::ActiveModel = job.clone().into();
-    let updated = active.update(state.db()).await?;
+    let active: job_info::ActiveModel = job.into();
+    let updated = active.reset_all().update(&txn).await?;
 
     let log = job_logglue::ActiveModel {
         job_id: Set(job_id),
         glue_type: Set(Some(updated.glue_type.clone())),
-        glue_source: Set(Some(payload.glue_source.clone())),
-        glue_remark: Set(payload.glue_remark.clone()),
+        glue_source: Set(Some(glue_source)),
+        glue_remark: Set(glue_remark),
         add_time: Set(Some(now)),
         update_time: Set(Some(now)),
         ..Default::default()
     };
-    job_logglue::Entity::insert(log).exec(state.db()).await?;
+    job_logglue::Entity::insert(log).exec(&txn).await?;
+    glue::prune_versions(&txn, job_id, state.settings().glue.max_versions).await?;
+    job_revision::record(&txn, &updated, RevisionAction::Glue, &user.username).await?;
 
-    Ok(Json(GlueDto::from(updated)))
+    let after = GlueDto::from(updated);
+    audit::record(
+        &txn,
+        user,
+        client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::Glue, job_id)
+            .before(&before)
+            .after(&after),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(after)
 }
 
 #[derive(Debug, Serialize)]
@@ -106,6 +169,8 @@ async fn save_glue(
 struct GlueVersionDto {
     id: i32,
     glue_type: Option<String>,
+    /// 与上一个版本的 GLUE 类型不同时为上一个版本的类型
+    previous_glue_type: Option<String>,
     glue_source: Option<String>,
     glue_remark: String,
     add_time: Option<chrono::NaiveDateTime>,
@@ -117,6 +182,7 @@ impl From<job_logglue::Model> for GlueVersionDto {
         Self {
             id: value.id,
             glue_type: value.glue_type,
+            previous_glue_type: None,
             glue_source: value.glue_source,
             glue_remark: value.glue_remark,
             add_time: value.add_time,
@@ -127,16 +193,241 @@ impl From<job_logglue::Model> for GlueVersionDto {
 
 async fn list_versions(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Path(job_id): Path<i32>,
 ) -> AppResult<Json<Vec<GlueVersionDto>>> {
-    let versions = job_logglue::Entity::find()
+    find_job(&state, &user, job_id, Permission::View).await?;
+    let mut versions: Vec<GlueVersionDto> = job_logglue::Entity::find()
         .filter(job_logglue::Column::JobId.eq(job_id))
         .order_by_desc(job_logglue::Column::AddTime)
+        .order_by_desc(job_logglue::Column::Id)
         .all(state.db())
         .await?
         .into_iter()
         .map(GlueVersionDto::from)
         .collect();
+    // 列表按时间倒序，下一条即为更早的版本，据此标出切换了 GLUE 类型的版本
+    for index in 1..versions.len() {
+        let previous = versions[index].glue_type.clone();
+        let current = &mut versions[index - 1];
+        if previous.is_some() && current.glue_type != previous {
+            current.previous_glue_type = previous;
+        }
+    }
     Ok(Json(versions))
 }
+
+/// 回滚到指定历史版本：版本内容成为当前 GLUE，回滚本身作为一个新版本记录。
+async fn rollback_version(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path((job_id, id)): Path<(i32, i32)>,
+) -> AppResult<Json<GlueDto>> {
+    let job = find_job(&state, &user, job_id, Permission::Develop).await?;
+    let version = find_version(&state, job_id, id).await?;
+
+    let glue_type = version.glue_type.unwrap_or_else(|| job.glue_type.clone());
+    let remark: String = format!("回滚至版本 #{id}: {}", version.glue_remark)
+        .chars()
+        .take(128)
+        .collect();
+    let after = store_glue(
+        &state,
+        &user,
+        &client,
+        job,
+        glue_type,
+        version.glue_source.unwrap_or_default(),
+        remark,
+    )
+    .await?;
+    Ok(Json(after))
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffParams {
+    /// 历史版本 ID
+    from: i32,
+    /// 历史版本 ID，不传时与当前 GLUE 对比
+    to: Option<i32>,
+}
+
+/// 按行对比两个历史版本，或某个历史版本与当前 GLUE。
+async fn diff_versions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(job_id): Path<i32>,
+    Query(params): Query<DiffParams>,
+) -> AppResult<Json<LineDiff>> {
+    let job = find_job(&state, &user, job_id, Permission::View).await?;
+    let from = find_version(&state, job_id, params.from).await?;
+    let (to_name, to_source) = match params.to {
+        Some(to) => {
+            let version = find_version(&state, job_id, to).await?;
+            (format!("#{to}"), version.glue_source)
+        }
+        None => ("current".to_string(), job.glue_source),
+    };
+    Ok(Json(glue::line_diff(
+        from.glue_source.as_deref().unwrap_or_default(),
+        to_source.as_deref().unwrap_or_default(),
+        &format!("#{}", params.from),
+        &to_name,
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckRequest {
+    /// 不传时使用任务当前的 GLUE 类型
+    glue_type: Option<String>,
+    glue_source: String,
+}
+
+/// 仅做语法检查不保存，便于编辑器在提交前提示出错行。
+async fn check_syntax(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(job_id): Path<i32>,
+    Json(payload): Json<CheckRequest>,
+) -> AppResult<Json<SyntaxCheck>> {
+    let job = find_job(&state, &user, job_id, Permission::Develop).await?;
+    let glue_type = match payload.glue_type {
+        Some(glue_type) => parse_glue_type(&glue_type)?,
+        None => parse_glue_type(&job.glue_type)?,
+    };
+    Ok(Json(
+        syntax::check(glue_type, &payload.glue_source, &state.settings().glue).await,
+    ))
+}
+
+fn parse_glue_type(value: &str) -> AppResult<GlueType> {
+    GlueType::parse(value.trim())
+        .ok_or_else(|| AppError::BadRequest(format!("不支持的 GLUE 类型: {value}")))
+}
+
+/// BEAN 等非脚本类型不做检查，返回 `None`。
+async fn check_source(state: &AppState, glue_type: &str, source: &str) -> Option<SyntaxCheck> {
+    let glue_type = GlueType::parse(glue_type)?;
+    Some(syntax::check(glue_type, source, &state.settings().glue).await)
+}
+
+async fn find_version(state: &AppState, job_id: i32, id: i32) -> AppResult<job_logglue::Model> {
+    job_logglue::Entity::find_by_id(id)
+        .filter(job_logglue::Column::JobId.eq(job_id))
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("GLUE 版本 {id} 不存在")))
+}
+
+async fn find_job(
+    state: &AppState,
+    user: &AuthUser,
+    job_id: i32,
+    permission: Permission,
+) -> AppResult<job_info::Model> {
+    let job = job_info::Entity::find_by_id(job_id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(permission, Some(job.job_group))?;
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    async fn shell_job(state: &AppState, source: &str) -> job_info::Model {
+        let job = test_support::insert_job(state, 1, 1).await;
+        job_info::ActiveModel {
+            id: Set(job.id),
+            glue_type: Set("GLUE_SHELL".into()),
+            glue_source: Set(Some(source.into())),
+            glue_remark: Set(Some("当前版本".into())),
+            ..Default::default()
+        }
+        .update(state.db())
+        .await
+        .unwrap()
+    }
+
+    async fn stored_job(state: &AppState, id: i32) -> job_info::Model {
+        job_info::Entity::find_by_id(id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rollback_writes_version_back_to_job() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let job = shell_job(&state, "echo v2\n").await;
+        let version = job_logglue::Entity::insert(job_logglue::ActiveModel {
+            job_id: Set(job.id),
+            glue_type: Set(Some("GLUE_SHELL".into())),
+            glue_source: Set(Some("echo v1\n".into())),
+            glue_remark: Set("v1".into()),
+            ..Default::default()
+        })
+        .exec(state.db())
+        .await
+        .unwrap()
+        .last_insert_id;
+
+        let Json(dto) = rollback_version(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path((job.id, version)),
+        )
+        .await
+        .unwrap();
+
+        let stored = stored_job(&state, job.id).await;
+        assert_eq!(stored.glue_source.as_deref(), Some("echo v1\n"));
+        assert_eq!(
+            stored.glue_remark.as_deref(),
+            Some(format!("回滚至版本 #{version}: v1").as_str())
+        );
+        assert!(stored.glue_updatetime.is_some());
+        assert_eq!(dto.glue_source, stored.glue_source);
+    }
+
+    #[tokio::test]
+    async fn save_persists_changed_glue_type() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let job = shell_job(&state, "echo ok\n").await;
+
+        let Json(dto) = save_glue(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(job.id),
+            Json(SaveGlueRequest {
+                glue_type: Some("GLUE_PYTHON".into()),
+                glue_source: "print('ok')\n".into(),
+                glue_remark: "改用 Python".into(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stored = stored_job(&state, job.id).await;
+        assert_eq!(stored.glue_type, "GLUE_PYTHON");
+        assert_eq!(dto.glue_type, stored.glue_type);
+        assert_eq!(stored.glue_source.as_deref(), Some("print('ok')\n"));
+        let version = job_logglue::Entity::find()
+            .filter(job_logglue::Column::JobId.eq(job.id))
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.glue_type.as_deref(), Some("GLUE_PYTHON"));
+    }
+}
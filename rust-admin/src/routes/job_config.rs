@@ -0,0 +1,756 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Local, NaiveDateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
+use crate::entities::{job_group, job_info, job_logglue};
+use crate::error::{AppError, AppResult};
+use crate::glue::{
+    self,
+    syntax::{self, GlueType},
+};
+use crate::job_config::{
+    self, ConfigFormat, GroupConfig, JobConfig, JobEntry, JobRef, CONFIG_VERSION,
+};
+use crate::job_revision::{self, RevisionAction};
+use crate::job_trash;
+use crate::routes::job_info::compute_next_for_params;
+use crate::session::ClientInfo;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/export", get(export))
+        .route("/import", post(import))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportParams {
+    format: Option<String>,
+    /// 只导出指定执行器，默认导出当前用户可查看的全部执行器
+    app_name: Option<String>,
+}
+
+fn parse_format(value: Option<&str>) -> AppResult<ConfigFormat> {
+    ConfigFormat::parse(value)
+        .ok_or_else(|| AppError::BadRequest("format 只支持 yaml 或 json".into()))
+}
+
+async fn export(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(params): Query<ExportParams>,
+) -> AppResult<Response> {
+    let format = parse_format(params.format.as_deref())?;
+    let app_name = params
+        .app_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+
+    let mut query = job_group::Entity::find().order_by_asc(job_group::Column::AppName);
+    if let Some(app_name) = app_name {
+        query = query.filter(job_group::Column::AppName.eq(app_name));
+    }
+    if let Some(scope) = user.group_scope() {
+        query = query.filter(job_group::Column::Id.is_in(scope));
+    }
+    let groups = query.all(state.db()).await?;
+    if app_name.is_some() && groups.is_empty() {
+        return Err(AppError::NotFound("执行器不存在".into()));
+    }
+
+    let jobs = job_info::Entity::find()
+        .filter(job_info::Column::JobGroup.is_in(groups.iter().map(|group| group.id)))
+        .order_by_asc(job_info::Column::Id)
+        .all(state.db())
+        .await?;
+    let refs = child_refs(state.db(), &jobs).await?;
+
+    let config = JobConfig {
+        version: CONFIG_VERSION,
+        groups: groups
+            .iter()
+            .map(|group| GroupConfig {
+                app_name: group.app_name.clone(),
+                title: group.title.clone(),
+                address_type: group.address_type,
+                // 自动注册的地址由注册表维护，不属于配置
+                address_list: if group.address_type == 1 {
+                    group.address_list.clone()
+                } else {
+                    None
+                },
+                jobs: jobs
+                    .iter()
+                    .filter(|job| job.job_group == group.id)
+                    .map(|job| entry_of(job, &group.app_name, &refs))
+                    .collect(),
+            })
+            .collect(),
+    };
+    let body = format
+        .render(&config)
+        .map_err(|err| AppError::Internal(anyhow::anyhow!(err)))?;
+
+    let file_name = format!(
+        "xxl-job-config-{}.{}",
+        Local::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// 子任务 ID 到 `appName/key` 引用的映射，子任务可能位于其他执行器。
+async fn child_refs<C: ConnectionTrait>(
+    conn: &C,
+    jobs: &[job_info::Model],
+) -> AppResult<HashMap<i32, JobRef>> {
+    let ids: HashSet<i32> = jobs
+        .iter()
+        .flat_map(|job| job_config::parse_child_ids(job.child_jobid.as_deref()))
+        .collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let children = job_info::Entity::find()
+        .filter(job_info::Column::Id.is_in(ids))
+        .all(conn)
+        .await?;
+    let app_names: HashMap<i32, String> = job_group::Entity::find()
+        .filter(job_group::Column::Id.is_in(children.iter().map(|job| job.job_group)))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|group| (group.id, group.app_name))
+        .collect();
+    Ok(children
+        .iter()
+        .filter_map(|job| {
+            let app_name = app_names.get(&job.job_group)?;
+            Some((
+                job.id,
+                JobRef {
+                    app_name: Some(app_name.clone()),
+                    key: job_config::job_key_of(job),
+                },
+            ))
+        })
+        .collect())
+}
+
+/// 已删除的子任务不会出现在引用中，导入时会一并清理。
+fn entry_of(job: &job_info::Model, app_name: &str, refs: &HashMap<i32, JobRef>) -> JobEntry {
+    let child_jobs = job_config::parse_child_ids(job.child_jobid.as_deref())
+        .into_iter()
+        .filter_map(|id| refs.get(&id))
+        .map(|item| item.canonical(app_name))
+        .collect();
+    JobEntry::from_model(job, app_name, child_jobs)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportParams {
+    format: Option<String>,
+    /// 只计算差异，不写入
+    #[serde(default)]
+    dry_run: bool,
+    /// 删除文件中执行器下未列出的任务
+    #[serde(default)]
+    prune: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobChange {
+    app_name: String,
+    key: String,
+    action: ChangeAction,
+    job_id: Option<i32>,
+    /// 更新时变化的字段，`{"字段": [当前值, 文件中的值]}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportResult {
+    dry_run: bool,
+    groups_created: Vec<String>,
+    created: usize,
+    updated: usize,
+    deleted: usize,
+    unchanged: usize,
+    changes: Vec<JobChange>,
+}
+
+/// 导入计划中的一个任务，`existing` 为空时新建。
+struct PlannedJob<'a> {
+    app_name: &'a str,
+    entry: &'a JobEntry,
+    existing: Option<job_info::Model>,
+}
+
+async fn import(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Query(params): Query<ImportParams>,
+    body: String,
+) -> AppResult<Json<ImportResult>> {
+    let format = parse_format(params.format.as_deref())?;
+    let mut config = format
+        .read(&body)
+        .map_err(|err| AppError::BadRequest(format!("配置文件解析失败: {err}")))?;
+    let errors = config.validate();
+    if !errors.is_empty() {
+        return Err(AppError::BadRequest(errors.join("；")));
+    }
+    for group in &mut config.groups {
+        group.app_name = group.app_name.trim().to_string();
+        let app_name = group.app_name.clone();
+        group.jobs = std::mem::take(&mut group.jobs)
+            .into_iter()
+            .map(|job| job.normalized(&app_name))
+            .collect();
+    }
+
+    // 已有执行器需要开发权限，新建执行器需要管理员权限
+    let mut group_ids: HashMap<String, i32> = job_group::Entity::find()
+        .filter(job_group::Column::AppName.is_in(config.groups.iter().map(|g| g.app_name.clone())))
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(|group| (group.app_name, group.id))
+        .collect();
+    for group in &config.groups {
+        match group_ids.get(&group.app_name) {
+            Some(id) => user.require(Permission::Develop, Some(*id))?,
+            None => user.require(Permission::Manage, None)?,
+        }
+    }
+
+    let existing_jobs = job_info::Entity::find()
+        .filter(job_info::Column::JobGroup.is_in(group_ids.values().copied()))
+        .order_by_asc(job_info::Column::Id)
+        .all(state.db())
+        .await?;
+    let mut existing_by_key: HashMap<(i32, String), job_info::Model> = existing_jobs
+        .iter()
+        .map(|job| ((job.job_group, job_config::job_key_of(job)), job.clone()))
+        .collect();
+
+    let mut errors = validate_schedules(&config);
+    errors.extend(validate_child_refs(&state, &config, &group_ids, params.prune).await?);
+    if state.settings().glue.syntax_check {
+        errors.extend(validate_glue_syntax(&state, &config, &group_ids, &existing_by_key).await);
+    }
+    if !errors.is_empty() {
+        return Err(AppError::BadRequest(errors.join("；")));
+    }
+
+    // 计算差异
+    let refs = child_refs(state.db(), &existing_jobs).await?;
+    let mut result = ImportResult {
+        dry_run: params.dry_run,
+        groups_created: Vec::new(),
+        created: 0,
+        updated: 0,
+        deleted: 0,
+        unchanged: 0,
+        changes: Vec::new(),
+    };
+    let mut planned = Vec::new();
+    for group in &config.groups {
+        if !group_ids.contains_key(&group.app_name) {
+            result.groups_created.push(group.app_name.clone());
+        }
+        for entry in &group.jobs {
+            let existing = group_ids
+                .get(&group.app_name)
+                .and_then(|id| existing_by_key.remove(&(*id, entry.key.clone())));
+            match &existing {
+                None => {
+                    result.created += 1;
+                    result.changes.push(JobChange {
+                        app_name: group.app_name.clone(),
+                        key: entry.key.clone(),
+                        action: ChangeAction::Create,
+                        job_id: None,
+                        changes: None,
+                    });
+                }
+                Some(job) => {
+                    let current = entry_of(job, &group.app_name, &refs);
+                    let changes = audit::diff(
+                        &serde_json::to_value(&current).map_err(AppError::internal)?,
+                        &serde_json::to_value(entry).map_err(AppError::internal)?,
+                    )
+                    .filter(|diff| diff.as_object().is_some_and(|map| !map.is_empty()));
+                    match changes {
+                        Some(changes) => {
+                            result.updated += 1;
+                            result.changes.push(JobChange {
+                                app_name: group.app_name.clone(),
+                                key: entry.key.clone(),
+                                action: ChangeAction::Update,
+                                job_id: Some(job.id),
+                                changes: Some(changes),
+                            });
+                        }
+                        None => result.unchanged += 1,
+                    }
+                }
+            }
+            planned.push(PlannedJob {
+                app_name: &group.app_name,
+                entry,
+                existing,
+            });
+        }
+    }
+    // 剩下的是文件中执行器下未列出的任务
+    let mut pruned: Vec<(String, job_info::Model)> = Vec::new();
+    if params.prune {
+        let app_names: HashMap<i32, &str> = group_ids
+            .iter()
+            .map(|(app_name, id)| (*id, app_name.as_str()))
+            .collect();
+        let mut leftovers: Vec<job_info::Model> = existing_by_key.into_values().collect();
+        leftovers.sort_by_key(|job| job.id);
+        for job in leftovers {
+            let app_name = app_names[&job.job_group].to_string();
+            result.deleted += 1;
+            result.changes.push(JobChange {
+                app_name: app_name.clone(),
+                key: job_config::job_key_of(&job),
+                action: ChangeAction::Delete,
+                job_id: Some(job.id),
+                changes: None,
+            });
+            pruned.push((app_name, job));
+        }
+    }
+    if params.dry_run || result.changes.is_empty() {
+        return Ok(Json(result));
+    }
+
+    let txn = state.db().begin().await?;
+    let now = Local::now().naive_local();
+    for group in &config.groups {
+        if group_ids.contains_key(&group.app_name) {
+            continue;
+        }
+        let inserted = job_group::Entity::insert(job_group::ActiveModel {
+            app_name: Set(group.app_name.clone()),
+            title: Set(group.title.trim().to_string()),
+            address_type: Set(group.address_type),
+            address_list: Set(if group.address_type == 1 {
+                group.address_list.clone()
+            } else {
+                None
+            }),
+            update_time: Set(Some(now)),
+            ..Default::default()
+        })
+        .exec_with_returning(&txn)
+        .await?;
+        group_ids.insert(inserted.app_name, inserted.id);
+    }
+
+    // 第一轮写入定义，第二轮在所有任务都有 ID 后解析子任务
+    let mut written: Vec<(RevisionAction, job_info::Model)> = Vec::new();
+    let mut ids_by_ref: HashMap<(String, String), i32> = HashMap::new();
+    for plan in &planned {
+        let group_id = group_ids[plan.app_name];
+        let job = match &plan.existing {
+            None => {
+                let mut job = blank_job(group_id, now);
+                plan.entry.apply(&mut job);
+                let mut active: job_info::ActiveModel = job.into();
+                active.id = NotSet;
+                let inserted = job_info::Entity::insert(active)
+                    .exec_with_returning(&txn)
+                    .await?;
+                if inserted.glue_type != "BEAN" {
+                    record_glue(&txn, &state, &inserted, now).await?;
+                }
+                written.push((RevisionAction::Create, inserted.clone()));
+                inserted
+            }
+            Some(existing) => {
+                let current = entry_of(existing, plan.app_name, &refs);
+                if current == *plan.entry {
+                    existing.clone()
+                } else {
+                    job_revision::ensure_baseline(&txn, existing).await?;
+                    let glue_changed = plan.entry.glue_changed(existing);
+                    let mut job = existing.clone();
+                    plan.entry.apply(&mut job);
+                    job.update_time = Some(now);
+                    if glue_changed {
+                        job.glue_updatetime = Some(now);
+                    }
+                    let active: job_info::ActiveModel = job.into();
+                    let updated = active.reset_all().update(&txn).await?;
+                    if glue_changed {
+                        record_glue(&txn, &state, &updated, now).await?;
+                    }
+                    written.push((RevisionAction::Update, updated.clone()));
+                    updated
+                }
+            }
+        };
+        ids_by_ref.insert((plan.app_name.to_string(), plan.entry.key.clone()), job.id);
+    }
+
+    let external = external_ref_ids(&txn, &config, &ids_by_ref).await?;
+    ids_by_ref.extend(external);
+    for plan in &planned {
+        let id = ids_by_ref[&(plan.app_name.to_string(), plan.entry.key.clone())];
+        let child_ids: Vec<String> = plan
+            .entry
+            .child_jobs
+            .iter()
+            .filter_map(|raw| JobRef::parse(raw))
+            .filter_map(|item| {
+                ids_by_ref.get(&(
+                    item.app_name_or(plan.app_name).to_string(),
+                    item.key.clone(),
+                ))
+            })
+            .map(|id| id.to_string())
+            .collect();
+        let child_jobid = (!child_ids.is_empty()).then(|| child_ids.join(","));
+        let current = match written.iter().find(|(_, job)| job.id == id) {
+            Some((_, job)) => job.child_jobid.clone(),
+            None => plan
+                .existing
+                .as_ref()
+                .and_then(|job| job.child_jobid.clone()),
+        };
+        if current == child_jobid {
+            continue;
+        }
+        let updated = job_info::ActiveModel {
+            id: Set(id),
+            child_jobid: Set(child_jobid),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+        match written.iter_mut().find(|(_, job)| job.id == id) {
+            Some((_, job)) => *job = updated,
+            None => {
+                // 只有子任务变化的任务同样需要记录版本
+                if let Some(existing) = &plan.existing {
+                    job_revision::ensure_baseline(&txn, existing).await?;
+                }
+                written.push((RevisionAction::Update, updated));
+            }
+        }
+    }
+
+    for (_, job) in &pruned {
+        job_trash::delete(
+            &txn,
+            &state.settings().job_trash,
+            job,
+            &user.username,
+            false,
+        )
+        .await?;
+    }
+    for (action, job) in &written {
+        job_revision::record(&txn, job, *action, &user.username).await?;
+    }
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::without_id(AuditAction::Import, AuditTarget::JobInfo)
+            .before(
+                &pruned
+                    .iter()
+                    .map(|(_, job)| job)
+                    .collect::<Vec<&job_info::Model>>(),
+            )
+            .after(&result),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(result))
+}
+
+fn blank_job(job_group: i32, now: NaiveDateTime) -> job_info::Model {
+    job_info::Model {
+        id: 0,
+        job_group,
+        job_desc: String::new(),
+        job_key: None,
+        add_time: Some(now),
+        update_time: Some(now),
+        author: None,
+        alarm_email: None,
+        schedule_type: String::new(),
+        schedule_conf: None,
+        misfire_strategy: String::new(),
+        executor_route_strategy: None,
+        executor_handler: None,
+        executor_param: None,
+        executor_block_strategy: None,
+        executor_timeout: 0,
+        executor_fail_retry_count: 0,
+        glue_type: String::new(),
+        glue_source: None,
+        glue_remark: None,
+        glue_updatetime: Some(now),
+        child_jobid: None,
+        cloned_from: None,
+        trigger_status: 0,
+        trigger_last_time: 0,
+        trigger_next_time: 0,
+    }
+}
+
+/// 与在线编辑 GLUE 一致，写入历史版本并按上限清理。
+async fn record_glue<C: ConnectionTrait>(
+    conn: &C,
+    state: &AppState,
+    job: &job_info::Model,
+    now: NaiveDateTime,
+) -> AppResult<()> {
+    job_logglue::Entity::insert(job_logglue::ActiveModel {
+        job_id: Set(job.id),
+        glue_type: Set(Some(job.glue_type.clone())),
+        glue_source: Set(job.glue_source.clone()),
+        glue_remark: Set(job.glue_remark.clone().unwrap_or_else(|| "配置导入".into())),
+        add_time: Set(Some(now)),
+        update_time: Set(Some(now)),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?;
+    glue::prune_versions(conn, job.id, state.settings().glue.max_versions).await?;
+    Ok(())
+}
+
+/// 使用 `compute_next_for_params` 校验每个任务的调度配置。
+fn validate_schedules(config: &JobConfig) -> Vec<String> {
+    let now = Local::now().naive_local();
+    let mut errors = Vec::new();
+    for group in &config.groups {
+        for entry in &group.jobs {
+            let mut job = blank_job(0, now);
+            entry.apply(&mut job);
+            if let Err(err) = compute_next_for_params(&job, Utc::now()) {
+                let message = match err {
+                    AppError::BadRequest(message) => message,
+                    other => other.to_string(),
+                };
+                errors.push(format!(
+                    "任务 `{}/{}`: {message}",
+                    group.app_name, entry.key
+                ));
+            }
+        }
+    }
+    errors
+}
+
+/// 与保存 GLUE 时相同，`glue.syntax_check` 开启时检查新增或有变化的 GLUE 源码，预览与导入都会执行。
+async fn validate_glue_syntax(
+    state: &AppState,
+    config: &JobConfig,
+    group_ids: &HashMap<String, i32>,
+    existing_by_key: &HashMap<(i32, String), job_info::Model>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    for group in &config.groups {
+        for entry in &group.jobs {
+            let Some(glue_type) = GlueType::parse(&entry.glue_type) else {
+                continue;
+            };
+            let unchanged = group_ids
+                .get(&group.app_name)
+                .and_then(|id| existing_by_key.get(&(*id, entry.key.clone())))
+                .is_some_and(|job| !entry.glue_changed(job));
+            if unchanged {
+                continue;
+            }
+            let source = entry.glue_source.as_deref().unwrap_or_default();
+            let result = syntax::check(glue_type, source, &state.settings().glue).await;
+            if !result.passed() {
+                let messages: Vec<String> = result.errors.iter().map(ToString::to_string).collect();
+                errors.push(format!(
+                    "任务 `{}/{}` GLUE 语法检查未通过: {}",
+                    group.app_name,
+                    entry.key,
+                    messages.join("；")
+                ));
+            }
+        }
+    }
+    errors
+}
+
+/// 子任务必须在文件中或已存在于数据库。启用 `prune` 时，文件中执行器下的任务只能引用文件中列出的任务。
+async fn validate_child_refs(
+    state: &AppState,
+    config: &JobConfig,
+    group_ids: &HashMap<String, i32>,
+    prune: bool,
+) -> AppResult<Vec<String>> {
+    let in_file: HashSet<(String, String)> = config
+        .groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .jobs
+                .iter()
+                .map(|job| (group.app_name.clone(), job.key.clone()))
+        })
+        .collect();
+    let file_apps: HashSet<&str> = config.groups.iter().map(|g| g.app_name.as_str()).collect();
+
+    let mut lookups: HashSet<(String, String)> = HashSet::new();
+    let mut pending = Vec::new();
+    for group in &config.groups {
+        for entry in &group.jobs {
+            for item in entry.child_jobs.iter().filter_map(|raw| JobRef::parse(raw)) {
+                let target = (
+                    item.app_name_or(&group.app_name).to_string(),
+                    item.key.clone(),
+                );
+                if in_file.contains(&target) {
+                    continue;
+                }
+                if !(prune && file_apps.contains(target.0.as_str())) {
+                    lookups.insert(target.clone());
+                }
+                pending.push((format!("{}/{}", group.app_name, entry.key), target));
+            }
+        }
+    }
+    if pending.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let found = find_refs(state.db(), &lookups, group_ids).await?;
+    Ok(pending
+        .into_iter()
+        .filter(|(_, target)| !found.contains_key(target))
+        .map(|(path, (app_name, key))| format!("任务 `{path}` 的子任务 `{app_name}/{key}` 不存在"))
+        .collect())
+}
+
+/// 文件之外被引用的任务 ID，键为 `(appName, key)`。
+async fn external_ref_ids<C: ConnectionTrait>(
+    conn: &C,
+    config: &JobConfig,
+    ids_by_ref: &HashMap<(String, String), i32>,
+) -> AppResult<HashMap<(String, String), i32>> {
+    let lookups: HashSet<(String, String)> = config
+        .groups
+        .iter()
+        .flat_map(|group| {
+            group.jobs.iter().flat_map(|entry| {
+                entry
+                    .child_jobs
+                    .iter()
+                    .filter_map(|raw| JobRef::parse(raw))
+                    .map(|item| {
+                        (
+                            item.app_name_or(&group.app_name).to_string(),
+                            item.key.clone(),
+                        )
+                    })
+            })
+        })
+        .filter(|target| !ids_by_ref.contains_key(target))
+        .collect();
+    find_refs(conn, &lookups, &HashMap::new()).await
+}
+
+async fn find_refs<C: ConnectionTrait>(
+    conn: &C,
+    lookups: &HashSet<(String, String)>,
+    known_groups: &HashMap<String, i32>,
+) -> AppResult<HashMap<(String, String), i32>> {
+    if lookups.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut group_ids = known_groups.clone();
+    let missing: HashSet<&String> = lookups
+        .iter()
+        .map(|(app_name, _)| app_name)
+        .filter(|app_name| !group_ids.contains_key(*app_name))
+        .collect();
+    if !missing.is_empty() {
+        let groups = job_group::Entity::find()
+            .filter(job_group::Column::AppName.is_in(missing.into_iter().cloned()))
+            .all(conn)
+            .await?;
+        group_ids.extend(groups.into_iter().map(|group| (group.app_name, group.id)));
+    }
+    let app_names: HashMap<i32, &String> = group_ids.iter().map(|(k, v)| (*v, k)).collect();
+
+    let groups: HashSet<i32> = lookups
+        .iter()
+        .filter_map(|(app_name, _)| group_ids.get(app_name).copied())
+        .collect();
+    let keys: HashSet<&String> = lookups.iter().map(|(_, key)| key).collect();
+    if groups.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let jobs = job_info::Entity::find()
+        .filter(job_info::Column::JobGroup.is_in(groups))
+        .filter(job_info::Column::JobKey.is_in(keys.into_iter().cloned()))
+        .all(conn)
+        .await?;
+    Ok(jobs
+        .into_iter()
+        .filter_map(|job| {
+            let target = (
+                (*app_names.get(&job.job_group)?).clone(),
+                job_config::job_key_of(&job),
+            );
+            lookups.contains(&target).then_some((target, job.id))
+        })
+        .collect())
+}
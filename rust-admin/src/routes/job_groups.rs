@@ -8,9 +8,11 @@ use sea_orm::{query::*, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter,
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::auth::AuthUser;
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
 use crate::entities::{job_group, job_info, job_registry};
 use crate::error::{AppError, AppResult};
+use crate::session::ClientInfo;
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
@@ -71,11 +73,15 @@ struct SaveJobGroupRequest {
 
 async fn page_list(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Query(params): Query<PageParams>,
 ) -> AppResult<Json<PageResult<JobGroupDto>>> {
     let mut query = job_group::Entity::find();
 
+    if let Some(groups) = user.group_scope() {
+        query = query.filter(job_group::Column::Id.is_in(groups));
+    }
+
     if let Some(appname) = params.appname.filter(|s| !s.trim().is_empty()) {
         query = query.filter(job_group::Column::AppName.contains(appname.trim()));
     }
@@ -106,9 +112,10 @@ async fn page_list(
 
 async fn load(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Path(id): Path<i32>,
 ) -> AppResult<Json<JobGroupDto>> {
+    user.require(Permission::View, Some(id))?;
     let model = job_group::Entity::find_by_id(id)
         .one(state.db())
         .await?
@@ -119,9 +126,10 @@ async fn load(
 async fn create(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Json(payload): Json<SaveJobGroupRequest>,
 ) -> AppResult<Json<JobGroupDto>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
@@ -145,9 +153,18 @@ async fn create(
         ..Default::default()
     };
 
+    let txn = state.db().begin().await?;
     let inserted = job_group::Entity::insert(active)
-        .exec_with_returning(state.db())
+        .exec_with_returning(&txn)
         .await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Create, AuditTarget::JobGroup, inserted.id).after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(Json(JobGroupDto::from(inserted)))
 }
@@ -155,10 +172,11 @@ async fn create(
 async fn update(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
     Json(payload): Json<SaveJobGroupRequest>,
 ) -> AppResult<Json<JobGroupDto>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
@@ -177,14 +195,26 @@ async fn update(
 
     let address_list = validate_address_list(&state, &payload).await?;
 
+    let before = model.clone();
     model.app_name = payload.appname.trim().to_string();
     model.title = payload.title.trim().to_string();
     model.address_type = payload.address_type;
     model.address_list = address_list;
     model.update_time = Some(Local::now().naive_local());
 
+    let txn = state.db().begin().await?;
     let active: job_group::ActiveModel = model.into();
-    let updated = active.update(state.db()).await?;
+    let updated = active.reset_all().update(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobGroup, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(Json(JobGroupDto::from(updated)))
 }
@@ -192,9 +222,10 @@ async fn update(
 async fn remove(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
 ) -> AppResult<Json<serde_json::Value>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
 
     let job_count = job_info::Entity::find()
         .filter(job_info::Column::JobGroup.eq(id))
@@ -211,10 +242,20 @@ async fn remove(
         return Err(AppError::BadRequest("至少保留一个执行器分组".into()));
     }
 
-    let result = job_group::Entity::delete_by_id(id).exec(state.db()).await?;
-    if result.rows_affected == 0 {
-        return Err(AppError::NotFound("执行器不存在".into()));
-    }
+    let model = job_group::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("执行器不存在".into()))?;
+    let txn = state.db().begin().await?;
+    job_group::Entity::delete_by_id(id).exec(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Delete, AuditTarget::JobGroup, id).before(&model),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(Json(serde_json::json!({ "message": "已删除执行器" })))
 }
@@ -263,3 +304,40 @@ async fn validate_address_list(
         Ok(Some(joined))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn update_persists_group() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+
+        let Json(dto) = update(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(1),
+            Json(SaveJobGroupRequest {
+                appname: "billing-executor".into(),
+                title: "账单执行器".into(),
+                address_type: 1,
+                address_list: Some("http://10.0.0.2:9999".into()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stored = job_group::Entity::find_by_id(1)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.app_name, "billing-executor");
+        assert_eq!(stored.title, "账单执行器");
+        assert_eq!(dto.appname, stored.app_name);
+        assert_eq!(stored.address_list.as_deref(), Some("http://10.0.0.2:9999"));
+    }
+}
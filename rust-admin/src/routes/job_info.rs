@@ -1,31 +1,32 @@
-use std::collections::HashSet;
 use std::str::FromStr;
 
 use axum::{
     extract::{Path, Query, State},
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use chrono::{Duration, Local, LocalResult, TimeZone, Utc};
+use chrono::{Duration, Local, TimeZone, Utc};
 use cron::Schedule;
-use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
-    Method,
+use sea_orm::{
+    query::*, ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, EntityTrait, QueryFilter,
+    QueryOrder, Set,
 };
-use sea_orm::{query::*, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use validator::Validate;
 
-use crate::auth::AuthUser;
-use crate::entities::{job_group, job_info, job_log, job_registry};
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
+use crate::entities::{job_group, job_info, job_info_revision, job_info_trash, job_logglue};
 use crate::error::{AppError, AppResult};
-use crate::request_preview::{format_executor_request_curl, to_pretty_json};
+use crate::job_config;
+use crate::job_revision::{self, RevisionAction};
+use crate::job_trash::{self, DeleteOutcome};
+use crate::session::ClientInfo;
 use crate::state::AppState;
+use crate::trigger::{self, Dispatch, Dispatched};
 use tracing::{debug, error, info, warn};
 
-const REGISTRY_DEAD_TIMEOUT_SECONDS: i64 = 90;
-
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(page_list).post(create))
@@ -33,6 +34,15 @@ pub fn router() -> Router<AppState> {
         .route("/:id/start", post(start_job))
         .route("/:id/stop", post(stop_job))
         .route("/:id/trigger", post(trigger_job))
+        .route("/:id/clone", post(clone_job))
+        .route("/bulk", post(bulk))
+        .route("/trash", get(list_trash))
+        .route("/trash/:id", delete(purge_trash))
+        .route("/trash/:id/restore", post(restore_trash))
+        .route("/:id/revisions", get(list_revisions))
+        .route("/:id/revisions/diff", get(diff_revisions))
+        .route("/:id/revisions/:revision", get(load_revision))
+        .route("/:id/revisions/:revision/restore", post(restore_revision))
         .route("/next-trigger-time", get(next_trigger_time))
 }
 
@@ -60,6 +70,7 @@ struct JobInfoDto {
     id: i32,
     job_group: i32,
     job_desc: String,
+    job_key: Option<String>,
     author: Option<String>,
     alarm_email: Option<String>,
     schedule_type: String,
@@ -76,6 +87,7 @@ struct JobInfoDto {
     glue_remark: Option<String>,
     glue_updatetime: Option<chrono::NaiveDateTime>,
     child_jobid: Option<String>,
+    cloned_from: Option<i32>,
     trigger_status: i8,
     trigger_last_time: i64,
     trigger_next_time: i64,
@@ -89,6 +101,7 @@ impl From<job_info::Model> for JobInfoDto {
             id: value.id,
             job_group: value.job_group,
             job_desc: value.job_desc,
+            job_key: value.job_key,
             author: value.author,
             alarm_email: value.alarm_email,
             schedule_type: value.schedule_type,
@@ -105,6 +118,7 @@ impl From<job_info::Model> for JobInfoDto {
             glue_remark: value.glue_remark,
             glue_updatetime: value.glue_updatetime,
             child_jobid: value.child_jobid,
+            cloned_from: value.cloned_from,
             trigger_status: value.trigger_status,
             trigger_last_time: value.trigger_last_time,
             trigger_next_time: value.trigger_next_time,
@@ -120,6 +134,8 @@ struct SaveJobInfoRequest {
     job_group: i32,
     #[validate(length(min = 1, message = "任务描述不能为空"))]
     job_desc: String,
+    /// 执行器内唯一的任务标识，新增时不传则使用 `job-{id}`，修改时不传则保持不变
+    job_key: Option<String>,
     author: Option<String>,
     alarm_email: Option<String>,
     schedule_type: String,
@@ -139,27 +155,18 @@ struct SaveJobInfoRequest {
 
 async fn page_list(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Query(params): Query<PageParams>,
 ) -> AppResult<Json<PageResult<JobInfoDto>>> {
-    let mut query =
-        job_info::Entity::find().filter(job_info::Column::JobGroup.eq(params.job_group));
-
-    if let Some(trigger_status) = params.trigger_status {
-        if trigger_status >= 0 {
-            query = query.filter(job_info::Column::TriggerStatus.eq(trigger_status));
-        }
-    }
-
-    if let Some(job_desc) = params.job_desc.filter(|s| !s.trim().is_empty()) {
-        query = query.filter(job_info::Column::JobDesc.contains(job_desc.trim()));
-    }
-    if let Some(handler) = params.executor_handler.filter(|s| !s.trim().is_empty()) {
-        query = query.filter(job_info::Column::ExecutorHandler.contains(handler.trim()));
-    }
-    if let Some(author) = params.author.filter(|s| !s.trim().is_empty()) {
-        query = query.filter(job_info::Column::Author.contains(author.trim()));
+    user.require(Permission::View, Some(params.job_group))?;
+    let query = JobFilter {
+        job_group: params.job_group,
+        trigger_status: params.trigger_status,
+        job_desc: params.job_desc,
+        executor_handler: params.executor_handler,
+        author: params.author,
     }
+    .query();
 
     let start = params.start.unwrap_or(0);
     let length = params.length.unwrap_or(10);
@@ -182,22 +189,117 @@ async fn page_list(
     }))
 }
 
+/// 未指定任务标识时使用 `job-{id}`，需要在插入后才能确定。
+async fn assign_default_job_key<C: ConnectionTrait>(
+    conn: &C,
+    job: job_info::Model,
+) -> AppResult<job_info::Model> {
+    if job.job_key.is_some() {
+        return Ok(job);
+    }
+    let key = job_config::default_job_key(job.id);
+    let updated = job_info::ActiveModel {
+        id: Set(job.id),
+        job_key: Set(Some(key)),
+        ..Default::default()
+    }
+    .update(conn)
+    .await?;
+    Ok(updated)
+}
+
+fn normalize_job_key(raw: Option<&str>) -> AppResult<Option<String>> {
+    match raw.map(str::trim).filter(|key| !key.is_empty()) {
+        Some(key) => {
+            job_config::validate_job_key(key).map_err(AppError::BadRequest)?;
+            Ok(Some(key.to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// 任务标识在执行器内唯一。
+pub(crate) async fn ensure_job_key_available<C: ConnectionTrait>(
+    conn: &C,
+    job_group: i32,
+    job_key: &str,
+    exclude: Option<i32>,
+) -> AppResult<()> {
+    let mut query = job_info::Entity::find()
+        .filter(job_info::Column::JobGroup.eq(job_group))
+        .filter(job_info::Column::JobKey.eq(job_key));
+    if let Some(id) = exclude {
+        query = query.filter(job_info::Column::Id.ne(id));
+    }
+    if query.one(conn).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "执行器 {job_group} 中已存在任务标识 `{job_key}`"
+        )));
+    }
+    Ok(())
+}
+
+/// 任务列表与批量操作共用的筛选条件。
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobFilter {
+    job_group: i32,
+    trigger_status: Option<i8>,
+    job_desc: Option<String>,
+    executor_handler: Option<String>,
+    author: Option<String>,
+}
+
+impl JobFilter {
+    fn query(&self) -> Select<job_info::Entity> {
+        let mut query =
+            job_info::Entity::find().filter(job_info::Column::JobGroup.eq(self.job_group));
+
+        if let Some(trigger_status) = self.trigger_status {
+            if trigger_status >= 0 {
+                query = query.filter(job_info::Column::TriggerStatus.eq(trigger_status));
+            }
+        }
+
+        if let Some(job_desc) = self.job_desc.as_deref().filter(|s| !s.trim().is_empty()) {
+            query = query.filter(job_info::Column::JobDesc.contains(job_desc.trim()));
+        }
+        if let Some(handler) = self
+            .executor_handler
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+        {
+            query = query.filter(job_info::Column::ExecutorHandler.contains(handler.trim()));
+        }
+        if let Some(author) = self.author.as_deref().filter(|s| !s.trim().is_empty()) {
+            query = query.filter(job_info::Column::Author.contains(author.trim()));
+        }
+        query
+    }
+}
+
 async fn create(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Json(payload): Json<SaveJobInfoRequest>,
 ) -> AppResult<Json<JobInfoDto>> {
-    user.require_admin()?;
+    user.require(Permission::Develop, Some(payload.job_group))?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
 
     ensure_group_exists(&state, payload.job_group).await?;
+    let job_key = normalize_job_key(payload.job_key.as_deref())?;
+    if let Some(key) = job_key.as_deref() {
+        ensure_job_key_available(state.db(), payload.job_group, key, None).await?;
+    }
 
     let now = Local::now().naive_local();
     let active = job_info::ActiveModel {
         job_group: Set(payload.job_group),
         job_desc: Set(payload.job_desc.clone()),
+        job_key: Set(job_key.clone()),
         add_time: Set(Some(now)),
         update_time: Set(Some(now)),
         author: Set(payload.author.clone()),
@@ -222,9 +324,20 @@ async fn create(
         ..Default::default()
     };
 
+    let txn = state.db().begin().await?;
     let inserted = job_info::Entity::insert(active)
-        .exec_with_returning(state.db())
+        .exec_with_returning(&txn)
         .await?;
+    let inserted = assign_default_job_key(&txn, inserted).await?;
+    job_revision::record(&txn, &inserted, RevisionAction::Create, &user.username).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Create, AuditTarget::JobInfo, inserted.id).after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(Json(JobInfoDto::from(inserted)))
 }
@@ -232,21 +345,32 @@ async fn create(
 async fn update(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
     Json(payload): Json<SaveJobInfoRequest>,
 ) -> AppResult<Json<JobInfoDto>> {
-    user.require_admin()?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
 
-    ensure_group_exists(&state, payload.job_group).await?;
-
     let mut model = job_info::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::Develop, Some(model.job_group))?;
+    user.require(Permission::Develop, Some(payload.job_group))?;
+
+    ensure_group_exists(&state, payload.job_group).await?;
+    if let Some(key) = normalize_job_key(payload.job_key.as_deref())? {
+        model.job_key = Some(key);
+    }
+    if let Some(key) = model.job_key.as_deref() {
+        ensure_job_key_available(state.db(), payload.job_group, key, Some(id)).await?;
+    }
+    let txn = state.db().begin().await?;
+    job_revision::ensure_baseline(&txn, &model).await?;
 
+    let before = model.clone();
     model.job_group = payload.job_group;
     model.job_desc = payload.job_desc.clone();
     model.author = payload.author.clone();
@@ -267,64 +391,380 @@ async fn update(
     model.update_time = Some(Local::now().naive_local());
 
     let active: job_info::ActiveModel = model.into();
-    let updated = active.update(state.db()).await?;
+    let updated = active.reset_all().update(&txn).await?;
+    job_revision::record(&txn, &updated, RevisionAction::Update, &user.username).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobInfo, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok(Json(JobInfoDto::from(updated)))
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CloneJobRequest {
+    /// 目标执行器，默认与原任务相同
+    job_group: Option<i32>,
+    /// 默认在原描述后追加“（副本）”
+    job_desc: Option<String>,
+    /// 默认使用 `job-{id}`
+    job_key: Option<String>,
+    /// 传入空字符串时清空
+    executor_param: Option<String>,
+    author: Option<String>,
+}
+
+/// 复制任务定义与当前 GLUE，新任务处于停止状态并记录复制来源。
+async fn clone_job(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+    Json(payload): Json<CloneJobRequest>,
+) -> AppResult<Json<JobInfoDto>> {
+    let source = find_job(&state, &user, id).await?;
+    let job_group = payload.job_group.unwrap_or(source.job_group);
+    user.require(Permission::Develop, Some(job_group))?;
+    ensure_group_exists(&state, job_group).await?;
+    let job_key = normalize_job_key(payload.job_key.as_deref())?;
+    if let Some(key) = job_key.as_deref() {
+        ensure_job_key_available(state.db(), job_group, key, None).await?;
+    }
+
+    let now = Local::now().naive_local();
+    let mut job = source.clone();
+    job.job_group = job_group;
+    job.job_key = job_key;
+    job.job_desc = normalize_optional_payload_string(payload.job_desc)
+        .unwrap_or_else(|| format!("{}（副本）", source.job_desc));
+    if let Some(param) = payload.executor_param {
+        job.executor_param = normalize_optional_payload_string(Some(param));
+    }
+    if let Some(author) = payload.author {
+        job.author = normalize_optional_payload_string(Some(author));
+    }
+    job.cloned_from = Some(source.id);
+    job.add_time = Some(now);
+    job.update_time = Some(now);
+    job.glue_updatetime = Some(now);
+    job.trigger_status = 0;
+    job.trigger_last_time = 0;
+    job.trigger_next_time = 0;
+
+    let txn = state.db().begin().await?;
+    let mut active: job_info::ActiveModel = job.into();
+    active.id = NotSet;
+    let inserted = job_info::Entity::insert(active)
+        .exec_with_returning(&txn)
+        .await?;
+    let inserted = assign_default_job_key(&txn, inserted).await?;
+    if inserted.glue_type != "BEAN" {
+        job_logglue::Entity::insert(job_logglue::ActiveModel {
+            job_id: Set(inserted.id),
+            glue_type: Set(Some(inserted.glue_type.clone())),
+            glue_source: Set(inserted.glue_source.clone()),
+            glue_remark: Set(format!("复制自任务 #{}", source.id)),
+            add_time: Set(Some(now)),
+            update_time: Set(Some(now)),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+    }
+    job_revision::record(&txn, &inserted, RevisionAction::Create, &user.username).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Create, AuditTarget::JobInfo, inserted.id).after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(JobInfoDto::from(inserted)))
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveParams {
+    /// 跳过回收站，立即删除任务及其调度日志、GLUE 历史
+    #[serde(default)]
+    purge: bool,
+}
+
 async fn remove(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
+    Query(params): Query<RemoveParams>,
 ) -> AppResult<Json<serde_json::Value>> {
-    user.require_admin()?;
+    let model = job_info::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::Develop, Some(model.job_group))?;
+
+    let txn = state.db().begin().await?;
+    let outcome = job_trash::delete(
+        &txn,
+        &state.settings().job_trash,
+        &model,
+        &user.username,
+        params.purge,
+    )
+    .await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Delete, AuditTarget::JobInfo, id)
+            .before(&model)
+            .after(&outcome),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(
+        json!({ "message": outcome.message(), "outcome": outcome }),
+    ))
+}
 
-    let deleted = job_info::Entity::delete_by_id(id).exec(state.db()).await?;
-    if deleted.rows_affected == 0 {
-        return Err(AppError::NotFound("任务不存在".into()));
+async fn delete_job(
+    state: &AppState,
+    user: &AuthUser,
+    model: &job_info::Model,
+    purge: bool,
+) -> AppResult<DeleteOutcome> {
+    let txn = state.db().begin().await?;
+    let outcome = job_trash::delete(
+        &txn,
+        &state.settings().job_trash,
+        model,
+        &user.username,
+        purge,
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(outcome)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrashParams {
+    job_group: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrashDto {
+    job_id: i32,
+    job_group: i32,
+    job_desc: String,
+    deleted_by: String,
+    deleted_at: chrono::NaiveDateTime,
+    purge_after: chrono::NaiveDateTime,
+}
+
+impl From<job_info_trash::Model> for TrashDto {
+    fn from(value: job_info_trash::Model) -> Self {
+        Self {
+            job_id: value.job_id,
+            job_group: value.job_group,
+            job_desc: value.job_desc,
+            deleted_by: value.deleted_by,
+            deleted_at: value.deleted_at,
+            purge_after: value.purge_after,
+        }
+    }
+}
+
+/// 回收站中的任务，按删除时间倒序。
+async fn list_trash(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(params): Query<TrashParams>,
+) -> AppResult<Json<Vec<TrashDto>>> {
+    let mut query = job_info_trash::Entity::find().order_by_desc(job_info_trash::Column::DeletedAt);
+    if let Some(group) = params.job_group {
+        user.require(Permission::View, Some(group))?;
+        query = query.filter(job_info_trash::Column::JobGroup.eq(group));
+    }
+    if let Some(scope) = user.group_scope() {
+        query = query.filter(job_info_trash::Column::JobGroup.is_in(scope));
     }
+    let entries = query
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(TrashDto::from)
+        .collect();
+    Ok(Json(entries))
+}
 
-    Ok(Json(json!({ "message": "任务已删除" })))
+async fn find_trash(
+    state: &AppState,
+    user: &AuthUser,
+    job_id: i32,
+) -> AppResult<job_info_trash::Model> {
+    let entry = job_info_trash::Entity::find_by_id(job_id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("回收站中没有该任务".into()))?;
+    user.require(Permission::Develop, Some(entry.job_group))?;
+    Ok(entry)
+}
+
+/// 恢复回收站中的任务，沿用原 ID，恢复后处于停止状态。
+async fn restore_trash(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(job_id): Path<i32>,
+) -> AppResult<Json<JobInfoDto>> {
+    let entry = find_trash(&state, &user, job_id).await?;
+    let job = job_trash::restored_job(&entry)?;
+    if job_group::Entity::find_by_id(job.job_group)
+        .one(state.db())
+        .await?
+        .is_none()
+    {
+        return Err(AppError::Conflict(format!(
+            "任务所在的执行器 {} 已删除，无法恢复",
+            job.job_group
+        )));
+    }
+    if let Some(key) = job.job_key.as_deref() {
+        ensure_job_key_available(state.db(), job.job_group, key, Some(job.id)).await?;
+    }
+
+    let txn = state.db().begin().await?;
+    let active: job_info::ActiveModel = job.into();
+    let restored = job_info::Entity::insert(active.reset_all())
+        .exec_with_returning(&txn)
+        .await?;
+    job_info_trash::Entity::delete_by_id(job_id)
+        .exec(&txn)
+        .await?;
+    job_revision::record(&txn, &restored, RevisionAction::Restore, &user.username).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Restore, AuditTarget::JobInfo, job_id).after(&restored),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(JobInfoDto::from(restored)))
+}
+
+/// 立即彻底删除回收站中的任务及其调度日志、GLUE 历史。
+async fn purge_trash(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(job_id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    let entry = find_trash(&state, &user, job_id).await?;
+    let txn = state.db().begin().await?;
+    let counts = job_trash::purge(&txn, &entry).await?;
+    let outcome = DeleteOutcome::Purged(counts);
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Delete, AuditTarget::JobInfo, job_id)
+            .before(&TrashDto::from(entry))
+            .after(&outcome),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(
+        json!({ "message": outcome.message(), "outcome": outcome }),
+    ))
 }
 
 async fn start_job(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
 ) -> AppResult<Json<JobInfoDto>> {
-    user.require_admin()?;
-    let mut model = job_info::Entity::find_by_id(id)
+    let model = job_info::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
-
-    let next = compute_next_trigger(&model)?;
-    model.trigger_status = 1;
-    model.trigger_last_time = Utc::now().timestamp_millis();
-    model.trigger_next_time = next.unwrap_or(0);
-
-    let active: job_info::ActiveModel = model.into();
-    let updated = active.update(state.db()).await?;
+    user.require(Permission::Operate, Some(model.job_group))?;
+
+    let before = model.clone();
+    let txn = state.db().begin().await?;
+    let updated = set_running(&txn, model, true).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Start, AuditTarget::JobInfo, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
     Ok(Json(JobInfoDto::from(updated)))
 }
 
 async fn stop_job(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
 ) -> AppResult<Json<JobInfoDto>> {
-    user.require_admin()?;
-    let mut model = job_info::Entity::find_by_id(id)
+    let model = job_info::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::Operate, Some(model.job_group))?;
+
+    let before = model.clone();
+    let txn = state.db().begin().await?;
+    let updated = set_running(&txn, model, false).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Stop, AuditTarget::JobInfo, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(JobInfoDto::from(updated)))
+}
 
-    model.trigger_status = 0;
-    model.trigger_next_time = 0;
-
+/// 启动时计算下次触发时间，停止时清空。
+async fn set_running<C: ConnectionTrait>(
+    conn: &C,
+    mut model: job_info::Model,
+    running: bool,
+) -> AppResult<job_info::Model> {
+    if running {
+        let next = compute_next_trigger(&model)?;
+        model.trigger_status = 1;
+        model.trigger_last_time = Utc::now().timestamp_millis();
+        model.trigger_next_time = next.unwrap_or(0);
+    } else {
+        model.trigger_status = 0;
+        model.trigger_next_time = 0;
+    }
     let active: job_info::ActiveModel = model.into();
-    let updated = active.update(state.db()).await?;
-    Ok(Json(JobInfoDto::from(updated)))
+    Ok(active.reset_all().update(conn).await?)
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -337,11 +777,10 @@ struct TriggerRequest {
 async fn trigger_job(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
     Json(payload): Json<TriggerRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    user.require_admin()?;
-
     let request_body = match serde_json::to_string(&payload) {
         Ok(body) => body,
         Err(err) => {
@@ -355,43 +794,50 @@ async fn trigger_job(
         "开始处理手动触发请求"
     );
 
-    let mut job = job_info::Entity::find_by_id(id)
+    let job = job_info::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::Operate, Some(job.job_group))?;
 
     info!(job_id = job.id, job_group = job.job_group, "已加载任务信息");
 
-    let handler = job.executor_handler.clone().unwrap_or_default();
-
-    let group = job_group::Entity::find_by_id(job.job_group)
-        .one(state.db())
-        .await?
-        .ok_or_else(|| AppError::NotFound("执行器分组不存在".into()))?;
-
-    debug!(
-        job_id = job.id,
-        job_group = group.id,
-        "已加载执行器分组信息"
-    );
-
-    let addresses =
-        resolve_executor_addresses(&state, &group, payload.address_list.as_deref()).await?;
-
-    if addresses.is_empty() {
-        return Err(AppError::BadRequest("未找到可用的执行器地址".into()));
-    }
-
-    info!(
-        job_id = job.id,
-        address_count = addresses.len(),
-        "已确定执行器地址列表"
-    );
-    debug!(job_id = job.id, addresses = %addresses.join(","), "执行器地址详情");
+    let dispatched = trigger_one(
+        &state,
+        &user,
+        job,
+        payload.executor_param.clone(),
+        payload.address_list.as_deref(),
+    )
+    .await?;
+    audit::record_after_dispatch(
+        &state,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Trigger, AuditTarget::JobInfo, id).after(&json!({
+            "logId": dispatched.log_id,
+            "executorParam": payload.executor_param,
+            "addressList": payload.address_list,
+            "executorAddress": dispatched.address,
+            "triggerCode": dispatched.code,
+        })),
+    )
+    .await;
+
+    Ok(Json(json!({ "message": trigger_message(&dispatched) })))
+}
 
-    let now = Local::now();
-    let executor_param = normalize_optional_payload_string(payload.executor_param.clone())
-        .or_else(|| job.executor_param.clone());
+/// 手动触发一次任务并更新任务的触发时间；`executor_param` 为空时使用任务配置的参数。
+async fn trigger_one(
+    state: &AppState,
+    user: &AuthUser,
+    mut job: job_info::Model,
+    executor_param: Option<String>,
+    address_list: Option<&str>,
+) -> AppResult<Dispatched> {
+    let handler = job.executor_handler.clone().unwrap_or_default();
+    let executor_param =
+        normalize_optional_payload_string(executor_param).or_else(|| job.executor_param.clone());
 
     debug!(
         job_id = job.id,
@@ -399,462 +845,488 @@ async fn trigger_job(
         "执行参数已准备"
     );
 
-    let log_active = job_log::ActiveModel {
-        job_group: Set(job.job_group),
-        job_id: Set(job.id),
-        executor_address: Set(None),
-        executor_handler: Set(if handler.trim().is_empty() {
-            None
-        } else {
-            Some(handler.clone())
-        }),
-        executor_param: Set(executor_param.clone()),
-        executor_sharding_param: Set(None),
-        executor_fail_retry_count: Set(job.executor_fail_retry_count),
-        trigger_time: Set(Some(now.naive_local())),
-        trigger_code: Set(0),
-        trigger_msg: Set(None),
-        handle_time: Set(None),
-        handle_code: Set(0),
-        handle_msg: Set(None),
-        alarm_status: Set(0),
-        ..Default::default()
-    };
-
-    debug!(job_id = job.id, "准备插入新的任务日志");
-
-    let inserted = job_log::Entity::insert(log_active).exec(state.db()).await?;
-    let log_id = inserted.last_insert_id;
-
-    info!(job_id = job.id, log_id, "已创建任务日志");
-
-    let trigger_param = build_trigger_param(
-        &job,
-        log_id,
-        now.timestamp_millis(),
-        handler,
-        executor_param,
-    );
-    debug!(job_id = job.id, log_id, "已构建执行器触发参数");
-    let mut trigger_lines = vec![format!("手动触发任务，触发人: {}", user.username)];
-    trigger_lines.push(format!("候选执行器地址: {}", addresses.join(", ")));
-
-    info!(job_id = job.id, log_id, "准备触发执行器");
-
-    let mut final_code = 500;
-    let mut final_msg: Option<String> = None;
-    let mut final_address: Option<String> = None;
-
-    for address in &addresses {
-        info!(
-            job_id = job.id,
-            log_id,
-            executor_address = address.as_str(),
-            "尝试触发执行器"
-        );
-        match trigger_executor(
-            state.http_client(),
-            address.as_str(),
-            state.settings().executor.access_token(),
-            &trigger_param,
-        )
-        .await
-        {
-            Ok(result) => {
-                info!(
-                    job_id = job.id,
-                    log_id,
-                    executor_address = address.as_str(),
-                    code = result.code,
-                    "执行器返回结果"
-                );
-                final_code = result.code;
-                final_msg = result.msg.clone();
-                final_address = Some(address.clone());
-
-                let mut line = format!("执行器 `{}` 返回 code = {}", address, result.code);
-                if let Some(msg) = result.msg.as_ref().filter(|m| !m.trim().is_empty()) {
-                    line.push_str(&format!(", msg = {}", msg));
-                }
-                if let Some(content) = result.content.as_ref().filter(|c| !c.trim().is_empty()) {
-                    line.push_str(&format!(", content = {}", content));
-                }
-                trigger_lines.push(line);
-
-                if result.code == 200 {
-                    info!(
-                        job_id = job.id,
-                        log_id,
-                        executor_address = address.as_str(),
-                        "执行器触发成功"
-                    );
-                    break;
-                }
-            }
-            Err(err) => {
-                warn!(
-                    job_id = job.id,
-                    log_id,
-                    executor_address = address.as_str(),
-                    error = %err,
-                    "调用执行器失败"
-                );
-                trigger_lines.push(format!("调用执行器 `{}` 失败: {}", address, err));
-            }
-        }
-    }
-
-    if final_address.is_none() {
-        error!(job_id = job.id, log_id, "所有候选执行器触发失败");
-        trigger_lines.push("所有可用执行器均触发失败".into());
-    }
-
-    let trigger_msg = trigger_lines.join("<br>");
-
-    let update_log = job_log::ActiveModel {
-        id: Set(log_id),
-        executor_address: Set(final_address.clone()),
-        trigger_code: Set(final_code),
-        trigger_msg: Set(Some(trigger_msg)),
-        ..Default::default()
-    };
-    job_log::Entity::update(update_log).exec(state.db()).await?;
-
-    debug!(job_id = job.id, log_id, "已更新任务日志触发结果");
-
-    job.trigger_last_time = now.timestamp_millis();
+    let dispatched = trigger::dispatch(
+        state,
+        Dispatch {
+            job: &job,
+            handler,
+            executor_param,
+            sharding: None,
+            address_list,
+            preferred_address: None,
+            rerun_of: None,
+            trigger_lines: vec![format!("手动触发任务，触发人: {}", user.username)],
+        },
+    )
+    .await?;
+
+    job.trigger_last_time = dispatched.trigger_time.timestamp_millis();
     if let Some(next) = compute_next_trigger(&job)? {
         job.trigger_next_time = next;
     }
     let job_id = job.id;
     let active: job_info::ActiveModel = job.into();
-    active.update(state.db()).await?;
+    active.reset_all().update(state.db()).await?;
 
     debug!(job_id = job_id, "已更新任务触发时间信息");
 
-    info!(job_id = job_id, log_id, code = final_code, "触发流程结束");
+    info!(
+        job_id = job_id,
+        log_id = dispatched.log_id,
+        code = dispatched.code,
+        "触发流程结束"
+    );
+    Ok(dispatched)
+}
 
-    let response_message = if final_code == 200 {
+fn trigger_message(dispatched: &Dispatched) -> String {
+    if dispatched.code == 200 {
         "触发成功".to_string()
-    } else if let Some(msg) = final_msg.filter(|m| !m.trim().is_empty()) {
+    } else if let Some(msg) = dispatched.msg.as_ref().filter(|m| !m.trim().is_empty()) {
         format!("触发失败: {}", msg)
     } else {
         "触发失败".to_string()
-    };
-
-    Ok(Json(json!({ "message": response_message })))
+    }
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// 单次批量操作最多处理的任务数。
+const BULK_LIMIT: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct TriggerParamPayload {
-    job_id: i32,
-    executor_handler: String,
-    executor_params: String,
-    executor_block_strategy: String,
-    executor_timeout: i32,
-    log_id: i64,
-    log_date_time: i64,
-    glue_type: String,
-    glue_source: String,
-    glue_updatetime: i64,
-    broadcast_index: i32,
-    broadcast_total: i32,
+enum BulkAction {
+    Start,
+    Stop,
+    Trigger,
+    Delete,
+    ChangeGroup,
 }
 
-fn build_trigger_param(
-    job: &job_info::Model,
-    log_id: i64,
-    log_time: i64,
-    handler: String,
-    executor_param: Option<String>,
-) -> TriggerParamPayload {
-    let executor_params = executor_param.unwrap_or_default();
-    let block_strategy = job
-        .executor_block_strategy
-        .clone()
-        .unwrap_or_else(|| "SERIAL_EXECUTION".to_string());
-    let glue_source = job.glue_source.clone().unwrap_or_default();
-    let glue_updatetime = job
-        .glue_updatetime
-        .map(timestamp_millis_from_naive_local)
-        .unwrap_or_default();
-
-    TriggerParamPayload {
-        job_id: job.id,
-        executor_handler: handler,
-        executor_params,
-        executor_block_strategy: block_strategy,
-        executor_timeout: job.executor_timeout,
-        log_id,
-        log_date_time: log_time,
-        glue_type: job.glue_type.clone(),
-        glue_source,
-        glue_updatetime,
-        broadcast_index: 0,
-        broadcast_total: 1,
+impl BulkAction {
+    fn audit_action(self) -> AuditAction {
+        match self {
+            BulkAction::Start => AuditAction::Start,
+            BulkAction::Stop => AuditAction::Stop,
+            BulkAction::Trigger => AuditAction::Trigger,
+            BulkAction::Delete => AuditAction::Delete,
+            BulkAction::ChangeGroup => AuditAction::Update,
+        }
     }
-}
 
-fn normalize_optional_payload_string(value: Option<String>) -> Option<String> {
-    value.and_then(|raw| {
-        let trimmed = raw.trim();
-        if trimmed.is_empty() {
-            None
-        } else if trimmed.len() == raw.len() {
-            Some(raw)
-        } else {
-            Some(trimmed.to_string())
+    fn permission(self) -> Permission {
+        match self {
+            BulkAction::Start | BulkAction::Stop | BulkAction::Trigger => Permission::Operate,
+            BulkAction::Delete | BulkAction::ChangeGroup => Permission::Develop,
         }
-    })
+    }
 }
 
-fn timestamp_millis_from_naive_local(dt: chrono::NaiveDateTime) -> i64 {
-    match Local.from_local_datetime(&dt) {
-        LocalResult::Single(value) => value.timestamp_millis(),
-        LocalResult::Ambiguous(first, second) => {
-            first.timestamp_millis().min(second.timestamp_millis())
-        }
-        LocalResult::None => Utc.from_utc_datetime(&dt).timestamp_millis(),
-    }
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkRequest {
+    action: BulkAction,
+    /// 任务 ID 列表，与 `filter` 二选一
+    #[serde(default)]
+    ids: Vec<i32>,
+    /// 与任务列表相同的筛选条件
+    filter: Option<JobFilter>,
+    /// `changeGroup` 的目标执行器
+    target_group: Option<i32>,
+    /// `trigger` 的执行参数，为空时使用各任务配置的参数
+    executor_param: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ExecutorReturn<T> {
-    code: i32,
-    msg: Option<T>,
-    content: Option<T>,
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkItemResult {
+    job_id: i32,
+    success: bool,
+    message: String,
 }
 
-async fn trigger_executor(
-    client: &reqwest::Client,
-    raw_address: &str,
-    access_token: Option<&str>,
-    payload: &TriggerParamPayload,
-) -> anyhow::Result<ExecutorReturn<String>> {
-    let mut address = raw_address.trim().to_string();
-    if !address.ends_with('/') {
-        address.push('/');
-    }
-    address.push_str("run");
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkResult {
+    action: BulkAction,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    results: Vec<BulkItemResult>,
+}
 
-    let request_body = match serde_json::to_string(payload) {
-        Ok(body) => body,
-        Err(err) => {
-            warn!(
-                executor_address = raw_address,
-                error = %err,
-                "序列化执行器触发请求体失败，使用 Debug 输出"
-            );
-            format!("{:?}", payload)
+/// 对一批任务执行同一操作，逐个返回结果；单个任务失败不影响其它任务，整批记录为一条审计。
+async fn bulk(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<BulkRequest>,
+) -> AppResult<Json<BulkResult>> {
+    let target_group = match (payload.action, payload.target_group) {
+        (BulkAction::ChangeGroup, Some(group)) => {
+            user.require(Permission::Develop, Some(group))?;
+            ensure_group_exists(&state, group).await?;
+            Some(group)
         }
-    };
-    let json_value: serde_json::Value = match serde_json::from_str(&request_body) {
-        Ok(value) => value,
-        Err(err) => {
-            warn!(
-                executor_address = raw_address,
-                error = %err,
-                "解析执行器触发请求体失败，继续使用结构体序列化结果"
-            );
-            serde_json::to_value(payload).unwrap_or(serde_json::Value::Null)
+        (BulkAction::ChangeGroup, None) => {
+            return Err(AppError::BadRequest(
+                "changeGroup 需要提供 targetGroup".into(),
+            ));
         }
+        _ => None,
     };
-    let curl_preview = format_executor_request_curl(address.as_str(), access_token, &request_body);
-    let pretty_body = to_pretty_json(payload).unwrap_or_else(|| request_body.clone());
 
-    info!(
-        executor_address = raw_address,
-        url = address.as_str(),
-        request_body = %request_body,
-        pretty_request_body = %pretty_body,
-        curl = %curl_preview,
-        "发送执行器触发请求",
-    );
-
-    let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    if let Some(token) = access_token {
-        let token_value = HeaderValue::from_str(token)
-            .map_err(|err| anyhow::anyhow!("访问令牌包含非法字符，无法写入请求头: {err}"))?;
-        headers.insert(HeaderName::from_static("xxl-job-access-token"), token_value);
-    }
-
-    let request_builder = client
-        .request(Method::POST, address.clone())
-        .headers(headers)
-        .json(&json_value);
-
-    if let Some(preview_builder) = request_builder.try_clone() {
-        if let Ok(preview_request) = preview_builder.build() {
-            info!(
-                executor_address = raw_address,
-                request_url = %preview_request.url(),
-                "执行器请求已构建"
-            );
+    let mut results = Vec::new();
+    let jobs = match (&payload.filter, payload.ids.is_empty()) {
+        (Some(filter), true) => {
+            user.require(Permission::View, Some(filter.job_group))?;
+            let query = filter.query();
+            let count = query.clone().count(state.db()).await?;
+            if count > BULK_LIMIT {
+                return Err(AppError::BadRequest(format!(
+                    "匹配的任务有 {count} 个，超过单次批量操作上限 {BULK_LIMIT} 个"
+                )));
+            }
+            query
+                .order_by_asc(job_info::Column::Id)
+                .all(state.db())
+                .await?
         }
-    }
+        (None, false) => {
+            let mut ids = Vec::with_capacity(payload.ids.len());
+            for id in &payload.ids {
+                if !ids.contains(id) {
+                    ids.push(*id);
+                }
+            }
+            if ids.len() as u64 > BULK_LIMIT {
+                return Err(AppError::BadRequest(format!(
+                    "单次批量操作最多 {BULK_LIMIT} 个任务"
+                )));
+            }
+            let found = job_info::Entity::find()
+                .filter(job_info::Column::Id.is_in(ids.clone()))
+                .all(state.db())
+                .await?;
+            let mut jobs = Vec::with_capacity(found.len());
+            for id in ids {
+                match found.iter().find(|job| job.id == id) {
+                    Some(job) => jobs.push(job.clone()),
+                    None => results.push(BulkItemResult {
+                        job_id: id,
+                        success: false,
+                        message: "任务不存在".into(),
+                    }),
+                }
+            }
+            jobs
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "请提供任务 ID 列表 ids 或筛选条件 filter 之一".into(),
+            ));
+        }
+    };
 
-    let response = request_builder.send().await.map_err(|err| {
-        if err.is_connect() {
-            anyhow::anyhow!("无法连接到执行器，请确认网络和端口是否可达: {err}")
-        } else if err.is_timeout() {
-            anyhow::anyhow!("请求执行器超时，请检查执行器负载或网络状况: {err}")
-        } else {
-            anyhow::anyhow!("调用执行器发生错误: {err}")
+    let mut before = Vec::new();
+    for job in jobs {
+        let job_id = job.id;
+        let snapshot = job.clone();
+        // 无权查看的任务按不存在处理，避免借助批量接口探测其它执行器下的任务
+        if user.require(Permission::View, Some(job.job_group)).is_err() {
+            results.push(BulkItemResult {
+                job_id,
+                success: false,
+                message: "任务不存在".into(),
+            });
+            continue;
         }
-    })?;
-    debug!(executor_address = raw_address, status = %response.status(), "收到执行器响应");
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "执行器返回非成功状态码: {}",
-            response.status()
-        ));
+        let outcome = match user.require(payload.action.permission(), Some(job.job_group)) {
+            Ok(()) => bulk_apply(&state, &user, &payload, target_group, job).await,
+            Err(err) => Err(err),
+        };
+        let (success, message) = match outcome {
+            Ok((success, message)) => {
+                before.push(snapshot);
+                (success, message)
+            }
+            Err(err) => {
+                if matches!(err, AppError::DbError(_) | AppError::Internal(_)) {
+                    error!(job_id, error = ?err, "批量操作任务失败");
+                }
+                (false, err.to_string())
+            }
+        };
+        results.push(BulkItemResult {
+            job_id,
+            success,
+            message,
+        });
     }
 
-    Ok(response.json::<ExecutorReturn<String>>().await?)
+    let succeeded = results.iter().filter(|item| item.success).count();
+    let result = BulkResult {
+        action: payload.action,
+        total: results.len(),
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    };
+    // 各任务已逐个生效（可能已下发到执行器），审计失败时不再让整批请求报错
+    audit::record_after_dispatch(
+        &state,
+        &user,
+        &client,
+        AuditEvent::without_id(payload.action.audit_action(), AuditTarget::JobInfo)
+            .before(&before)
+            .after(&json!({ "request": payload, "result": result })),
+    )
+    .await;
+    Ok(Json(result))
 }
 
-async fn resolve_executor_addresses(
+async fn bulk_apply(
     state: &AppState,
-    group: &job_group::Model,
-    override_address: Option<&str>,
-) -> AppResult<Vec<String>> {
-    if let Some(raw) = override_address {
-        debug!(job_group = group.id, "收到手动指定执行器地址");
-        let list = parse_address_list(raw);
-        if !list.is_empty() {
-            info!(
-                job_group = group.id,
-                address_count = list.len(),
-                "使用手动指定的执行器地址"
-            );
-            return Ok(list);
+    user: &AuthUser,
+    payload: &BulkRequest,
+    target_group: Option<i32>,
+    job: job_info::Model,
+) -> AppResult<(bool, String)> {
+    match payload.action {
+        BulkAction::Start => {
+            set_running(state.db(), job, true).await?;
+            Ok((true, "已启动".into()))
         }
-        warn!(
-            job_group = group.id,
-            "手动指定的执行器地址为空或无效，继续尝试其它来源"
-        );
-    }
-
-    if group.address_type == 1 {
-        if let Some(raw) = group.address_list.as_deref() {
-            debug!(job_group = group.id, "尝试使用执行器分组静态地址");
-            let list = parse_address_list(raw);
-            if !list.is_empty() {
-                info!(
-                    job_group = group.id,
-                    address_count = list.len(),
-                    "使用执行器分组的静态地址"
-                );
-                return Ok(list);
+        BulkAction::Stop => {
+            set_running(state.db(), job, false).await?;
+            Ok((true, "已停止".into()))
+        }
+        BulkAction::Trigger => {
+            let dispatched =
+                trigger_one(state, user, job, payload.executor_param.clone(), None).await?;
+            Ok((
+                dispatched.code == 200,
+                format!(
+                    "{}，日志 #{}",
+                    trigger_message(&dispatched),
+                    dispatched.log_id
+                ),
+            ))
+        }
+        BulkAction::Delete => {
+            let outcome = delete_job(state, user, &job, false).await?;
+            Ok((true, outcome.message()))
+        }
+        BulkAction::ChangeGroup => {
+            let target = target_group.unwrap_or(job.job_group);
+            if job.job_group == target {
+                return Ok((true, "已在目标执行器中".into()));
             }
-            warn!(
-                job_group = group.id,
-                "执行器分组静态地址列表为空，尝试从注册中心获取"
-            );
+            move_to_group(state, user, job, target).await?;
+            Ok((true, format!("已移动到执行器 {target}")))
         }
     }
+}
 
-    info!(
-        job_group = group.id,
-        app_name = group.app_name.as_str(),
-        "从注册中心查询执行器地址"
-    );
-    let cutoff = Local::now()
-        .checked_sub_signed(Duration::seconds(REGISTRY_DEAD_TIMEOUT_SECONDS))
-        .unwrap_or_else(Local::now)
-        .naive_local();
-
-    let registries = job_registry::Entity::find()
-        .filter(job_registry::Column::RegistryGroup.eq("EXECUTOR"))
-        .filter(job_registry::Column::RegistryKey.eq(group.app_name.as_str()))
+/// 修改任务所属执行器，记录为一次任务定义修改。
+async fn move_to_group(
+    state: &AppState,
+    user: &AuthUser,
+    mut job: job_info::Model,
+    target: i32,
+) -> AppResult<job_info::Model> {
+    if let Some(key) = job.job_key.as_deref() {
+        ensure_job_key_available(state.db(), target, key, Some(job.id)).await?;
+    }
+    let txn = state.db().begin().await?;
+    job_revision::ensure_baseline(&txn, &job).await?;
+    job.job_group = target;
+    job.update_time = Some(Local::now().naive_local());
+    let active: job_info::ActiveModel = job.into();
+    let updated = active.reset_all().update(&txn).await?;
+    job_revision::record(&txn, &updated, RevisionAction::Update, &user.username).await?;
+    txn.commit().await?;
+    Ok(updated)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RevisionDto {
+    id: i64,
+    job_id: i32,
+    revision: i32,
+    action: String,
+    operator: String,
+    created_at: chrono::NaiveDateTime,
+}
+
+impl From<job_info_revision::Model> for RevisionDto {
+    fn from(value: job_info_revision::Model) -> Self {
+        Self {
+            id: value.id,
+            job_id: value.job_id,
+            revision: value.revision,
+            action: value.action,
+            operator: value.operator,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RevisionDetailDto {
+    #[serde(flatten)]
+    revision: RevisionDto,
+    job: JobInfoDto,
+}
+
+async fn find_job(state: &AppState, user: &AuthUser, id: i32) -> AppResult<job_info::Model> {
+    let job = job_info::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::View, Some(job.job_group))?;
+    Ok(job)
+}
+
+/// 任务定义的历史版本，按版本号倒序。
+async fn list_revisions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<RevisionDto>>> {
+    find_job(&state, &user, id).await?;
+    let revisions = job_info_revision::Entity::find()
+        .filter(job_info_revision::Column::JobId.eq(id))
+        .order_by_desc(job_info_revision::Column::Revision)
         .all(state.db())
-        .await?;
+        .await?
+        .into_iter()
+        .map(RevisionDto::from)
+        .collect();
+    Ok(Json(revisions))
+}
 
-    let mut unique = HashSet::new();
-    let mut alive: Vec<String> = Vec::new();
-    let mut stale: Vec<(String, Option<chrono::NaiveDateTime>)> = Vec::new();
+async fn load_revision(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((id, revision)): Path<(i32, i32)>,
+) -> AppResult<Json<RevisionDetailDto>> {
+    find_job(&state, &user, id).await?;
+    let revision = job_revision::find(state.db(), id, revision).await?;
+    let job = job_revision::snapshot(&revision)?;
+    Ok(Json(RevisionDetailDto {
+        revision: RevisionDto::from(revision),
+        job: JobInfoDto::from(job),
+    }))
+}
 
-    for item in registries {
-        let Some(address) = normalize_executor_address(&item.registry_value) else {
-            continue;
-        };
+#[derive(Debug, Deserialize)]
+struct DiffParams {
+    from: i32,
+    /// 不传时与任务当前定义对比
+    to: Option<i32>,
+}
 
-        if !unique.insert(address.clone()) {
-            continue;
+/// 对比两个版本的任务定义，返回 `{"字段": [from 的值, to 的值]}`。
+async fn diff_revisions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i32>,
+    Query(params): Query<DiffParams>,
+) -> AppResult<Json<serde_json::Value>> {
+    let current = find_job(&state, &user, id).await?;
+    let from = job_revision::snapshot(&job_revision::find(state.db(), id, params.from).await?)?;
+    let to = match params.to {
+        Some(revision) => {
+            job_revision::snapshot(&job_revision::find(state.db(), id, revision).await?)?
         }
+        None => current,
+    };
+    Ok(Json(json!({
+        "from": params.from,
+        "to": params.to,
+        "changes": job_revision::diff(&from, &to)?,
+    })))
+}
 
-        if item.update_time.map(|t| t >= cutoff).unwrap_or(false) {
-            alive.push(address);
-        } else {
-            stale.push((address, item.update_time));
-        }
+/// 将任务定义恢复到指定版本，恢复结果记为一个新版本，调度状态保持不变。
+async fn restore_revision(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path((id, revision)): Path<(i32, i32)>,
+) -> AppResult<Json<JobInfoDto>> {
+    let mut model = job_info::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    user.require(Permission::Develop, Some(model.job_group))?;
+
+    let target = job_revision::find(state.db(), id, revision).await?;
+    let snapshot = job_revision::snapshot(&target)?;
+    user.require(Permission::Develop, Some(snapshot.job_group))?;
+    ensure_group_exists(&state, snapshot.job_group).await?;
+    // 恢复时保留任务当前的标识，移入其它执行器前需确认目标执行器中没有同名任务
+    if let Some(key) = model.job_key.as_deref() {
+        ensure_job_key_available(state.db(), snapshot.job_group, key, Some(id)).await?;
     }
 
-    if !stale.is_empty() {
-        let last_seen = stale.iter().filter_map(|(_, ts)| *ts).max();
-        warn!(
-            job_group = group.id,
-            skipped = stale.len(),
-            cutoff = cutoff.to_string(),
-            last_seen = last_seen.map(|ts| ts.to_string()),
-            "剔除超过心跳超时时间的执行器实例"
-        );
-    }
-
-    if !alive.is_empty() {
-        info!(
-            job_group = group.id,
-            address_count = alive.len(),
-            stale_skipped = stale.len(),
-            "成功解析执行器地址"
-        );
-        return Ok(alive);
-    }
-
-    if !stale.is_empty() {
-        let fallback: Vec<String> = stale.into_iter().map(|(addr, _)| addr).collect();
-        warn!(
-            job_group = group.id,
-            address_count = fallback.len(),
-            cutoff = cutoff.to_string(),
-            "所有实例心跳超时，回退返回最近一次上报的执行器地址"
-        );
-        return Ok(fallback);
-    }
-
-    error!(job_group = group.id, "未检测到可用执行器实例");
-    Err(AppError::BadRequest(
-        "未检测到可用的执行器实例，请确认执行器是否注册成功并保持心跳".into(),
-    ))
-}
+    let txn = state.db().begin().await?;
+    job_revision::ensure_baseline(&txn, &model).await?;
 
-fn parse_address_list(input: &str) -> Vec<String> {
-    let mut values = Vec::new();
-    for value in input.split([',', '\n']) {
-        if let Some(address) = normalize_executor_address(value) {
-            if !values.contains(&address) {
-                values.push(address);
-            }
-        }
+    let before = model.clone();
+    job_revision::apply(&mut model, snapshot);
+    let now = Local::now().naive_local();
+    let glue_changed = model.glue_type != before.glue_type
+        || model.glue_source != before.glue_source
+        || model.glue_remark != before.glue_remark;
+    if glue_changed {
+        model.glue_updatetime = Some(now);
     }
-    values
-}
+    model.update_time = Some(now);
 
-fn normalize_executor_address(raw: &str) -> Option<String> {
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return None;
+    let active: job_info::ActiveModel = model.into();
+    let updated = active.reset_all().update(&txn).await?;
+    if glue_changed {
+        job_logglue::Entity::insert(job_logglue::ActiveModel {
+            job_id: Set(id),
+            glue_type: Set(Some(updated.glue_type.clone())),
+            glue_source: Set(updated.glue_source.clone()),
+            glue_remark: Set(format!("恢复至版本 {revision}")),
+            add_time: Set(Some(now)),
+            update_time: Set(Some(now)),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
     }
+    job_revision::record(&txn, &updated, RevisionAction::Restore, &user.username).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobInfo, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
 
-    let normalized = if trimmed.contains("://") {
-        trimmed.to_string()
-    } else {
-        format!("http://{}", trimmed)
-    };
+    Ok(Json(JobInfoDto::from(updated)))
+}
 
-    Some(normalized)
+fn normalize_optional_payload_string(value: Option<String>) -> Option<String> {
+    value.and_then(|raw| {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            None
+        } else if trimmed.len() == raw.len() {
+            Some(raw)
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
 }
 
 #[derive(Debug, Deserialize)]
@@ -872,6 +1344,7 @@ async fn next_trigger_time(
         id: 0,
         job_group: 0,
         job_desc: String::new(),
+        job_key: None,
         add_time: None,
         update_time: None,
         author: None,
@@ -890,6 +1363,7 @@ async fn next_trigger_time(
         glue_remark: None,
         glue_updatetime: None,
         child_jobid: None,
+        cloned_from: None,
         trigger_status: 0,
         trigger_last_time: 0,
         trigger_next_time: 0,
@@ -930,7 +1404,7 @@ fn compute_next_trigger(job: &job_info::Model) -> AppResult<Option<i64>> {
     compute_next_for_params(job, Utc::now())
 }
 
-fn compute_next_for_params(
+pub(crate) fn compute_next_for_params(
     job: &job_info::Model,
     after: chrono::DateTime<Utc>,
 ) -> AppResult<Option<i64>> {
@@ -964,3 +1438,156 @@ fn compute_next_for_params(
         other => Err(AppError::BadRequest(format!("不支持的调度类型: {other}"))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::audit_log;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn restore_revision_writes_snapshot_back_to_job() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let job = test_support::insert_job(&state, 1, 1).await;
+        job_revision::record(state.db(), &job, RevisionAction::Create, "admin")
+            .await
+            .unwrap();
+        job_info::ActiveModel {
+            id: Set(job.id),
+            job_desc: Set("已修改".into()),
+            executor_handler: Set(Some("otherHandler".into())),
+            ..Default::default()
+        }
+        .update(state.db())
+        .await
+        .unwrap();
+
+        let Json(dto) = restore_revision(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path((job.id, 1)),
+        )
+        .await
+        .unwrap();
+
+        let stored = job_info::Entity::find_by_id(job.id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.job_desc, "任务 1");
+        assert_eq!(stored.executor_handler.as_deref(), Some("demoJobHandler"));
+        assert!(stored.update_time.is_some());
+        assert_eq!(dto.job_desc, stored.job_desc);
+    }
+
+    async fn run_bulk(
+        state: &AppState,
+        action: BulkAction,
+        ids: Vec<i32>,
+        target_group: Option<i32>,
+    ) {
+        let Json(result) = bulk(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Json(BulkRequest {
+                action,
+                ids,
+                filter: None,
+                target_group,
+                executor_param: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(result.results.iter().all(|item| item.success));
+    }
+
+    async fn stored_job(state: &AppState, id: i32) -> job_info::Model {
+        job_info::Entity::find_by_id(id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn bulk_change_group_moves_job() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        test_support::insert_group(&state, 2).await;
+        let job = test_support::insert_job(&state, 1, 1).await;
+
+        run_bulk(&state, BulkAction::ChangeGroup, vec![job.id], Some(2)).await;
+
+        assert_eq!(stored_job(&state, job.id).await.job_group, 2);
+    }
+
+    #[tokio::test]
+    async fn bulk_start_and_stop_update_trigger_status() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let job = test_support::insert_job(&state, 1, 1).await;
+
+        run_bulk(&state, BulkAction::Start, vec![job.id], None).await;
+        let started = stored_job(&state, job.id).await;
+        assert_eq!(started.trigger_status, 1);
+        assert!(started.trigger_next_time > 0);
+
+        run_bulk(&state, BulkAction::Stop, vec![job.id], None).await;
+        let stopped = stored_job(&state, job.id).await;
+        assert_eq!(stopped.trigger_status, 0);
+        assert_eq!(stopped.trigger_next_time, 0);
+    }
+
+    #[tokio::test]
+    async fn update_persists_job_and_audits_new_values() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let job = test_support::insert_job(&state, 1, 1).await;
+
+        let Json(dto) = update(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(job.id),
+            Json(SaveJobInfoRequest {
+                job_group: 1,
+                job_desc: "每日对账".into(),
+                job_key: None,
+                author: job.author.clone(),
+                alarm_email: None,
+                schedule_type: job.schedule_type.clone(),
+                schedule_conf: job.schedule_conf.clone(),
+                misfire_strategy: job.misfire_strategy.clone(),
+                executor_route_strategy: job.executor_route_strategy.clone(),
+                executor_handler: Some("reconcileHandler".into()),
+                executor_param: None,
+                executor_block_strategy: job.executor_block_strategy.clone(),
+                executor_timeout: Some(30),
+                executor_fail_retry_count: None,
+                glue_type: job.glue_type.clone(),
+                glue_source: None,
+                glue_remark: None,
+                child_jobid: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stored = stored_job(&state, job.id).await;
+        assert_eq!(stored.job_desc, "每日对账");
+        assert_eq!(stored.executor_handler.as_deref(), Some("reconcileHandler"));
+        assert_eq!(stored.executor_timeout, 30);
+        assert_eq!(dto.job_desc, stored.job_desc);
+        let entry = audit_log::Entity::find()
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(entry.after_data.unwrap().contains("每日对账"));
+    }
+}
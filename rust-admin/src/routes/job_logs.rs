@@ -1,28 +1,49 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
 use axum::{
+    body::Body,
     extract::{Form, Path, Query, State},
+    http::header,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
-use chrono::{Duration, Local, LocalResult, Months, NaiveDateTime, TimeZone, Utc};
-use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use chrono::{Duration, Local, Months, NaiveDateTime};
+use futures::Stream;
 use sea_orm::{
-    query::*, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder,
-    QuerySelect,
+    query::*, sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tracing::error;
 
-use crate::auth::AuthUser;
-use crate::entities::{job_group, job_log};
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
+use crate::entities::{self, job_group, job_info, job_log};
 use crate::error::{AppError, AppResult};
+use crate::executor_log::{self, LogContent};
+use crate::log_archive;
+use crate::log_page::{LogCursor, TotalMode};
+use crate::log_search::{SearchField, SearchHighlight, SearchKeyword};
+use crate::session::ClientInfo;
 use crate::state::AppState;
+use crate::trigger::{self, Dispatch, Sharding};
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(page_list))
+        .route("/export", get(export))
+        .route("/rerun", post(rerun_failed))
         .route("/:id", get(detail))
         .route("/:id/cat", get(log_content))
+        .route("/:id/tail", get(tail_log))
         .route("/:id/kill", post(kill))
+        .route("/:id/rerun", post(rerun))
         .route("/clear", post(clear))
 }
 
@@ -46,6 +67,50 @@ struct PageParams {
     job_id: Option<i32>,
     log_status: Option<i32>,
     filter_time: Option<String>,
+    /// 全文检索关键字，匹配调度备注、执行备注与归档的执行日志
+    search: Option<String>,
+    executor_address: Option<String>,
+    executor_handler: Option<String>,
+    trigger_code: Option<i32>,
+    /// 最短执行耗时（毫秒），只匹配已回调的日志
+    min_duration_millis: Option<i64>,
+    /// 游标翻页：传入（首页为空字符串）时忽略 `start`，按 `(trigger_time, id)` 倒序返回 `next_cursor`
+    cursor: Option<String>,
+    /// 游标翻页的总数统计：`none`（默认）、`approx`、`exact`
+    total: Option<String>,
+}
+
+impl PageParams {
+    fn filter(&self) -> LogFilter {
+        LogFilter {
+            job_group: self.job_group,
+            job_id: self.job_id,
+            log_status: self.log_status,
+            filter_time: self.filter_time.clone(),
+            search: self.search.clone(),
+            executor_address: self.executor_address.clone(),
+            executor_handler: self.executor_handler.clone(),
+            trigger_code: self.trigger_code,
+            min_duration_millis: self.min_duration_millis,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CursorPageResult<T> {
+    data: Vec<T>,
+    /// 下一页游标，没有更多数据时为空
+    next_cursor: Option<String>,
+    /// 按 `total` 参数统计的总数，`none` 时为空
+    records_total: Option<u64>,
+    total_approximate: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum PageResponse<T> {
+    Offset(PageResult<T>),
+    Cursor(CursorPageResult<T>),
 }
 
 #[derive(Debug, Serialize)]
@@ -73,6 +138,167 @@ impl<T> From<PageResult<T>> for LegacyPageResult<T> {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct ExportParams {
+    job_group: i32,
+    job_id: Option<i32>,
+    log_status: Option<i32>,
+    filter_time: Option<String>,
+    search: Option<String>,
+    executor_address: Option<String>,
+    executor_handler: Option<String>,
+    trigger_code: Option<i32>,
+    min_duration_millis: Option<i64>,
+    /// `csv`（默认）或 `ndjson`
+    format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn parse(value: Option<&str>) -> AppResult<Self> {
+        match value.map(str::trim).unwrap_or("csv") {
+            "" | "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            other => Err(AppError::BadRequest(format!(
+                "不支持的导出格式 `{other}`，可选 csv、ndjson"
+            ))),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// 由调度结果与执行结果推导出的执行状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunStatus {
+    Success,
+    Failed,
+    Running,
+}
+
+impl RunStatus {
+    fn of(log: &job_log::Model) -> Self {
+        if log.handle_code == 200 {
+            Self::Success
+        } else if log.handle_code == 0 && log.trigger_code == 200 {
+            Self::Running
+        } else {
+            Self::Failed
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failed => "failed",
+            Self::Running => "running",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobLogExportRow {
+    #[serde(flatten)]
+    log: JobLogDto,
+    /// 触发到执行回调的耗时（毫秒），尚未回调时为空
+    duration_millis: Option<i64>,
+    status: &'static str,
+}
+
+impl From<job_log::Model> for JobLogExportRow {
+    fn from(value: job_log::Model) -> Self {
+        let duration_millis = match (value.trigger_time, value.handle_time) {
+            (Some(trigger), Some(handle)) => Some((handle - trigger).num_milliseconds().max(0)),
+            _ => None,
+        };
+        let status = RunStatus::of(&value).as_str();
+        Self {
+            log: JobLogDto::from(value),
+            duration_millis,
+            status,
+        }
+    }
+}
+
+const EXPORT_CSV_HEADER: [&str; 16] = [
+    "id",
+    "jobGroup",
+    "jobId",
+    "executorAddress",
+    "executorHandler",
+    "executorParam",
+    "triggerTime",
+    "triggerCode",
+    "triggerMsg",
+    "handleTime",
+    "handleCode",
+    "handleMsg",
+    "alarmStatus",
+    "rerunOf",
+    "durationMillis",
+    "status",
+];
+
+/// 以 `=`、`+`、`-`、`@`、制表符或回车开头的单元格会被表格软件当作公式执行，前置 `'` 使其按文本显示。
+fn spreadsheet_safe(value: Option<&str>) -> String {
+    let value = value.unwrap_or_default();
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    }
+}
+
+impl JobLogExportRow {
+    /// 执行器地址、Handler、参数与调度消息来自执行器或用户输入，写入前统一做公式转义。
+    fn csv_record(&self) -> [String; 16] {
+        let log = &self.log;
+        let time = |value: Option<NaiveDateTime>| {
+            value
+                .map(|value| value.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+        [
+            log.id.to_string(),
+            log.job_group.to_string(),
+            log.job_id.to_string(),
+            spreadsheet_safe(log.executor_address.as_deref()),
+            spreadsheet_safe(log.executor_handler.as_deref()),
+            spreadsheet_safe(log.executor_param.as_deref()),
+            time(log.trigger_time),
+            log.trigger_code.to_string(),
+            spreadsheet_safe(log.trigger_msg.as_deref()),
+            time(log.handle_time),
+            log.handle_code.to_string(),
+            spreadsheet_safe(log.handle_msg.as_deref()),
+            log.alarm_status.to_string(),
+            log.rerun_of.map(|id| id.to_string()).unwrap_or_default(),
+            self.duration_millis
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            self.status.to_string(),
+        ]
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct JobLogDto {
@@ -89,6 +315,10 @@ struct JobLogDto {
     handle_code: i32,
     handle_msg: Option<String>,
     alarm_status: i8,
+    /// 重跑来源的调度日志
+    rerun_of: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    highlights: Vec<SearchHighlight>,
 }
 
 impl From<job_log::Model> for JobLogDto {
@@ -107,6 +337,8 @@ impl From<job_log::Model> for JobLogDto {
             handle_code: value.handle_code,
             handle_msg: value.handle_msg,
             alarm_status: value.alarm_status,
+            rerun_of: value.rerun_of,
+            highlights: Vec::new(),
         }
     }
 }
@@ -131,6 +363,13 @@ impl From<LegacyPageParams> for PageParams {
             job_id: value.job_id,
             log_status: value.log_status,
             filter_time: value.filter_time,
+            search: None,
+            executor_address: None,
+            executor_handler: None,
+            trigger_code: None,
+            min_duration_millis: None,
+            cursor: None,
+            total: None,
         }
     }
 }
@@ -195,54 +434,34 @@ struct LegacyClearRequest {
 
 async fn page_list(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Query(params): Query<PageParams>,
-) -> AppResult<Json<PageResult<JobLogDto>>> {
-    let result = page_list_impl(&state, params).await?;
+) -> AppResult<Json<PageResponse<JobLogDto>>> {
+    let result = if params.cursor.is_some() {
+        PageResponse::Cursor(cursor_page_impl(&state, &user, params).await?)
+    } else {
+        PageResponse::Offset(page_list_impl(&state, &user, params).await?)
+    };
     Ok(Json(result))
 }
 
-async fn page_list_impl(state: &AppState, params: PageParams) -> AppResult<PageResult<JobLogDto>> {
+async fn page_list_impl(
+    state: &AppState,
+    user: &AuthUser,
+    params: PageParams,
+) -> AppResult<PageResult<JobLogDto>> {
+    user.require(Permission::View, Some(params.job_group))?;
     ensure_job_group(state, params.job_group).await?;
 
-    let PageParams {
-        start,
-        length,
-        job_group,
-        job_id,
-        log_status,
-        filter_time,
-    } = params;
-
-    let mut query = job_log::Entity::find().filter(job_log::Column::JobGroup.eq(job_group));
-
-    if let Some(job_id) = job_id {
-        if job_id > 0 {
-            query = query.filter(job_log::Column::JobId.eq(job_id));
-        }
-    }
-
-    if let Some(status) = log_status {
-        if status == 1 {
-            query = query.filter(job_log::Column::HandleCode.eq(200));
-        } else if status == 2 {
-            query = query.filter(job_log::Column::HandleCode.ne(200));
-        }
-    }
-
-    if let Some(range) = filter_time.as_ref() {
-        if let Some((start_time, end_time)) = parse_time_range(range) {
-            query = query
-                .filter(job_log::Column::TriggerTime.gte(start_time))
-                .filter(job_log::Column::TriggerTime.lte(end_time));
-        }
-    }
+    let (query, keyword) = params.filter().query()?;
+    let start = params.start;
+    let length = params.length;
 
     let offset = start.unwrap_or(0);
     let limit = length.unwrap_or(10);
 
     let total = query.clone().count(state.db()).await? as u64;
-    let data = query
+    let mut data: Vec<JobLogDto> = query
         .order_by_desc(job_log::Column::TriggerTime)
         .offset(offset)
         .limit(limit)
@@ -251,6 +470,9 @@ async fn page_list_impl(state: &AppState, params: PageParams) -> AppResult<PageR
         .into_iter()
         .map(JobLogDto::from)
         .collect();
+    if let Some(keyword) = keyword.as_ref() {
+        attach_highlights(state, keyword, &mut data).await?;
+    }
 
     Ok(PageResult {
         records_total: total,
@@ -259,187 +481,506 @@ async fn page_list_impl(state: &AppState, params: PageParams) -> AppResult<PageR
     })
 }
 
+/// 游标翻页：按 `(trigger_time, id)` 倒序，使用索引定位到上一页末尾，翻页代价与深度无关。
+async fn cursor_page_impl(
+    state: &AppState,
+    user: &AuthUser,
+    params: PageParams,
+) -> AppResult<CursorPageResult<JobLogDto>> {
+    user.require(Permission::View, Some(params.job_group))?;
+    ensure_job_group(state, params.job_group).await?;
+
+    let total_mode = TotalMode::parse(params.total.as_deref())
+        .ok_or_else(|| AppError::BadRequest("total 可选 none、approx、exact".into()))?;
+    let cursor = match params.cursor.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(raw) => Some(
+            LogCursor::parse(raw).ok_or_else(|| AppError::BadRequest("无效的翻页游标".into()))?,
+        ),
+    };
+    let limit = params.length.unwrap_or(10).clamp(1, 500);
+    let (query, keyword) = params.filter().query()?;
+
+    let (records_total, total_approximate) = match total_mode {
+        TotalMode::None => (None, false),
+        TotalMode::Exact => (Some(query.clone().count(state.db()).await?), false),
+        TotalMode::Approx => (Some(estimate_rows(state, &query).await?), true),
+    };
+
+    let mut page = query;
+    if let Some(cursor) = cursor {
+        // 倒序时 trigger_time 为空的日志排在最后
+        let after = match cursor.trigger_time {
+            Some(time) => Condition::any()
+                .add(job_log::Column::TriggerTime.lt(time))
+                .add(
+                    Condition::all()
+                        .add(job_log::Column::TriggerTime.eq(time))
+                        .add(job_log::Column::Id.lt(cursor.id)),
+                )
+                .add(job_log::Column::TriggerTime.is_null()),
+            None => Condition::all()
+                .add(job_log::Column::TriggerTime.is_null())
+                .add(job_log::Column::Id.lt(cursor.id)),
+        };
+        page = page.filter(after);
+    }
+    let rows = page
+        .order_by_desc(job_log::Column::TriggerTime)
+        .order_by_desc(job_log::Column::Id)
+        .limit(limit + 1)
+        .all(state.db())
+        .await?;
+
+    let has_more = rows.len() as u64 > limit;
+    let mut data: Vec<JobLogDto> = rows
+        .into_iter()
+        .take(limit as usize)
+        .map(JobLogDto::from)
+        .collect();
+    let next_cursor = data.last().filter(|_| has_more).map(|last| {
+        LogCursor {
+            trigger_time: last.trigger_time,
+            id: last.id,
+        }
+        .encode()
+    });
+    if let Some(keyword) = keyword.as_ref() {
+        attach_highlights(state, keyword, &mut data).await?;
+    }
+
+    Ok(CursorPageResult {
+        data,
+        next_cursor,
+        records_total,
+        total_approximate,
+    })
+}
+
+/// 用 `EXPLAIN` 的估算行数作为近似总数，避免在大表上执行 `COUNT(*)`。
+async fn estimate_rows(state: &AppState, query: &Select<job_log::Entity>) -> AppResult<u64> {
+    let backend = state.db().get_database_backend();
+    let statement = query.build(backend);
+    let explain = Statement::from_sql_and_values(
+        backend,
+        format!("EXPLAIN {}", statement.sql),
+        statement.values.map(|values| values.0).unwrap_or_default(),
+    );
+    let Some(row) = state.db().query_one(explain).await? else {
+        return Ok(0);
+    };
+    let rows = row
+        .try_get::<Option<u64>>("", "rows")
+        .ok()
+        .flatten()
+        .unwrap_or(0);
+    let filtered = row
+        .try_get::<Option<f64>>("", "filtered")
+        .ok()
+        .flatten()
+        .unwrap_or(100.0);
+    Ok((rows as f64 * filtered / 100.0).round() as u64)
+}
+
+/// 每批从数据库读取的日志条数。
+const EXPORT_BATCH: u64 = 1000;
+
+/// 以流的形式导出日志：按 ID 倒序分批读取，每批写出后再读下一批，内存占用与结果总数无关。
+async fn export(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(params): Query<ExportParams>,
+) -> AppResult<Response> {
+    user.require(Permission::View, Some(params.job_group))?;
+    ensure_job_group(&state, params.job_group).await?;
+    let format = ExportFormat::parse(params.format.as_deref())?;
+    let filter = LogFilter {
+        job_group: params.job_group,
+        job_id: params.job_id,
+        log_status: params.log_status,
+        filter_time: params.filter_time,
+        search: params.search,
+        executor_address: params.executor_address,
+        executor_handler: params.executor_handler,
+        trigger_code: params.trigger_code,
+        min_duration_millis: params.min_duration_millis,
+    };
+    let (query, _) = filter.query()?;
+
+    let stream = async_stream::stream! {
+        if format == ExportFormat::Csv {
+            // 带 BOM 以便 Excel 正确识别 UTF-8
+            let mut head = "\u{feff}".as_bytes().to_vec();
+            head.extend(encode_csv(&EXPORT_CSV_HEADER));
+            yield Ok::<_, std::io::Error>(head);
+        }
+        let mut last_id: Option<i64> = None;
+        loop {
+            let mut batch = query.clone();
+            if let Some(id) = last_id {
+                batch = batch.filter(job_log::Column::Id.lt(id));
+            }
+            let rows = match batch
+                .order_by_desc(job_log::Column::Id)
+                .limit(EXPORT_BATCH)
+                .all(state.db())
+                .await
+            {
+                Ok(rows) => rows,
+                Err(err) => {
+                    error!("导出调度日志失败: {err}");
+                    yield Err(std::io::Error::other(err));
+                    break;
+                }
+            };
+            let count = rows.len() as u64;
+            last_id = rows.last().map(|row| row.id);
+
+            let mut chunk = Vec::new();
+            for row in rows.into_iter().map(JobLogExportRow::from) {
+                match format {
+                    ExportFormat::Csv => chunk.extend(encode_csv(&row.csv_record())),
+                    ExportFormat::Ndjson => {
+                        if let Err(err) = serde_json::to_writer(&mut chunk, &row) {
+                            error!("导出调度日志失败: {err}");
+                        }
+                        chunk.push(b'\n');
+                    }
+                }
+            }
+            if !chunk.is_empty() {
+                yield Ok(chunk);
+            }
+            if count < EXPORT_BATCH {
+                break;
+            }
+        }
+    };
+
+    let file_name = format!(
+        "job-logs-{}-{}.{}",
+        params.job_group,
+        Local::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+fn encode_csv<S: AsRef<[u8]>>(record: &[S]) -> Vec<u8> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    // 写入内存缓冲区不会失败
+    let _ = writer.write_record(record);
+    writer.into_inner().unwrap_or_default()
+}
+
+/// 日志列表与导出共用的筛选条件。
+struct LogFilter {
+    job_group: i32,
+    job_id: Option<i32>,
+    log_status: Option<i32>,
+    filter_time: Option<String>,
+    search: Option<String>,
+    executor_address: Option<String>,
+    executor_handler: Option<String>,
+    trigger_code: Option<i32>,
+    min_duration_millis: Option<i64>,
+}
+
+impl LogFilter {
+    fn query(&self) -> AppResult<(Select<job_log::Entity>, Option<SearchKeyword>)> {
+        let mut query =
+            job_log::Entity::find().filter(job_log::Column::JobGroup.eq(self.job_group));
+
+        if let Some(job_id) = self.job_id {
+            if job_id > 0 {
+                query = query.filter(job_log::Column::JobId.eq(job_id));
+            }
+        }
+
+        // 1-成功、2-失败、3-运行中，与 RunStatus 的推导规则一致
+        let running = Condition::all()
+            .add(job_log::Column::TriggerCode.eq(200))
+            .add(job_log::Column::HandleCode.eq(0));
+        match self.log_status {
+            Some(1) => query = query.filter(job_log::Column::HandleCode.eq(200)),
+            Some(2) => {
+                query = query
+                    .filter(job_log::Column::HandleCode.ne(200))
+                    .filter(running.not())
+            }
+            Some(3) => query = query.filter(running),
+            _ => {}
+        }
+
+        if let Some(address) = non_empty(self.executor_address.as_deref()) {
+            query = query.filter(job_log::Column::ExecutorAddress.eq(address));
+        }
+        if let Some(handler) = non_empty(self.executor_handler.as_deref()) {
+            query = query.filter(job_log::Column::ExecutorHandler.eq(handler));
+        }
+        if let Some(code) = self.trigger_code {
+            query = query.filter(job_log::Column::TriggerCode.eq(code));
+        }
+        if let Some(millis) = self.min_duration_millis.filter(|millis| *millis > 0) {
+            query = query.filter(Expr::cust_with_values(
+                "`xxl_job_log`.`handle_time` IS NOT NULL \
+                 AND TIMESTAMPDIFF(MICROSECOND, `xxl_job_log`.`trigger_time`, `xxl_job_log`.`handle_time`) >= ?",
+                [millis.saturating_mul(1000)],
+            ));
+        }
+
+        if let Some(range) = self.filter_time.as_ref() {
+            if let Some((start_time, end_time)) = parse_time_range(range) {
+                query = query
+                    .filter(job_log::Column::TriggerTime.gte(start_time))
+                    .filter(job_log::Column::TriggerTime.lte(end_time));
+            }
+        }
+
+        let keyword = match non_empty(self.search.as_deref()) {
+            Some(raw) => Some(
+                SearchKeyword::parse(raw)
+                    .ok_or_else(|| AppError::BadRequest("搜索关键字至少需要 2 个字符".into()))?,
+            ),
+            None => None,
+        };
+        if let Some(keyword) = keyword.as_ref() {
+            let against = keyword.boolean_query();
+            // 分别走两张表的全文索引，再按日志 ID 合并
+            query = query.filter(
+                Condition::any()
+                    .add(Expr::cust_with_values(
+                        "`xxl_job_log`.`id` IN (SELECT `id` FROM `xxl_job_log` \
+                         WHERE MATCH (`trigger_msg`, `handle_msg`) AGAINST (? IN BOOLEAN MODE))",
+                        [against.clone()],
+                    ))
+                    .add(Expr::cust_with_values(
+                        "`xxl_job_log`.`id` IN (SELECT `log_id` FROM `xxl_job_log_archive` \
+                         WHERE MATCH (`log_text`) AGAINST (? IN BOOLEAN MODE))",
+                        [against],
+                    )),
+            );
+        }
+        Ok((query, keyword))
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// 为搜索结果标注命中片段，归档日志文本按当前页的日志 ID 一次查出。
+async fn attach_highlights(
+    state: &AppState,
+    keyword: &SearchKeyword,
+    logs: &mut [JobLogDto],
+) -> AppResult<()> {
+    if logs.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<i64> = logs.iter().map(|log| log.id).collect();
+    let texts: HashMap<i64, String> = entities::log_archive::Entity::find()
+        .select_only()
+        .columns([
+            entities::log_archive::Column::LogId,
+            entities::log_archive::Column::LogText,
+        ])
+        .filter(entities::log_archive::Column::LogId.is_in(ids))
+        .into_tuple::<(i64, Option<String>)>()
+        .all(state.db())
+        .await?
+        .into_iter()
+        .filter_map(|(id, text)| text.map(|text| (id, text)))
+        .collect();
+
+    for log in logs.iter_mut() {
+        let mut highlights = Vec::new();
+        if let Some(msg) = log.trigger_msg.as_deref() {
+            highlights.extend(keyword.highlight(SearchField::TriggerMsg, msg));
+        }
+        if let Some(msg) = log.handle_msg.as_deref() {
+            highlights.extend(keyword.highlight(SearchField::HandleMsg, msg));
+        }
+        if let Some(text) = texts.get(&log.id) {
+            highlights.extend(keyword.highlight(SearchField::Log, text));
+        }
+        log.highlights = highlights;
+    }
+    Ok(())
+}
+
 async fn detail(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Path(id): Path<i64>,
 ) -> AppResult<Json<JobLogDto>> {
-    let log = detail_impl(&state, id).await?;
+    let log = detail_impl(&state, &user, id).await?;
     Ok(Json(log))
 }
 
-async fn detail_impl(state: &AppState, id: i64) -> AppResult<JobLogDto> {
+async fn detail_impl(state: &AppState, user: &AuthUser, id: i64) -> AppResult<JobLogDto> {
+    let log = find_log(state, user, id, Permission::View).await?;
+    Ok(JobLogDto::from(log))
+}
+
+async fn find_log(
+    state: &AppState,
+    user: &AuthUser,
+    id: i64,
+    permission: Permission,
+) -> AppResult<job_log::Model> {
     let log = job_log::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("日志不存在".into()))?;
-    Ok(JobLogDto::from(log))
+    user.require(permission, Some(log.job_group))?;
+    Ok(log)
 }
 
 async fn log_content(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Path(id): Path<i64>,
     Query(params): Query<LogCatParams>,
 ) -> AppResult<Json<LogContentDto>> {
     let from = params.from_line_num.unwrap_or(1).max(1);
-    let content = log_content_impl(&state, id, from).await?;
+    let content = log_content_impl(&state, &user, id, from).await?;
     Ok(Json(LogContentDto::from(content)))
 }
 
-async fn log_content_impl(state: &AppState, id: i64, from: i64) -> AppResult<LogContent> {
-    let log = job_log::Entity::find_by_id(id)
-        .one(state.db())
-        .await?
-        .ok_or_else(|| AppError::NotFound("日志不存在".into()))?;
-    let summary_content = format!(
-        "调度日志:\n{}\n执行日志:\n{}",
-        log.trigger_msg.clone().unwrap_or_default(),
-        log.handle_msg.clone().unwrap_or_default()
-    );
+async fn log_content_impl(
+    state: &AppState,
+    user: &AuthUser,
+    id: i64,
+    from: i64,
+) -> AppResult<LogContent> {
+    let log = find_log(state, user, id, Permission::View).await?;
     let from = from.max(1);
+    Ok(match executor_log::fetch(state, &log, from).await? {
+        Ok(content) => content,
+        Err(reason) => fallback_content(state, &log, from, reason).await,
+    })
+}
 
-    if log.trigger_code != 200 && log.handle_code == 0 {
-        return Ok(build_summary(
-            from,
-            &summary_content,
-            Some("任务调度失败，执行日志不可用".into()),
-        ));
-    }
-
-    let executor_address = match log
-        .executor_address
-        .as_deref()
-        .map(str::trim)
-        .filter(|addr| !addr.is_empty())
-    {
-        Some(address) => address,
-        None => {
-            return Ok(build_summary(
-                from,
-                &summary_content,
-                Some("执行器地址缺失，返回摘要日志".into()),
-            ))
-        }
-    };
-
-    let trigger_time = match log.trigger_time {
-        Some(time) => time,
-        None => {
-            return Ok(build_summary(
-                from,
-                &summary_content,
-                Some("触发时间缺失，返回摘要日志".into()),
-            ))
-        }
-    };
-
-    let trigger_timestamp = match Local.from_local_datetime(&trigger_time) {
-        LocalResult::Single(value) => value.timestamp_millis(),
-        _ => Utc.from_utc_datetime(&trigger_time).timestamp_millis(),
-    };
-
-    #[derive(Serialize)]
-    struct ExecutorLogRequest {
-        #[serde(rename = "logId")]
-        log_id: i64,
-        #[serde(rename = "logDateTim")]
-        log_date_tim: i64,
-        #[serde(rename = "fromLineNum")]
-        from_line_num: i64,
-    }
-
-    let mut url = executor_address.to_string();
-    if !url.ends_with('/') {
-        url.push('/');
-    }
-    url.push_str("log");
-
-    let mut request = state
-        .http_client()
-        .post(&url)
-        .header(CONTENT_TYPE, "application/json")
-        .json(&ExecutorLogRequest {
-            log_id: log.id,
-            log_date_tim: trigger_timestamp,
-            from_line_num: from,
-        })
-        .timeout(state.settings().executor.timeout());
-
-    if let Some(token) = state.settings().executor.access_token() {
-        let header_value = HeaderValue::from_str(token)
-            .map_err(|err| AppError::BadRequest(format!("访问令牌格式非法: {err}")))?;
-        request = request.header(
-            HeaderName::from_static("xxl-job-access-token"),
-            header_value,
-        );
-    }
-
-    let response = match request.send().await {
-        Ok(resp) => resp,
-        Err(err) => {
-            let message = if err.is_timeout() {
-                format!("请求执行器超时: {err}")
-            } else if err.is_connect() {
-                format!("无法连接到执行器: {err}")
-            } else {
-                format!("调用执行器失败: {err}")
-            };
-            return Ok(build_summary(from, &summary_content, Some(message)));
-        }
-    };
-
-    if !response.status().is_success() {
-        let message = format!("执行器返回状态码 {}", response.status());
-        return Ok(build_summary(from, &summary_content, Some(message)));
-    }
-
-    #[derive(Deserialize)]
-    struct ExecutorLogResponse {
-        code: i32,
-        msg: Option<String>,
-        content: Option<ExecutorLogContent>,
+/// 执行器不可达时优先返回归档的完整日志，没有归档时返回摘要日志。
+async fn fallback_content(
+    state: &AppState,
+    log: &job_log::Model,
+    from: i64,
+    reason: String,
+) -> LogContent {
+    match log_archive::load(state, log.id, from).await {
+        Some(archived) => archived,
+        None => build_summary(from, &summary_content(log), Some(reason)),
     }
+}
 
-    #[derive(Deserialize)]
-    struct ExecutorLogContent {
-        #[serde(rename = "fromLineNum")]
-        from_line_num: i64,
-        #[serde(rename = "toLineNum")]
-        to_line_num: i64,
-        #[serde(rename = "logContent")]
-        log_content: String,
-        #[serde(rename = "isEnd")]
-        is_end: Option<bool>,
-    }
+fn summary_content(log: &job_log::Model) -> String {
+    format!(
+        "调度日志:\n{}\n执行日志:\n{}",
+        log.trigger_msg.clone().unwrap_or_default(),
+        log.handle_msg.clone().unwrap_or_default()
+    )
+}
 
-    let payload = match response.json::<ExecutorLogResponse>().await {
-        Ok(body) => body,
-        Err(err) => {
-            let message = format!("解析执行器日志响应失败: {err}");
-            return Ok(build_summary(from, &summary_content, Some(message)));
-        }
-    };
+/// 实时日志流中连续拉取失败的上限，超过后回退到摘要日志并结束。
+const TAIL_MAX_FAILURES: u32 = 5;
 
-    if payload.code != 200 {
-        let message = payload.msg.unwrap_or_else(|| "执行器返回失败".into());
-        return Ok(build_summary(from, &summary_content, Some(message)));
-    }
+/// 以 Server-Sent Events 推送执行日志：管理端持续轮询执行器 `/log`，有新行时推送 `log` 事件，
+/// 执行器返回 `isEnd` 或执行结果回调到达后推送 `end` 事件并关闭。
+async fn tail_log(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Query(params): Query<LogCatParams>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let log = find_log(&state, &user, id, Permission::View).await?;
+    let from = params.from_line_num.unwrap_or(1).max(1);
+    Ok(Sse::new(tail_stream(state, log, from)).keep_alive(KeepAlive::default()))
+}
 
-    if let Some(content) = payload.content {
-        return Ok(LogContent {
-            from_line_num: content.from_line_num,
-            to_line_num: content.to_line_num,
-            end: content.is_end.unwrap_or(false),
-            log_content: content.log_content,
-        });
+fn tail_stream(
+    state: AppState,
+    mut log: job_log::Model,
+    mut from: i64,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    async_stream::stream! {
+        let interval = state.settings().executor.log_tail_interval();
+        let deadline =
+            tokio::time::Instant::now() + state.settings().executor.log_tail_max_duration();
+        let mut failures = 0;
+
+        let reason = loop {
+            // 先记下回调状态再拉取：回调到达之后的这一次拉取即为最后一页
+            let finished = log.handle_code != 0;
+            let fetched = executor_log::fetch(&state, &log, from)
+                .await
+                .unwrap_or_else(|err| Err(err.to_string()));
+            match fetched {
+                Ok(content) => {
+                    failures = 0;
+                    let (to_line_num, end) = (content.to_line_num, content.end);
+                    if !content.log_content.is_empty() {
+                        yield Ok(tail_event("log", &LogContentDto::from(content)));
+                    }
+                    from = from.max(to_line_num + 1);
+                    if end {
+                        break "isEnd";
+                    }
+                    if finished {
+                        break "callback";
+                    }
+                }
+                Err(message) => {
+                    failures += 1;
+                    if finished || log.trigger_code != 200 || failures >= TAIL_MAX_FAILURES {
+                        let fallback = fallback_content(&state, &log, from, message).await;
+                        yield Ok(tail_event("log", &LogContentDto::from(fallback)));
+                        break "unavailable";
+                    }
+                    yield Ok(tail_event("error", &json!({ "message": message })));
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                break "timeout";
+            }
+            tokio::time::sleep(interval).await;
+            match job_log::Entity::find_by_id(log.id).one(state.db()).await {
+                Ok(Some(latest)) => log = latest,
+                Ok(None) => break "deleted",
+                Err(err) => {
+                    yield Ok(tail_event("error", &json!({ "message": err.to_string() })));
+                }
+            }
+        };
+
+        yield Ok(tail_event(
+            "end",
+            &json!({
+                "reason": reason,
+                "handleCode": log.handle_code,
+                "handleMsg": log.handle_msg,
+            }),
+        ));
     }
+}
 
-    Ok(build_summary(
-        from,
-        &summary_content,
-        Some("执行器未返回日志内容".into()),
-    ))
+fn tail_event(name: &str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|_| Event::default().event(name))
 }
 
 fn build_summary(from: i64, summary: &str, reason: Option<String>) -> LogContent {
@@ -473,14 +1014,6 @@ struct LogCatParams {
     from_line_num: Option<i64>,
 }
 
-#[derive(Debug)]
-struct LogContent {
-    from_line_num: i64,
-    to_line_num: i64,
-    end: bool,
-    log_content: String,
-}
-
 #[derive(Debug, Serialize)]
 struct LogContentDto {
     from_line_num: i64,
@@ -523,18 +1056,21 @@ impl From<LogContent> for LegacyLogContentDto {
 async fn kill(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i64>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let message = kill_impl(&state, &user, id).await?;
+    let message = kill_impl(&state, &user, &client, id).await?;
     Ok(Json(json!({ "message": message })))
 }
 
-async fn kill_impl(state: &AppState, user: &AuthUser, id: i64) -> AppResult<String> {
-    user.require_admin()?;
-    let mut model = job_log::Entity::find_by_id(id)
-        .one(state.db())
-        .await?
-        .ok_or_else(|| AppError::NotFound("日志不存在".into()))?;
+async fn kill_impl(
+    state: &AppState,
+    user: &AuthUser,
+    client: &ClientInfo,
+    id: i64,
+) -> AppResult<String> {
+    let mut model = find_log(state, user, id, Permission::Operate).await?;
+    let before = model.clone();
 
     if model.handle_code == 200 {
         return Err(AppError::BadRequest("任务已完成，无需终止".into()));
@@ -548,13 +1084,188 @@ async fn kill_impl(state: &AppState, user: &AuthUser, id: i64) -> AppResult<Stri
         None => note,
     });
 
+    let txn = state.db().begin().await?;
     let active: job_log::ActiveModel = model.into();
-    active.update(state.db()).await?;
+    let updated = active.reset_all().update(&txn).await?;
+    audit::record(
+        &txn,
+        user,
+        client,
+        AuditEvent::new(AuditAction::Kill, AuditTarget::JobLog, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
 
     Ok("已标记为终止".to_string())
 }
 
-#[derive(Debug, Deserialize)]
+/// 单次批量重跑的最大日志数。
+const BULK_RERUN_LIMIT: u64 = 200;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RerunResultDto {
+    log_id: i64,
+    /// 新调度日志，调度前失败（如任务已删除）时为空
+    rerun_log_id: Option<i64>,
+    trigger_code: Option<i32>,
+    executor_address: Option<String>,
+    message: String,
+}
+
+async fn rerun(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i64>,
+) -> AppResult<Json<RerunResultDto>> {
+    let log = find_log(&state, &user, id, Permission::Operate).await?;
+    if RunStatus::of(&log) == RunStatus::Running {
+        return Err(AppError::BadRequest("任务仍在运行，无法重跑".into()));
+    }
+    let result = rerun_log(&state, &user, &log).await?;
+    audit::record_after_dispatch(
+        &state,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Rerun, AuditTarget::JobLog, id).after(&result),
+    )
+    .await;
+    Ok(Json(result))
+}
+
+/// 使用原调度日志的执行参数、分片参数与 Handler 重新调度，优先发往原执行器。
+async fn rerun_log(
+    state: &AppState,
+    user: &AuthUser,
+    log: &job_log::Model,
+) -> AppResult<RerunResultDto> {
+    let job = job_info::Entity::find_by_id(log.job_id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("任务不存在".into()))?;
+    let handler = log
+        .executor_handler
+        .clone()
+        .or_else(|| job.executor_handler.clone())
+        .unwrap_or_default();
+    let dispatched = trigger::dispatch(
+        state,
+        Dispatch {
+            job: &job,
+            handler,
+            executor_param: log.executor_param.clone(),
+            sharding: log
+                .executor_sharding_param
+                .as_deref()
+                .and_then(Sharding::parse),
+            address_list: None,
+            preferred_address: log.executor_address.as_deref(),
+            rerun_of: Some(log.id),
+            trigger_lines: vec![format!(
+                "重跑调度日志 #{}，触发人: {}",
+                log.id, user.username
+            )],
+        },
+    )
+    .await?;
+    let message = if dispatched.code == 200 {
+        "重跑已触发".to_string()
+    } else {
+        match dispatched.msg.filter(|msg| !msg.trim().is_empty()) {
+            Some(msg) => format!("重跑触发失败: {msg}"),
+            None => "重跑触发失败".to_string(),
+        }
+    };
+    Ok(RerunResultDto {
+        log_id: log.id,
+        rerun_log_id: Some(dispatched.log_id),
+        trigger_code: Some(dispatched.code),
+        executor_address: dispatched.address,
+        message,
+    })
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RerunFailedRequest {
+    job_group: i32,
+    job_id: Option<i32>,
+    /// 触发时间范围，格式同列表的 `filter_time`
+    filter_time: String,
+}
+
+/// 重跑时间范围内所有失败且尚未重跑过的日志，整个批次记录为一条审计。
+async fn rerun_failed(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<RerunFailedRequest>,
+) -> AppResult<Json<Vec<RerunResultDto>>> {
+    user.require(Permission::Operate, Some(payload.job_group))?;
+    ensure_job_group(&state, payload.job_group).await?;
+    if parse_time_range(&payload.filter_time).is_none() {
+        return Err(AppError::BadRequest(
+            "filter_time 格式应为 `yyyy-MM-dd HH:mm:ss - yyyy-MM-dd HH:mm:ss`".into(),
+        ));
+    }
+    let filter = LogFilter {
+        job_group: payload.job_group,
+        job_id: payload.job_id,
+        log_status: Some(2),
+        filter_time: Some(payload.filter_time.clone()),
+        search: None,
+        executor_address: None,
+        executor_handler: None,
+        trigger_code: None,
+        min_duration_millis: None,
+    };
+    let (query, _) = filter.query()?;
+    let query = query.filter(Expr::cust(
+        "NOT EXISTS (SELECT 1 FROM `xxl_job_log` r WHERE r.`rerun_of` = `xxl_job_log`.`id`)",
+    ));
+
+    let count = query.clone().count(state.db()).await?;
+    if count > BULK_RERUN_LIMIT {
+        return Err(AppError::BadRequest(format!(
+            "匹配的失败日志有 {count} 条，超过单次重跑上限 {BULK_RERUN_LIMIT} 条，请缩小时间范围"
+        )));
+    }
+    let logs = query
+        .order_by_asc(job_log::Column::TriggerTime)
+        .order_by_asc(job_log::Column::Id)
+        .all(state.db())
+        .await?;
+
+    let mut results = Vec::with_capacity(logs.len());
+    for log in &logs {
+        let result = match rerun_log(&state, &user, log).await {
+            Ok(result) => result,
+            Err(err) => RerunResultDto {
+                log_id: log.id,
+                rerun_log_id: None,
+                trigger_code: None,
+                executor_address: None,
+                message: err.to_string(),
+            },
+        };
+        results.push(result);
+    }
+
+    audit::record_after_dispatch(
+        &state,
+        &user,
+        &client,
+        AuditEvent::without_id(AuditAction::Rerun, AuditTarget::JobLog)
+            .after(&json!({ "request": payload, "results": results })),
+    )
+    .await;
+    Ok(Json(results))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ClearRequest {
     job_group: i32,
@@ -568,14 +1279,20 @@ struct ClearRequest {
 async fn clear(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Json(payload): Json<ClearRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let deleted = clear_impl(&state, &user, payload).await?;
+    let deleted = clear_impl(&state, &user, &client, payload).await?;
     Ok(Json(json!({ "deleted": deleted })))
 }
 
-async fn clear_impl(state: &AppState, user: &AuthUser, payload: ClearRequest) -> AppResult<u64> {
-    user.require_admin()?;
+async fn clear_impl(
+    state: &AppState,
+    user: &AuthUser,
+    client: &ClientInfo,
+    payload: ClearRequest,
+) -> AppResult<u64> {
+    user.require(Permission::Develop, Some(payload.job_group))?;
     ensure_job_group(state, payload.job_group).await?;
 
     let job_group = payload.job_group;
@@ -595,11 +1312,22 @@ async fn clear_impl(state: &AppState, user: &AuthUser, payload: ClearRequest) ->
 
     let keep_recent = payload.clear_before_rows.filter(|value| *value > 0);
 
-    delete_logs(state, job_group, job_id, before_time, keep_recent).await
+    let txn = state.db().begin().await?;
+    let deleted = delete_logs(&txn, job_group, job_id, before_time, keep_recent).await?;
+    audit::record(
+        &txn,
+        user,
+        client,
+        AuditEvent::without_id(AuditAction::Clear, AuditTarget::JobLog)
+            .after(&json!({ "request": payload, "deleted": deleted })),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(deleted)
 }
 
-async fn delete_logs(
-    state: &AppState,
+async fn delete_logs<C: ConnectionTrait>(
+    conn: &C,
     job_group: i32,
     job_id: Option<i32>,
     before_time: Option<NaiveDateTime>,
@@ -620,7 +1348,7 @@ async fn delete_logs(
     if keep_recent.unwrap_or(0) <= 0 {
         let result = job_log::Entity::delete_many()
             .filter(filter_condition)
-            .exec(state.db())
+            .exec(conn)
             .await?;
         return Ok(result.rows_affected);
     }
@@ -640,13 +1368,13 @@ async fn delete_logs(
         .order_by_desc(job_log::Column::TriggerTime)
         .limit(limit as u64)
         .into_tuple::<i64>()
-        .all(state.db())
+        .all(conn)
         .await?;
 
     if keep_ids.is_empty() {
         let result = job_log::Entity::delete_many()
             .filter(filter_condition)
-            .exec(state.db())
+            .exec(conn)
             .await?;
         return Ok(result.rows_affected);
     }
@@ -662,14 +1390,14 @@ async fn delete_logs(
 
         candidates = candidates.filter(job_log::Column::Id.is_not_in(keep_ids.clone()));
 
-        let ids = candidates.into_tuple::<i64>().all(state.db()).await?;
+        let ids = candidates.into_tuple::<i64>().all(conn).await?;
         if ids.is_empty() {
             break;
         }
 
         let result = job_log::Entity::delete_many()
             .filter(job_log::Column::Id.is_in(ids.clone()))
-            .exec(state.db())
+            .exec(conn)
             .await?;
         total_deleted += result.rows_affected;
 
@@ -727,19 +1455,19 @@ fn convert_legacy_clear_request(params: LegacyClearRequest) -> Result<ClearReque
 
 async fn legacy_page_list(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Form(params): Form<LegacyPageParams>,
 ) -> AppResult<Json<LegacyPageResult<JobLogDto>>> {
-    let result = page_list_impl(&state, params.into()).await?;
+    let result = page_list_impl(&state, &user, params.into()).await?;
     Ok(Json(result.into()))
 }
 
 async fn legacy_log_detail(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Query(params): Query<LegacyLogIdParam>,
 ) -> Json<LegacyReturn<JobLogDto>> {
-    match detail_impl(&state, params.id).await {
+    match detail_impl(&state, &user, params.id).await {
         Ok(detail) => Json(LegacyReturn::success_with(detail)),
         Err(err) => Json(LegacyReturn::failure(err.to_string())),
     }
@@ -747,10 +1475,10 @@ async fn legacy_log_detail(
 
 async fn legacy_log_detail_post(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Form(params): Form<LegacyLogIdParam>,
 ) -> Json<LegacyReturn<JobLogDto>> {
-    match detail_impl(&state, params.id).await {
+    match detail_impl(&state, &user, params.id).await {
         Ok(detail) => Json(LegacyReturn::success_with(detail)),
         Err(err) => Json(LegacyReturn::failure(err.to_string())),
     }
@@ -758,11 +1486,11 @@ async fn legacy_log_detail_post(
 
 async fn legacy_log_detail_cat(
     State(state): State<AppState>,
-    _user: AuthUser,
+    user: AuthUser,
     Form(params): Form<LegacyLogDetailCatParams>,
 ) -> Json<LegacyReturn<LegacyLogContentDto>> {
     let from = params.from_line_num.unwrap_or(1).max(1);
-    match log_content_impl(&state, params.log_id, from).await {
+    match log_content_impl(&state, &user, params.log_id, from).await {
         Ok(content) => Json(LegacyReturn::success_with(LegacyLogContentDto::from(
             content,
         ))),
@@ -773,9 +1501,10 @@ async fn legacy_log_detail_cat(
 async fn legacy_log_kill(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Form(params): Form<LegacyKillParams>,
 ) -> Json<LegacyReturn<String>> {
-    match kill_impl(&state, &user, params.id).await {
+    match kill_impl(&state, &user, &client, params.id).await {
         Ok(message) => Json(LegacyReturn::success_with(message)),
         Err(err) => Json(LegacyReturn::failure(err.to_string())),
     }
@@ -784,6 +1513,7 @@ async fn legacy_log_kill(
 async fn legacy_clear_log(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Form(params): Form<LegacyClearRequest>,
 ) -> Json<LegacyReturn<String>> {
     let payload = match convert_legacy_clear_request(params) {
@@ -791,7 +1521,7 @@ async fn legacy_clear_log(
         Err(err) => return Json(LegacyReturn::failure(err)),
     };
 
-    match clear_impl(&state, &user, payload).await {
+    match clear_impl(&state, &user, &client, payload).await {
         Ok(_) => Json(LegacyReturn::<String>::success(None)),
         Err(err) => Json(LegacyReturn::failure(err.to_string())),
     }
@@ -817,3 +1547,58 @@ fn parse_time_range(range: &str) -> Option<(NaiveDateTime, NaiveDateTime)> {
     let end = NaiveDateTime::parse_from_str(parts[1], "%Y-%m-%d %H:%M:%S").ok()?;
     Some((start, end))
 }
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::Set;
+
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn spreadsheet_safe_neutralizes_formulas() {
+        for raw in ["=1+1", "+SUM(A1)", "-2+3", "@cmd", "\tx", "\rx"] {
+            assert_eq!(spreadsheet_safe(Some(raw)), format!("'{raw}"));
+        }
+        assert_eq!(spreadsheet_safe(Some("触发成功")), "触发成功");
+        assert_eq!(spreadsheet_safe(Some("a=b")), "a=b");
+        assert_eq!(spreadsheet_safe(None), "");
+    }
+
+    #[tokio::test]
+    async fn kill_marks_running_log_failed() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        test_support::insert_job(&state, 1, 1).await;
+        let log = job_log::Entity::insert(job_log::ActiveModel {
+            job_group: Set(1),
+            job_id: Set(1),
+            executor_fail_retry_count: Set(0),
+            trigger_code: Set(200),
+            handle_code: Set(0),
+            alarm_status: Set(0),
+            ..Default::default()
+        })
+        .exec_with_returning(state.db())
+        .await
+        .unwrap();
+
+        kill_impl(
+            &state,
+            &test_support::admin(),
+            &ClientInfo::default(),
+            log.id,
+        )
+        .await
+        .unwrap();
+
+        let stored = job_log::Entity::find_by_id(log.id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.handle_code, 500);
+        assert!(stored.handle_time.is_some());
+        assert!(stored.handle_msg.unwrap().contains("强制终止"));
+    }
+}
@@ -1,21 +1,45 @@
 use axum::{
     extract::{Path, State},
-    routing::{get, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::auth::AuthUser;
-use crate::entities::job_user;
+use super::auth::{list_sessions, SessionDto};
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, GroupRole, Permission};
+use crate::authenticator::AuthSource;
+use crate::entities::{job_group, job_user, job_user_group_role};
 use crate::error::{AppError, AppResult};
+use crate::login_guard::{self, LockScope};
+use crate::oidc;
+use crate::password::{check_strength, hash_password};
+use crate::session::{self, ClientInfo};
 use crate::state::AppState;
+use crate::totp;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(list_users).post(create_user))
         .route("/:id", put(update_user).delete(remove_user))
+        .route(
+            "/:id/sessions",
+            get(list_user_sessions).delete(revoke_user_sessions),
+        )
+        .route("/:id/roles", get(list_user_roles).put(replace_user_roles))
+        .route("/:id/roles/:job_group", delete(revoke_user_role))
+        .route("/:id/totp", delete(reset_user_totp))
+        .route("/:id/unlock", post(unlock_user))
+        .route(
+            "/:id/oidc",
+            put(link_oidc_identity).delete(unlink_oidc_identity),
+        )
 }
 
 #[derive(Debug, Serialize)]
@@ -25,6 +49,9 @@ struct JobUserDto {
     username: String,
     role: i8,
     permission: Option<String>,
+    auth_source: String,
+    oidc_issuer: Option<String>,
+    oidc_subject: Option<String>,
 }
 
 impl From<job_user::Model> for JobUserDto {
@@ -34,6 +61,9 @@ impl From<job_user::Model> for JobUserDto {
             username: value.username,
             role: value.role,
             permission: value.permission,
+            auth_source: value.auth_source,
+            oidc_issuer: value.oidc_issuer,
+            oidc_subject: value.oidc_subject,
         }
     }
 }
@@ -42,7 +72,7 @@ async fn list_users(
     State(state): State<AppState>,
     user: AuthUser,
 ) -> AppResult<Json<Vec<JobUserDto>>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     let users = job_user::Entity::find()
         .order_by_asc(job_user::Column::Id)
         .all(state.db())
@@ -67,9 +97,10 @@ struct SaveUserRequest {
 async fn create_user(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Json(payload): Json<SaveUserRequest>,
 ) -> AppResult<Json<JobUserDto>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     if payload.password.is_none() {
         return Err(AppError::BadRequest("创建用户必须提供密码".into()));
     }
@@ -87,6 +118,13 @@ async fn create_user(
         role,
         permission,
     } = payload;
+    if let Some(password) = password.as_deref() {
+        check_strength(
+            &state.settings().security.password_policy,
+            &username,
+            password.trim(),
+        )?;
+    }
 
     if job_user::Entity::find()
         .filter(job_user::Column::Username.eq(username.trim()))
@@ -97,29 +135,40 @@ async fn create_user(
         return Err(AppError::Conflict("用户名已存在".into()));
     }
 
-    let password = hash_password(password.unwrap().trim());
+    let password = hash_password(password.unwrap().trim())?;
     let active = job_user::ActiveModel {
         username: Set(username.trim().to_string()),
         password: Set(password),
         token: Set(None),
         role: Set(role),
         permission: Set(permission.clone()),
+        auth_source: Set(AuthSource::Local.as_str().to_string()),
         ..Default::default()
     };
 
+    let txn = state.db().begin().await?;
     let inserted = job_user::Entity::insert(active)
-        .exec_with_returning(state.db())
+        .exec_with_returning(&txn)
         .await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Create, AuditTarget::JobUser, inserted.id).after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
     Ok(Json(JobUserDto::from(inserted)))
 }
 
 async fn update_user(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
     Json(payload): Json<SaveUserRequest>,
 ) -> AppResult<Json<JobUserDto>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     payload
         .validate()
         .map_err(|err| AppError::BadRequest(err.to_string()))?;
@@ -134,56 +183,480 @@ async fn update_user(
         role,
         permission,
     } = payload;
+    if let Some(password) = password.as_deref() {
+        check_strength(
+            &state.settings().security.password_policy,
+            &username,
+            password.trim(),
+        )?;
+    }
 
     let mut model = job_user::Entity::find_by_id(id)
         .one(state.db())
         .await?
         .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
 
-    if username != model.username {
-        if job_user::Entity::find()
+    if username != model.username
+        && job_user::Entity::find()
             .filter(job_user::Column::Username.eq(username.trim()))
             .one(state.db())
             .await?
             .is_some()
-        {
-            return Err(AppError::Conflict("用户名已存在".into()));
-        }
+    {
+        return Err(AppError::Conflict("用户名已存在".into()));
     }
 
+    let before = model.clone();
     model.username = username.trim().to_string();
     model.role = role;
     model.permission = permission.clone();
+    let password_changed = password.is_some();
     if let Some(password) = password {
-        model.password = hash_password(password.trim());
-        model.token = None;
+        model.password = hash_password(password.trim())?;
     }
 
+    let txn = state.db().begin().await?;
     let active: job_user::ActiveModel = model.into();
-    let updated = active.update(state.db()).await?;
+    let updated = active.reset_all().update(&txn).await?;
+    if password_changed {
+        session::revoke_all(&txn, updated.id, None).await?;
+    }
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobUser, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
     Ok(Json(JobUserDto::from(updated)))
 }
 
 async fn remove_user(
     State(state): State<AppState>,
     user: AuthUser,
+    client: ClientInfo,
     Path(id): Path<i32>,
 ) -> AppResult<Json<serde_json::Value>> {
-    user.require_admin()?;
+    user.require(Permission::Manage, None)?;
     if id == user.id {
         return Err(AppError::BadRequest("不能删除当前登录用户".into()));
     }
 
-    let result = job_user::Entity::delete_by_id(id).exec(state.db()).await?;
+    let model = job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
+    let roles = load_user_roles(state.db(), id).await?;
+    let txn = state.db().begin().await?;
+    job_user::Entity::delete_by_id(id).exec(&txn).await?;
+    session::revoke_all(&txn, id, None).await?;
+    job_user_group_role::Entity::delete_many()
+        .filter(job_user_group_role::Column::UserId.eq(id))
+        .exec(&txn)
+        .await?;
+    totp::disable(&txn, id).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Delete, AuditTarget::JobUser, id)
+            .before(&serde_json::json!({ "user": model, "roles": roles })),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "message": "用户已删除" })))
+}
+
+async fn list_user_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<SessionDto>>> {
+    user.require(Permission::Manage, None)?;
+    let current = user.session_id.filter(|_| id == user.id);
+    let sessions = list_sessions(&state, id, current).await?;
+    Ok(Json(sessions))
+}
+
+async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    let keep = user.session_id.filter(|_| id == user.id);
+    let txn = state.db().begin().await?;
+    let revoked = session::revoke_all(&txn, id, keep).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Revoke, AuditTarget::UserSession, id)
+            .after(&serde_json::json!({ "revoked": revoked })),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
+/// 解除账号因连续登录失败触发的临时锁定。
+async fn unlock_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    let target = job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
+    let txn = state.db().begin().await?;
+    let cleared =
+        login_guard::unlock(&txn, LockScope::User, &target.username, &user.username).await?;
+    if cleared {
+        audit::record(
+            &txn,
+            &user,
+            &client,
+            AuditEvent::new(AuditAction::Unlock, AuditTarget::JobUser, id),
+        )
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({
+        "message": if cleared { "账号已解锁" } else { "账号未被锁定" }
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkOidcRequest {
+    /// 默认使用身份提供方发现文档中的 `issuer`
+    issuer: Option<String>,
+    subject: String,
+}
+
+/// 为已有账号关联单点登录身份（ID Token 的 `iss` + `sub`），之后该身份登录时使用此账号。
+async fn link_oidc_identity(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+    Json(payload): Json<LinkOidcRequest>,
+) -> AppResult<Json<JobUserDto>> {
+    user.require(Permission::Manage, None)?;
+    let subject = payload.subject.trim();
+    if subject.is_empty() {
+        return Err(AppError::BadRequest("单点登录用户标识不能为空".into()));
+    }
+    let issuer = match payload.issuer.as_deref().map(str::trim) {
+        Some(issuer) if !issuer.is_empty() => issuer.to_string(),
+        _ => {
+            let settings = &state.settings().oidc;
+            if !settings.enabled {
+                return Err(AppError::BadRequest("未启用 OIDC 单点登录".into()));
+            }
+            oidc::discover(state.http_client(), settings.issuer_url()?)
+                .await
+                .map_err(AppError::internal)?
+                .issuer
+        }
+    };
+
+    let model = job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
+    if let Some(other) = job_user::Entity::find()
+        .filter(job_user::Column::OidcIssuer.eq(issuer.as_str()))
+        .filter(job_user::Column::OidcSubject.eq(subject))
+        .filter(job_user::Column::Id.ne(id))
+        .one(state.db())
+        .await?
+    {
+        return Err(AppError::Conflict(format!(
+            "该单点登录身份已关联账号 {}",
+            other.username
+        )));
+    }
+
+    let before = model.clone();
+    let mut active: job_user::ActiveModel = model.into();
+    active.oidc_issuer = Set(Some(issuer));
+    active.oidc_subject = Set(Some(subject.to_string()));
+    let txn = state.db().begin().await?;
+    let updated = active.update(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobUser, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(JobUserDto::from(updated)))
+}
+
+/// 解除账号关联的单点登录身份。
+async fn unlink_oidc_identity(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+) -> AppResult<Json<JobUserDto>> {
+    user.require(Permission::Manage, None)?;
+    let model = job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在".into()))?;
+    if model.oidc_subject.is_none() {
+        return Err(AppError::NotFound("该用户未关联单点登录身份".into()));
+    }
+
+    let before = model.clone();
+    let mut active: job_user::ActiveModel = model.into();
+    active.oidc_issuer = Set(None);
+    active.oidc_subject = Set(None);
+    let txn = state.db().begin().await?;
+    let updated = active.update(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobUser, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(JobUserDto::from(updated)))
+}
+
+/// 用户丢失身份验证器与恢复码时，由管理员重置其两步验证。
+async fn reset_user_totp(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    ensure_user_exists(&state, id).await?;
+    let txn = state.db().begin().await?;
+    if !totp::disable(&txn, id).await? {
+        return Err(AppError::NotFound("该用户未绑定两步验证".into()));
+    }
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Reset, AuditTarget::JobUser, id),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "message": "两步验证已重置" })))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupRoleDto {
+    job_group: i32,
+    role: GroupRole,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplaceRolesRequest {
+    roles: Vec<GroupRoleDto>,
+}
+
+async fn list_user_roles(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<GroupRoleDto>>> {
+    user.require(Permission::Manage, None)?;
+    ensure_user_exists(&state, id).await?;
+    let roles = load_user_roles(state.db(), id).await?;
+    Ok(Json(roles))
+}
+
+/// 以请求中的列表整体替换用户在各执行器上的角色。
+async fn replace_user_roles(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+    Json(payload): Json<ReplaceRolesRequest>,
+) -> AppResult<Json<Vec<GroupRoleDto>>> {
+    user.require(Permission::Manage, None)?;
+    ensure_user_exists(&state, id).await?;
+
+    let mut group_ids: Vec<i32> = payload.roles.iter().map(|item| item.job_group).collect();
+    group_ids.sort_unstable();
+    group_ids.dedup();
+    if group_ids.len() != payload.roles.len() {
+        return Err(AppError::BadRequest("同一执行器只能授予一个角色".into()));
+    }
+    let existing = job_group::Entity::find()
+        .filter(job_group::Column::Id.is_in(group_ids.clone()))
+        .all(state.db())
+        .await?;
+    if let Some(missing) = group_ids
+        .iter()
+        .find(|id| !existing.iter().any(|group| group.id == **id))
+    {
+        return Err(AppError::BadRequest(format!("执行器 {missing} 不存在")));
+    }
+
+    let before = load_user_roles(state.db(), id).await?;
+    let now = Local::now().naive_local();
+    let txn = state.db().begin().await?;
+    job_user_group_role::Entity::delete_many()
+        .filter(job_user_group_role::Column::UserId.eq(id))
+        .exec(&txn)
+        .await?;
+    if !payload.roles.is_empty() {
+        let models = payload
+            .roles
+            .iter()
+            .map(|item| job_user_group_role::ActiveModel {
+                user_id: Set(id),
+                job_group: Set(item.job_group),
+                role: Set(item.role.as_str().to_string()),
+                update_time: Set(Some(now)),
+                ..Default::default()
+            });
+        job_user_group_role::Entity::insert_many(models)
+            .exec(&txn)
+            .await?;
+    }
+
+    let roles = load_user_roles(&txn, id).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobUser, id)
+            .before(&serde_json::json!({ "roles": before }))
+            .after(&serde_json::json!({ "roles": roles })),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(roles))
+}
+
+async fn revoke_user_role(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path((id, job_group)): Path<(i32, i32)>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    let before = load_user_roles(state.db(), id).await?;
+    let txn = state.db().begin().await?;
+    let result = job_user_group_role::Entity::delete_many()
+        .filter(job_user_group_role::Column::UserId.eq(id))
+        .filter(job_user_group_role::Column::JobGroup.eq(job_group))
+        .exec(&txn)
+        .await?;
     if result.rows_affected == 0 {
+        return Err(AppError::NotFound("角色授权不存在".into()));
+    }
+    let after = load_user_roles(&txn, id).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::JobUser, id)
+            .before(&serde_json::json!({ "roles": before }))
+            .after(&serde_json::json!({ "roles": after })),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "message": "角色授权已撤销" })))
+}
+
+async fn load_user_roles<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+) -> AppResult<Vec<GroupRoleDto>> {
+    let roles = job_user_group_role::Entity::find()
+        .filter(job_user_group_role::Column::UserId.eq(user_id))
+        .order_by_asc(job_user_group_role::Column::JobGroup)
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter_map(|grant| {
+            GroupRole::parse(&grant.role).ok().map(|role| GroupRoleDto {
+                job_group: grant.job_group,
+                role,
+            })
+        })
+        .collect();
+    Ok(roles)
+}
+
+async fn ensure_user_exists(state: &AppState, id: i32) -> AppResult<()> {
+    if job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .is_none()
+    {
         return Err(AppError::NotFound("用户不存在".into()));
     }
-    Ok(Json(serde_json::json!({ "message": "用户已删除" })))
+    Ok(())
 }
 
-fn hash_password(password: &str) -> String {
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    hex::encode(hasher.finalize())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn update_persists_user() {
+        let state = test_support::state().await;
+        let created = job_user::Entity::insert(job_user::ActiveModel {
+            username: Set("alice".into()),
+            password: Set("hash".into()),
+            role: Set(0),
+            auth_source: Set("local".into()),
+            ..Default::default()
+        })
+        .exec_with_returning(state.db())
+        .await
+        .unwrap();
+
+        let Json(dto) = update_user(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(created.id),
+            Json(SaveUserRequest {
+                username: "alice.ops".into(),
+                password: None,
+                role: 1,
+                permission: Some("1,2".into()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stored = job_user::Entity::find_by_id(created.id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.username, "alice.ops");
+        assert_eq!(stored.role, 1);
+        assert_eq!(dto.username, stored.username);
+        assert_eq!(stored.permission.as_deref(), Some("1,2"));
+        assert_eq!(stored.password, "hash");
+    }
 }
@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::Local;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, Permission};
+use crate::entities::login_lockout;
+use crate::error::{AppError, AppResult};
+use crate::login_guard::{self, LockScope};
+use crate::session::ClientInfo;
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_lockouts))
+        .route("/ips/:ip", delete(unlock_ip))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LockoutDto {
+    id: i64,
+    scope: String,
+    subject: String,
+    client_ip: Option<String>,
+    failure_count: i32,
+    locked_at: chrono::NaiveDateTime,
+    locked_until: chrono::NaiveDateTime,
+    unlocked_at: Option<chrono::NaiveDateTime>,
+    unlocked_by: Option<String>,
+    active: bool,
+}
+
+impl LockoutDto {
+    fn new(value: login_lockout::Model, now: chrono::NaiveDateTime) -> Self {
+        Self {
+            active: value.unlocked_at.is_none() && value.locked_until > now,
+            id: value.id,
+            scope: value.scope,
+            subject: value.subject,
+            client_ip: value.client_ip,
+            failure_count: value.failure_count,
+            locked_at: value.locked_at,
+            locked_until: value.locked_until,
+            unlocked_at: value.unlocked_at,
+            unlocked_by: value.unlocked_by,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockoutQuery {
+    /// 仅返回仍在锁定期内的记录
+    #[serde(default)]
+    active: bool,
+    subject: Option<String>,
+    limit: Option<u64>,
+}
+
+/// 锁定事件审计记录，按锁定时间倒序。
+async fn list_lockouts(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(query): Query<LockoutQuery>,
+) -> AppResult<Json<Vec<LockoutDto>>> {
+    user.require(Permission::Manage, None)?;
+    let now = Local::now().naive_local();
+    let mut select = login_lockout::Entity::find().order_by_desc(login_lockout::Column::LockedAt);
+    if query.active {
+        select = select
+            .filter(login_lockout::Column::LockedUntil.gt(now))
+            .filter(login_lockout::Column::UnlockedAt.is_null());
+    }
+    if let Some(subject) = query
+        .subject
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        select = select.filter(login_lockout::Column::Subject.contains(subject));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let lockouts = select
+        .limit(limit)
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(|item| LockoutDto::new(item, now))
+        .collect();
+    Ok(Json(lockouts))
+}
+
+async fn unlock_ip(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(ip): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    let txn = state.db().begin().await?;
+    if !login_guard::unlock(&txn, LockScope::Ip, &ip, &user.username).await? {
+        return Err(AppError::NotFound("该 IP 没有登录失败记录".into()));
+    }
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Unlock, AuditTarget::LoginLockout, &ip),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "message": "已解除 IP 锁定" })))
+}
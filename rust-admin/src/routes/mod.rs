@@ -1,12 +1,18 @@
 pub mod admin;
+pub mod audit;
 pub mod auth;
 pub mod dashboard;
 pub mod glue;
+pub mod job_config;
 pub mod job_groups;
 pub mod job_info;
 pub mod job_logs;
 pub mod job_user;
+pub mod login_lockouts;
+pub mod oidc;
 pub mod openapi;
+pub mod service_accounts;
+pub mod totp;
 
 use axum::{response::Redirect, routing::get, Router};
 use tower_http::cors::CorsLayer;
@@ -32,12 +38,18 @@ fn build_router() -> Router<AppState> {
     Router::new()
         .route("/", get(root_redirect))
         .nest("/api/auth", auth::router())
+        .nest("/api/auth/oidc", oidc::router())
+        .nest("/api/auth/totp", totp::router())
         .nest("/api/dashboard", dashboard::router())
         .nest("/api/job-groups", job_groups::router())
         .nest("/api/job-info", job_info::router())
+        .nest("/api/job-config", job_config::router())
         .nest("/api/job-logs", job_logs::router())
         .nest("/api/job-users", job_user::router())
         .nest("/api/job-code", glue::router())
+        .nest("/api/service-accounts", service_accounts::router())
+        .nest("/api/login-lockouts", login_lockouts::router())
+        .nest("/api/audit", audit::router())
         .nest("/api", openapi::router())
         .nest("/joblog", job_logs::compat_router())
         .nest("/admin", admin::router())
@@ -0,0 +1,241 @@
+use axum::{
+    extract::{Query, State},
+    response::Redirect,
+    routing::get,
+    Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::authenticator::{self, ExternalAccount};
+use crate::entities::job_user;
+use crate::error::{AppError, AppResult};
+use crate::login_guard;
+use crate::oidc;
+use crate::session::{self, ClientInfo, IssuedSession};
+use crate::state::AppState;
+use crate::totp;
+
+/// 单点登录创建的账号不设置可用密码，只能通过身份提供方登录。
+const SSO_PASSWORD_PLACEHOLDER: &str = "!oidc";
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/login", get(login))
+        .route("/callback", get(callback))
+}
+
+/// 跳转到身份提供方的授权页面。
+async fn login(State(state): State<AppState>) -> AppResult<Redirect> {
+    let settings = &state.settings().oidc;
+    if !settings.enabled {
+        return Err(AppError::NotFound("未启用 OIDC 单点登录".into()));
+    }
+    let metadata = oidc::discover(state.http_client(), settings.issuer_url()?)
+        .await
+        .map_err(AppError::internal)?;
+    let request = state
+        .oidc_logins()
+        .begin(&metadata, settings)
+        .map_err(AppError::internal)?;
+    Ok(Redirect::to(request.url.as_str()))
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// 单点登录回调的处理结果。
+#[derive(Debug)]
+enum OidcOutcome {
+    Session(IssuedSession),
+    /// 账号已启用两步验证，交给登录页提交验证码完成登录
+    TotpRequired {
+        challenge: String,
+        expires_in: u64,
+    },
+}
+
+/// 身份提供方认证通过后还需要完成的步骤。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NextStep {
+    Session,
+    TotpChallenge,
+    /// 策略要求两步验证但账号尚未绑定，单点登录无法在回调中完成绑定
+    TotpEnrollmentRequired,
+}
+
+fn next_step(totp_enabled: bool, totp_required: bool) -> NextStep {
+    if totp_enabled {
+        NextStep::TotpChallenge
+    } else if totp_required {
+        NextStep::TotpEnrollmentRequired
+    } else {
+        NextStep::Session
+    }
+}
+
+/// 身份提供方回调：校验 ID Token、按需创建本地账号，签发会话并写入管理台 Cookie。
+///
+/// 已启用两步验证的账号不签发会话，而是把挑战令牌交给登录页，与密码登录共用
+/// `/api/auth/login/totp` 完成第二步。挑战与错误信息都通过 URL fragment 传递，
+/// 不会出现在服务端访问日志中。
+async fn callback(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Query(query): Query<CallbackQuery>,
+) -> (CookieJar, Redirect) {
+    match complete_login(&state, &client, query).await {
+        Ok(OidcOutcome::Session(issued)) => (
+            session::set_cookies(jar, &state.settings().security, &issued),
+            Redirect::to("/admin/dashboard"),
+        ),
+        Ok(OidcOutcome::TotpRequired {
+            challenge,
+            expires_in,
+        }) => (
+            jar,
+            Redirect::to(&format!(
+                "/admin#totp_challenge={}&expires_in={expires_in}",
+                urlencoding(&challenge)
+            )),
+        ),
+        Err(err) => {
+            warn!(error = %err, "OIDC 登录失败");
+            let message = match err {
+                AppError::Internal(_) | AppError::DbError(_) => "单点登录失败，请稍后重试".into(),
+                other => other.to_string(),
+            };
+            (
+                jar,
+                Redirect::to(&format!("/admin#sso_error={}", urlencoding(&message))),
+            )
+        }
+    }
+}
+
+async fn complete_login(
+    state: &AppState,
+    client: &ClientInfo,
+    query: CallbackQuery,
+) -> AppResult<OidcOutcome> {
+    let settings = &state.settings().oidc;
+    if !settings.enabled {
+        return Err(AppError::NotFound("未启用 OIDC 单点登录".into()));
+    }
+    if let Some(error) = query.error {
+        return Err(AppError::Unauthorized(
+            query.error_description.unwrap_or(error),
+        ));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| AppError::BadRequest("回调缺少 code 参数".into()))?;
+    let pending = query
+        .state
+        .as_deref()
+        .and_then(|value| state.oidc_logins().complete(value))
+        .ok_or_else(|| AppError::Unauthorized("登录请求已过期，请重新登录".into()))?;
+
+    let metadata = oidc::discover(state.http_client(), settings.issuer_url()?)
+        .await
+        .map_err(AppError::internal)?;
+    let id_token = oidc::exchange_code(
+        state.http_client(),
+        &metadata,
+        settings,
+        &code,
+        &pending.pkce_verifier,
+    )
+    .await
+    .map_err(AppError::internal)?;
+    let identity = oidc::verify_id_token(
+        state.http_client(),
+        &metadata,
+        settings,
+        &id_token,
+        &pending.nonce,
+    )
+    .await
+    .map_err(|err| AppError::Unauthorized(format!("{err:#}")))?;
+
+    let access = oidc::map_access(settings, &identity);
+    let sync_access = !settings.admin_groups.is_empty() || !settings.group_mappings.is_empty();
+    let user = authenticator::provision_user(
+        state,
+        ExternalAccount::Oidc {
+            issuer: &metadata.issuer,
+            subject: &identity.subject,
+            username: &identity.username,
+        },
+        SSO_PASSWORD_PLACEHOLDER,
+        &access,
+        sync_access,
+    )
+    .await?;
+    login_guard::ensure_allowed(state, &user.username, client).await?;
+
+    let totp_enabled = totp::is_enabled(state, user.id).await?;
+    match next_step(totp_enabled, totp::is_required(state, &user)) {
+        NextStep::TotpChallenge => {
+            let (challenge, expires_in) = state.totp_challenges().begin(user.id, false);
+            Ok(OidcOutcome::TotpRequired {
+                challenge,
+                expires_in,
+            })
+        }
+        NextStep::TotpEnrollmentRequired => Err(AppError::Forbidden(
+            "管理员账号必须先启用两步验证才能使用单点登录".into(),
+        )),
+        NextStep::Session => issue_session(state, &user, client)
+            .await
+            .map(OidcOutcome::Session),
+    }
+}
+
+async fn issue_session(
+    state: &AppState,
+    user: &job_user::Model,
+    client: &ClientInfo,
+) -> AppResult<IssuedSession> {
+    login_guard::record_success(state, &user.username).await?;
+    session::issue(state, user.id, client).await
+}
+
+fn urlencoding(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::UserRole;
+    use crate::config::OidcSettings;
+
+    #[test]
+    fn admin_with_totp_gets_challenge_instead_of_session() {
+        let settings = OidcSettings {
+            enabled: true,
+            admin_groups: vec!["ops-admins".into()],
+            ..OidcSettings::default()
+        };
+        let identity = oidc::OidcIdentity {
+            subject: "sub-1".into(),
+            username: "root".into(),
+            groups: vec!["ops-admins".into()],
+        };
+        let access = oidc::map_access(&settings, &identity);
+        assert_eq!(access.role, UserRole::Admin);
+
+        assert_eq!(next_step(true, false), NextStep::TotpChallenge);
+        assert_eq!(next_step(true, true), NextStep::TotpChallenge);
+        assert_eq!(next_step(false, true), NextStep::TotpEnrollmentRequired);
+        assert_eq!(next_step(false, false), NextStep::Session);
+    }
+}
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use crate::entities::{job_log, job_registry};
+use crate::log_archive;
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
@@ -81,7 +82,7 @@ async fn save_registry(state: &AppState, payload: RegistryRequest) -> Result<(),
     if let Some(mut model) = existing {
         model.update_time = Some(now);
         let active: job_registry::ActiveModel = model.into();
-        active.update(state.db()).await.map_err(|err| {
+        active.reset_all().update(state.db()).await.map_err(|err| {
             error!("更新执行器注册信息失败: {err}");
             "保存执行器注册信息失败".to_string()
         })?;
@@ -139,6 +140,8 @@ async fn remove_registry(state: &AppState, payload: RegistryRequest) -> Result<(
 #[serde(rename_all = "camelCase")]
 struct HandleCallbackParam {
     log_id: i64,
+    // 执行器回调协议字段，当前按 log_id 定位日志，无需使用
+    #[allow(dead_code)]
     log_date_tim: i64,
     handle_code: i32,
     handle_msg: Option<String>,
@@ -188,10 +191,57 @@ async fn process_callback(state: &AppState, param: HandleCallbackParam) -> Resul
     };
 
     let active: job_log::ActiveModel = model.into();
-    active.update(state.db()).await.map_err(|err| {
+    active.reset_all().update(state.db()).await.map_err(|err| {
         error!("更新调度日志失败: {err}");
         "更新调度日志失败".to_string()
     })?;
+    log_archive::spawn_archive(state.clone(), param.log_id);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn callback_records_handle_result() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        test_support::insert_job(&state, 1, 1).await;
+        let log = job_log::Entity::insert(job_log::ActiveModel {
+            job_group: Set(1),
+            job_id: Set(1),
+            executor_fail_retry_count: Set(0),
+            trigger_code: Set(200),
+            handle_code: Set(0),
+            alarm_status: Set(0),
+            ..Default::default()
+        })
+        .exec_with_returning(state.db())
+        .await
+        .unwrap();
+
+        process_callback(
+            &state,
+            HandleCallbackParam {
+                log_id: log.id,
+                log_date_tim: 0,
+                handle_code: 200,
+                handle_msg: Some("执行成功".into()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let stored = job_log::Entity::find_by_id(log.id)
+            .one(state.db())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.handle_code, 200);
+        assert!(stored.handle_time.is_some());
+        assert_eq!(stored.handle_msg.as_deref(), Some("执行成功"));
+    }
+}
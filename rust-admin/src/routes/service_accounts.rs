@@ -0,0 +1,461 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, put},
+    Json, Router,
+};
+use chrono::{Duration, Local};
+use sea_orm::{query::*, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api_key;
+use crate::audit::{self, AuditAction, AuditEvent, AuditTarget};
+use crate::auth::{AuthUser, GroupRole, Permission};
+use crate::entities::{api_key as api_key_entity, job_group, service_account};
+use crate::error::{AppError, AppResult};
+use crate::session::ClientInfo;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_accounts).post(create_account))
+        .route("/:id", put(update_account).delete(remove_account))
+        .route("/:id/keys", get(list_keys).post(create_key))
+        .route("/:id/keys/:key_id", delete(revoke_key))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceAccountDto {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    enabled: bool,
+    created_by: Option<String>,
+    add_time: Option<chrono::NaiveDateTime>,
+    update_time: Option<chrono::NaiveDateTime>,
+}
+
+impl From<service_account::Model> for ServiceAccountDto {
+    fn from(value: service_account::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            enabled: value.enabled != 0,
+            created_by: value.created_by,
+            add_time: value.add_time,
+            update_time: value.update_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiKeyDto {
+    id: i64,
+    account_id: i32,
+    name: String,
+    key_prefix: String,
+    job_groups: Vec<i32>,
+    role: String,
+    expires_at: Option<chrono::NaiveDateTime>,
+    revoked_at: Option<chrono::NaiveDateTime>,
+    last_used_at: Option<chrono::NaiveDateTime>,
+    last_used_ip: Option<String>,
+    add_time: Option<chrono::NaiveDateTime>,
+}
+
+impl From<api_key_entity::Model> for ApiKeyDto {
+    fn from(value: api_key_entity::Model) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            name: value.name,
+            key_prefix: value.key_prefix,
+            job_groups: value
+                .job_groups
+                .split(',')
+                .filter_map(|item| item.trim().parse().ok())
+                .collect(),
+            role: value.role,
+            expires_at: value.expires_at,
+            revoked_at: value.revoked_at,
+            last_used_at: value.last_used_at,
+            last_used_ip: value.last_used_ip,
+            add_time: value.add_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedApiKeyDto {
+    /// API Key 明文，仅在创建时返回一次
+    key: String,
+    #[serde(flatten)]
+    detail: ApiKeyDto,
+}
+
+async fn list_accounts(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> AppResult<Json<Vec<ServiceAccountDto>>> {
+    user.require(Permission::Manage, None)?;
+    let accounts = service_account::Entity::find()
+        .order_by_asc(service_account::Column::Id)
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(ServiceAccountDto::from)
+        .collect();
+    Ok(Json(accounts))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+struct CreateAccountRequest {
+    #[validate(length(min = 3, max = 50, message = "服务账号名称需为 3-50 位"))]
+    name: String,
+    #[validate(length(max = 255, message = "用途说明不能超过 255 字符"))]
+    description: Option<String>,
+}
+
+async fn create_account(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<CreateAccountRequest>,
+) -> AppResult<Json<ServiceAccountDto>> {
+    user.require(Permission::Manage, None)?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+
+    let name = payload.name.trim().to_string();
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(AppError::BadRequest(
+            "服务账号名称只能包含字母、数字、- 和 _".into(),
+        ));
+    }
+    if service_account::Entity::find()
+        .filter(service_account::Column::Name.eq(name.as_str()))
+        .one(state.db())
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict("服务账号已存在".into()));
+    }
+
+    let now = Local::now().naive_local();
+    let active = service_account::ActiveModel {
+        name: Set(name),
+        description: Set(payload.description.clone()),
+        enabled: Set(1),
+        created_by: Set(Some(user.username.clone())),
+        add_time: Set(Some(now)),
+        update_time: Set(Some(now)),
+        ..Default::default()
+    };
+    let txn = state.db().begin().await?;
+    let inserted = service_account::Entity::insert(active)
+        .exec_with_returning(&txn)
+        .await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(
+            AuditAction::Create,
+            AuditTarget::ServiceAccount,
+            inserted.id,
+        )
+        .after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(ServiceAccountDto::from(inserted)))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+struct UpdateAccountRequest {
+    #[validate(length(max = 255, message = "用途说明不能超过 255 字符"))]
+    description: Option<String>,
+    enabled: bool,
+}
+
+async fn update_account(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateAccountRequest>,
+) -> AppResult<Json<ServiceAccountDto>> {
+    user.require(Permission::Manage, None)?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+
+    let before = find_account(&state, id).await?;
+    let mut active: service_account::ActiveModel = before.clone().into();
+    active.description = Set(payload.description.clone());
+    active.enabled = Set(i8::from(payload.enabled));
+    active.update_time = Set(Some(Local::now().naive_local()));
+
+    let txn = state.db().begin().await?;
+    let updated = active.update(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Update, AuditTarget::ServiceAccount, id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(ServiceAccountDto::from(updated)))
+}
+
+async fn remove_account(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require(Permission::Manage, None)?;
+    let model = find_account(&state, id).await?;
+
+    let txn = state.db().begin().await?;
+    api_key_entity::Entity::delete_many()
+        .filter(api_key_entity::Column::AccountId.eq(id))
+        .exec(&txn)
+        .await?;
+    service_account::Entity::delete_by_id(id).exec(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Delete, AuditTarget::ServiceAccount, id).before(&model),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({ "message": "服务账号已删除" })))
+}
+
+async fn list_keys(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<ApiKeyDto>>> {
+    user.require(Permission::Manage, None)?;
+    find_account(&state, id).await?;
+
+    let keys = api_key_entity::Entity::find()
+        .filter(api_key_entity::Column::AccountId.eq(id))
+        .order_by_desc(api_key_entity::Column::AddTime)
+        .all(state.db())
+        .await?
+        .into_iter()
+        .map(ApiKeyDto::from)
+        .collect();
+    Ok(Json(keys))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+struct CreateKeyRequest {
+    #[validate(length(min = 1, max = 50, message = "Key 名称需为 1-50 位"))]
+    name: String,
+    #[validate(length(min = 1, message = "至少授权一个执行器"))]
+    job_groups: Vec<i32>,
+    role: GroupRole,
+    #[validate(range(min = 1, message = "有效天数必须大于 0"))]
+    expires_in_days: Option<i64>,
+}
+
+async fn create_key(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(id): Path<i32>,
+    Json(payload): Json<CreateKeyRequest>,
+) -> AppResult<Json<CreatedApiKeyDto>> {
+    user.require(Permission::Manage, None)?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+
+    let account = find_account(&state, id).await?;
+    if account.enabled == 0 {
+        return Err(AppError::BadRequest("服务账号已停用，无法签发 Key".into()));
+    }
+
+    let mut job_groups = payload.job_groups.clone();
+    job_groups.sort_unstable();
+    job_groups.dedup();
+    let existing = job_group::Entity::find()
+        .filter(job_group::Column::Id.is_in(job_groups.clone()))
+        .count(state.db())
+        .await?;
+    if existing as usize != job_groups.len() {
+        return Err(AppError::BadRequest("授权的执行器不存在".into()));
+    }
+
+    let now = Local::now().naive_local();
+    let generated = api_key::generate();
+    let active = api_key_entity::ActiveModel {
+        account_id: Set(account.id),
+        name: Set(payload.name.trim().to_string()),
+        key_prefix: Set(generated.prefix.clone()),
+        key_hash: Set(generated.hash.clone()),
+        job_groups: Set(job_groups
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",")),
+        role: Set(payload.role.as_str().to_string()),
+        expires_at: Set(payload
+            .expires_in_days
+            .map(|days| now + Duration::days(days))),
+        revoked_at: Set(None),
+        last_used_at: Set(None),
+        last_used_ip: Set(None),
+        add_time: Set(Some(now)),
+        ..Default::default()
+    };
+    let txn = state.db().begin().await?;
+    let inserted = api_key_entity::Entity::insert(active)
+        .exec_with_returning(&txn)
+        .await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Create, AuditTarget::ApiKey, inserted.id).after(&inserted),
+    )
+    .await?;
+    txn.commit().await?;
+
+    Ok(Json(CreatedApiKeyDto {
+        key: generated.plaintext,
+        detail: ApiKeyDto::from(inserted),
+    }))
+}
+
+async fn revoke_key(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path((id, key_id)): Path<(i32, i64)>,
+) -> AppResult<Json<ApiKeyDto>> {
+    user.require(Permission::Manage, None)?;
+    let model = api_key_entity::Entity::find_by_id(key_id)
+        .filter(api_key_entity::Column::AccountId.eq(id))
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("API Key 不存在".into()))?;
+    if model.revoked_at.is_some() {
+        return Ok(Json(ApiKeyDto::from(model)));
+    }
+
+    let before = model.clone();
+    let mut active: api_key_entity::ActiveModel = model.into();
+    active.revoked_at = Set(Some(Local::now().naive_local()));
+    let txn = state.db().begin().await?;
+    let updated = active.update(&txn).await?;
+    audit::record(
+        &txn,
+        &user,
+        &client,
+        AuditEvent::new(AuditAction::Revoke, AuditTarget::ApiKey, key_id)
+            .before(&before)
+            .after(&updated),
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(Json(ApiKeyDto::from(updated)))
+}
+
+async fn find_account(state: &AppState, id: i32) -> AppResult<service_account::Model> {
+    service_account::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("服务账号不存在".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::FromRequestParts;
+    use axum::http::Request;
+
+    use super::*;
+    use crate::test_support;
+
+    async fn authenticate(state: &AppState, key: &str) -> AppResult<AuthUser> {
+        let (mut parts, _) = Request::builder()
+            .header("authorization", format!("Bearer {key}"))
+            .body(())
+            .unwrap()
+            .into_parts();
+        AuthUser::from_request_parts(&mut parts, state).await
+    }
+
+    #[tokio::test]
+    async fn disabled_account_keys_are_rejected() {
+        let state = test_support::state().await;
+        test_support::insert_group(&state, 1).await;
+        let Json(account) = create_account(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Json(CreateAccountRequest {
+                name: "deploy-bot".into(),
+                description: None,
+            }),
+        )
+        .await
+        .unwrap();
+        let Json(created) = create_key(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(account.id),
+            Json(CreateKeyRequest {
+                name: "ci".into(),
+                job_groups: vec![1],
+                role: GroupRole::Operator,
+                expires_in_days: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(authenticate(&state, &created.key).await.is_ok());
+
+        let Json(updated) = update_account(
+            State(state.clone()),
+            test_support::admin(),
+            ClientInfo::default(),
+            Path(account.id),
+            Json(UpdateAccountRequest {
+                description: Some("已泄露".into()),
+                enabled: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert!(!updated.enabled);
+        let stored = find_account(&state, account.id).await.unwrap();
+        assert_eq!(stored.enabled, 0);
+        assert_eq!(stored.description.as_deref(), Some("已泄露"));
+        assert!(matches!(
+            authenticate(&state, &created.key).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::auth::AuthUser;
+use crate::entities::job_user;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::totp;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(status))
+        .route("/enroll", post(enroll))
+        .route("/confirm", post(confirm))
+        .route("/disable", post(disable))
+        .route("/recovery-codes", post(regenerate_recovery_codes))
+}
+
+/// 绑定两步验证所需的信息，`qrSvg` 可直接嵌入页面供身份验证器应用扫描。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct TotpSetupDto {
+    secret: String,
+    otpauth_uri: String,
+    qr_svg: String,
+}
+
+impl TotpSetupDto {
+    pub(super) fn new(state: &AppState, username: &str, secret: &str) -> AppResult<Self> {
+        let otpauth_uri =
+            totp::provisioning_uri(&state.settings().security.totp_issuer, username, secret);
+        let qr_svg = totp::qr_svg(&otpauth_uri).map_err(AppError::internal)?;
+        Ok(Self {
+            secret: secret.to_string(),
+            otpauth_uri,
+            qr_svg,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TotpStatusDto {
+    enabled: bool,
+    /// 当前策略是否要求该账号启用两步验证
+    required: bool,
+    recovery_codes_remaining: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoveryCodesDto {
+    /// 恢复码明文，仅返回一次
+    recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct TotpCodeRequest {
+    #[validate(length(min = 1, message = "验证码不能为空"))]
+    code: String,
+}
+
+async fn status(State(state): State<AppState>, user: AuthUser) -> AppResult<Json<TotpStatusDto>> {
+    user.require_session()?;
+    let account = find_account(&state, user.id).await?;
+    let enabled = totp::is_enabled(&state, user.id).await?;
+    let recovery_codes_remaining = if enabled {
+        totp::remaining_recovery_codes(&state, user.id).await?
+    } else {
+        0
+    };
+    Ok(Json(TotpStatusDto {
+        enabled,
+        required: totp::is_required(&state, &account),
+        recovery_codes_remaining,
+    }))
+}
+
+/// 生成新的密钥，提交 `/confirm` 前不会生效。
+async fn enroll(State(state): State<AppState>, user: AuthUser) -> AppResult<Json<TotpSetupDto>> {
+    user.require_session()?;
+    if totp::is_enabled(&state, user.id).await? {
+        return Err(AppError::Conflict(
+            "已启用两步验证，如需更换请先关闭".into(),
+        ));
+    }
+    let secret = totp::generate_secret();
+    let setup = TotpSetupDto::new(&state, &user.username, &secret)?;
+    totp::save_pending_secret(&state, user.id, &secret).await?;
+    Ok(Json(setup))
+}
+
+async fn confirm(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> AppResult<Json<RecoveryCodesDto>> {
+    user.require_session()?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+    let pending = totp::find(&state, user.id)
+        .await?
+        .filter(|item| item.enabled == 0)
+        .ok_or_else(|| AppError::BadRequest("请先获取两步验证密钥".into()))?;
+
+    let now = chrono::Local::now().timestamp();
+    let step = totp::verify_code(&pending.secret, &payload.code, now, None)
+        .ok_or_else(|| AppError::BadRequest("验证码错误".into()))?;
+    let recovery_codes = totp::enable(&state, user.id, &pending.secret, step).await?;
+    Ok(Json(RecoveryCodesDto { recovery_codes }))
+}
+
+async fn disable(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    user.require_session()?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+    let account = find_account(&state, user.id).await?;
+    if totp::is_required(&state, &account) {
+        return Err(AppError::Forbidden("管理员账号必须启用两步验证".into()));
+    }
+    if !totp::verify_user_code(&state, user.id, &payload.code).await? {
+        return Err(AppError::BadRequest("验证码错误".into()));
+    }
+    totp::disable(state.db(), user.id).await?;
+    Ok(Json(serde_json::json!({ "message": "两步验证已关闭" })))
+}
+
+async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> AppResult<Json<RecoveryCodesDto>> {
+    user.require_session()?;
+    payload
+        .validate()
+        .map_err(|err| AppError::BadRequest(err.to_string()))?;
+    if !totp::verify_user_code(&state, user.id, &payload.code).await? {
+        return Err(AppError::BadRequest("验证码错误".into()));
+    }
+    let recovery_codes = totp::replace_recovery_codes(state.db(), user.id).await?;
+    Ok(Json(RecoveryCodesDto { recovery_codes }))
+}
+
+async fn find_account(state: &AppState, id: i32) -> AppResult<job_user::Model> {
+    job_user::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("用户不存在".into()))
+}
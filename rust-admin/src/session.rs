@@ -0,0 +1,266 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{header::USER_AGENT, request::Parts, HeaderMap, Method};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use chrono::{Local, NaiveDateTime};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set};
+use sha2::{Digest, Sha256};
+
+use crate::config::SecuritySettings;
+use crate::entities::job_user_session;
+use crate::error::{AppError, AppResult};
+use crate::password;
+use crate::state::AppState;
+
+const USER_AGENT_MAX_LEN: usize = 255;
+
+/// 管理台会话 Cookie，带 `HttpOnly`，页面脚本无法读取。
+pub const SESSION_COOKIE: &str = "xxl_admin_session";
+/// 管理台 CSRF 令牌 Cookie，页面脚本读取后通过 [`CSRF_HEADER`] 回传。
+pub const CSRF_COOKIE: &str = "xxl_admin_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// 发起请求的客户端信息；连接来自 `security.trusted_proxies` 中的反向代理时才读取其写入的
+/// `X-Forwarded-For` / `X-Real-IP`，否则使用连接的对端地址，避免客户端伪造来源 IP。
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(ClientInfo::from_parts(parts, &state.settings().security))
+    }
+}
+
+impl ClientInfo {
+    pub fn from_parts(parts: &Parts, security: &SecuritySettings) -> Self {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let ip = client_ip(&parts.headers, peer, |ip| security.is_trusted_proxy(ip))
+            .map(|ip| ip.to_string());
+
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(USER_AGENT_MAX_LEN).collect());
+
+        Self { ip, user_agent }
+    }
+}
+
+/// 对端是可信代理时，从 `X-Forwarded-For` 末尾向前跳过可信代理，取第一个不可信的地址；
+/// 没有该头时使用 `X-Real-IP`。头中的值无法解析为 IP 时回退到对端地址。
+fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    is_trusted: impl Fn(IpAddr) -> bool,
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !is_trusted(peer) {
+        return Some(peer);
+    }
+    let header = |name: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let forwarded = header("x-forwarded-for");
+    let mut hops = forwarded
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .rev()
+        .peekable();
+    if hops.peek().is_none() {
+        return Some(header("x-real-ip").trim().parse().unwrap_or(peer));
+    }
+    let mut client = peer;
+    for hop in hops {
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// 数据库只保存令牌摘要，泄露会话表也无法直接冒用登录态。
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct IssuedSession {
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// 为用户签发新的登录会话，同时顺带清理该用户已过期的会话。
+pub async fn issue(
+    state: &AppState,
+    user_id: i32,
+    client: &ClientInfo,
+) -> AppResult<IssuedSession> {
+    let now = Local::now().naive_local();
+    job_user_session::Entity::delete_many()
+        .filter(job_user_session::Column::UserId.eq(user_id))
+        .filter(job_user_session::Column::ExpiresAt.lte(now))
+        .exec(state.db())
+        .await?;
+
+    let token = uuid::Uuid::new_v4().to_string();
+    let expires_at = now + state.settings().security.token_ttl();
+    let active = job_user_session::ActiveModel {
+        user_id: Set(user_id),
+        token_hash: Set(hash_token(&token)),
+        issued_at: Set(now),
+        expires_at: Set(expires_at),
+        last_used_at: Set(Some(now)),
+        client_ip: Set(client.ip.clone()),
+        user_agent: Set(client.user_agent.clone()),
+        ..Default::default()
+    };
+    job_user_session::Entity::insert(active)
+        .exec(state.db())
+        .await?;
+
+    Ok(IssuedSession { token, expires_at })
+}
+
+/// 注销用户的全部会话，`keep` 指定需要保留的会话（例如当前会话）。
+pub async fn revoke_all<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    keep: Option<i64>,
+) -> AppResult<u64> {
+    let mut delete = job_user_session::Entity::delete_many()
+        .filter(job_user_session::Column::UserId.eq(user_id));
+    if let Some(id) = keep {
+        delete = delete.filter(job_user_session::Column::Id.ne(id));
+    }
+    Ok(delete.exec(conn).await?.rows_affected)
+}
+
+/// 由会话令牌派生 CSRF 令牌，校验时无需额外存储，且无法反推出会话令牌。
+pub fn csrf_token(session_token: &str) -> String {
+    hex::encode(Sha256::digest(format!("csrf:{session_token}").as_bytes()))
+}
+
+/// 写入管理台会话 Cookie 与 CSRF Cookie，有效期与会话一致。
+pub fn set_cookies(
+    jar: CookieJar,
+    settings: &SecuritySettings,
+    issued: &IssuedSession,
+) -> CookieJar {
+    let remaining = (issued.expires_at - Local::now().naive_local())
+        .num_seconds()
+        .max(0);
+    let build = |name: &'static str, value: String, http_only: bool| {
+        Cookie::build((name, value))
+            .path("/")
+            .http_only(http_only)
+            .secure(settings.cookie_secure)
+            .same_site(SameSite::Lax)
+            .max_age(time::Duration::seconds(remaining))
+    };
+    jar.add(build(SESSION_COOKIE, issued.token.clone(), true))
+        .add(build(CSRF_COOKIE, csrf_token(&issued.token), false))
+}
+
+/// 退出登录时清除管理台 Cookie。
+pub fn clear_cookies(jar: CookieJar) -> CookieJar {
+    jar.remove(Cookie::build(SESSION_COOKIE).path("/"))
+        .remove(Cookie::build(CSRF_COOKIE).path("/"))
+}
+
+/// 读取请求携带的管理台会话令牌。
+pub fn cookie_token(headers: &HeaderMap) -> Option<String> {
+    CookieJar::from_headers(headers)
+        .get(SESSION_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// 通过 Cookie 认证的写操作必须回传 CSRF 令牌；只读请求不做校验。
+pub fn verify_csrf(method: &Method, headers: &HeaderMap, session_token: &str) -> AppResult<()> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(());
+    }
+    let provided = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if password::constant_time_eq(&csrf_token(session_token), provided) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "CSRF 令牌校验失败，请刷新页面后重试".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csrf_required_for_cookie_writes() {
+        let token = "7b0c6f9e-session";
+        let mut headers = HeaderMap::new();
+        assert!(verify_csrf(&Method::GET, &headers, token).is_ok());
+        assert!(verify_csrf(&Method::POST, &headers, token).is_err());
+
+        headers.insert(CSRF_HEADER, csrf_token("other-session").parse().unwrap());
+        assert!(verify_csrf(&Method::DELETE, &headers, token).is_err());
+
+        headers.insert(CSRF_HEADER, csrf_token(token).parse().unwrap());
+        assert!(verify_csrf(&Method::PUT, &headers, token).is_ok());
+    }
+
+    #[test]
+    fn forwarded_headers_only_trusted_from_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let direct: IpAddr = "203.0.113.9".parse().unwrap();
+        let is_trusted = |ip: IpAddr| ip.to_string().starts_with("10.");
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "1.1.1.1, 198.51.100.7, 10.0.0.1".parse().unwrap(),
+        );
+        headers.insert("x-real-ip", "1.1.1.1".parse().unwrap());
+
+        assert_eq!(client_ip(&headers, Some(direct), is_trusted), Some(direct));
+        assert_eq!(
+            client_ip(&headers, Some(proxy), is_trusted),
+            Some("198.51.100.7".parse().unwrap())
+        );
+
+        headers.remove("x-forwarded-for");
+        assert_eq!(
+            client_ip(&headers, Some(proxy), is_trusted),
+            Some("1.1.1.1".parse().unwrap())
+        );
+        headers.insert("x-real-ip", "unknown".parse().unwrap());
+        assert_eq!(client_ip(&headers, Some(proxy), is_trusted), Some(proxy));
+        assert_eq!(client_ip(&headers, None, is_trusted), None);
+    }
+}
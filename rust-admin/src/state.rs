@@ -2,7 +2,11 @@ use std::sync::Arc;
 
 use sea_orm::DatabaseConnection;
 
+use crate::authenticator::{self, Authenticator};
 use crate::config::Settings;
+use crate::log_archive::{self, LogStore};
+use crate::oidc::PendingLogins;
+use crate::totp::LoginChallenges;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -13,15 +17,25 @@ struct AppStateInner {
     pub db: DatabaseConnection,
     pub settings: Settings,
     pub http_client: reqwest::Client,
+    pub oidc_logins: PendingLogins,
+    pub authenticators: Vec<Arc<dyn Authenticator>>,
+    pub totp_challenges: LoginChallenges,
+    pub log_store: Option<Arc<dyn LogStore>>,
 }
 
 impl AppState {
     pub fn new(db: DatabaseConnection, settings: Settings, http_client: reqwest::Client) -> Self {
+        let authenticators = authenticator::from_settings(&settings);
+        let log_store = log_archive::store::from_settings(&settings.log_archive);
         Self {
             inner: Arc::new(AppStateInner {
                 db,
                 settings,
                 http_client,
+                oidc_logins: PendingLogins::default(),
+                authenticators,
+                totp_challenges: LoginChallenges::default(),
+                log_store,
             }),
         }
     }
@@ -37,4 +51,21 @@ impl AppState {
     pub fn http_client(&self) -> &reqwest::Client {
         &self.inner.http_client
     }
+
+    pub fn oidc_logins(&self) -> &PendingLogins {
+        &self.inner.oidc_logins
+    }
+
+    pub fn authenticators(&self) -> &[Arc<dyn Authenticator>] {
+        &self.inner.authenticators
+    }
+
+    pub fn totp_challenges(&self) -> &LoginChallenges {
+        &self.inner.totp_challenges
+    }
+
+    /// 执行日志归档的存储后端，未启用归档时为 `None`。
+    pub fn log_store(&self) -> Option<&dyn LogStore> {
+        self.inner.log_store.as_deref()
+    }
 }
@@ -5,6 +5,7 @@ use askama::Template;
 pub struct LoginTemplate<'a> {
     pub app_name: &'a str,
     pub tagline: &'a str,
+    pub sso_enabled: bool,
 }
 
 #[derive(Template)]
@@ -12,6 +13,7 @@ pub struct LoginTemplate<'a> {
 pub struct DashboardTemplate<'a> {
     pub app_name: &'a str,
     pub active_nav: &'a str,
+    pub username: &'a str,
     pub summary_endpoint: &'a str,
     pub chart_endpoint: &'a str,
 }
@@ -21,6 +23,7 @@ pub struct DashboardTemplate<'a> {
 pub struct JobGroupsTemplate<'a> {
     pub app_name: &'a str,
     pub active_nav: &'a str,
+    pub username: &'a str,
     pub job_groups_endpoint: &'a str,
 }
 
@@ -29,6 +32,7 @@ pub struct JobGroupsTemplate<'a> {
 pub struct JobInfoTemplate<'a> {
     pub app_name: &'a str,
     pub active_nav: &'a str,
+    pub username: &'a str,
     pub job_groups_endpoint: &'a str,
     pub job_info_endpoint: &'a str,
     pub job_info_next_trigger_endpoint: &'a str,
@@ -39,6 +43,7 @@ pub struct JobInfoTemplate<'a> {
 pub struct JobLogsTemplate<'a> {
     pub app_name: &'a str,
     pub active_nav: &'a str,
+    pub username: &'a str,
     pub job_groups_endpoint: &'a str,
     pub job_logs_endpoint: &'a str,
 }
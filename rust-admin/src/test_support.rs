@@ -0,0 +1,117 @@
+//! 单元测试共用的内存数据库与应用状态，按实体建表后即可直接调用路由处理函数。
+
+use std::collections::BTreeMap;
+
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, EntityTrait, Schema, Set,
+};
+
+use crate::auth::{AuthUser, UserRole};
+use crate::config::Settings;
+use crate::entities::{
+    api_key, audit_log, job_group, job_info, job_info_revision, job_log, job_logglue, job_user,
+    job_user_session, service_account,
+};
+use crate::state::AppState;
+
+/// 基于 SQLite 内存库构造的应用状态，配置取自 `config/default.toml`。
+pub async fn state() -> AppState {
+    let settings: Settings = config::Config::builder()
+        .add_source(config::File::from_str(
+            include_str!("../config/default.toml"),
+            config::FileFormat::Toml,
+        ))
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .expect("解析默认配置失败");
+    AppState::new(database().await, settings, reqwest::Client::new())
+}
+
+async fn database() -> DatabaseConnection {
+    // 内存库只存在于单个连接中，连接池限定为一个连接
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1).sqlx_logging(false);
+    let db = Database::connect(options)
+        .await
+        .expect("连接内存数据库失败");
+    create_table(&db, api_key::Entity).await;
+    create_table(&db, audit_log::Entity).await;
+    create_table(&db, job_group::Entity).await;
+    create_table(&db, job_info::Entity).await;
+    create_table(&db, job_info_revision::Entity).await;
+    create_table(&db, job_log::Entity).await;
+    create_table(&db, job_logglue::Entity).await;
+    create_table(&db, job_user::Entity).await;
+    create_table(&db, job_user_session::Entity).await;
+    create_table(&db, service_account::Entity).await;
+    db
+}
+
+async fn create_table<E: EntityTrait>(db: &DatabaseConnection, entity: E) {
+    let backend = db.get_database_backend();
+    let statement = backend.build(&Schema::new(backend).create_table_from_entity(entity));
+    db.execute(statement).await.expect("创建测试表失败");
+}
+
+/// 拥有全部权限的管理员。
+pub fn admin() -> AuthUser {
+    AuthUser {
+        id: 1,
+        username: "admin".into(),
+        role: UserRole::Admin,
+        permission: None,
+        session_id: None,
+        api_key_id: None,
+        group_roles: BTreeMap::new(),
+    }
+}
+
+/// 写入一个手动注册的执行器。
+pub async fn insert_group(state: &AppState, id: i32) -> job_group::Model {
+    job_group::Entity::insert(job_group::ActiveModel {
+        id: Set(id),
+        app_name: Set(format!("app-{id}")),
+        title: Set(format!("执行器 {id}")),
+        address_type: Set(1),
+        address_list: Set(Some("http://127.0.0.1:9999".into())),
+        update_time: Set(None),
+    })
+    .exec_with_returning(state.db())
+    .await
+    .expect("写入执行器失败")
+}
+
+/// 在指定执行器下写入一个 BEAN 任务。
+pub async fn insert_job(state: &AppState, id: i32, job_group: i32) -> job_info::Model {
+    job_info::Entity::insert(job_info::ActiveModel {
+        id: Set(id),
+        job_group: Set(job_group),
+        job_desc: Set(format!("任务 {id}")),
+        job_key: Set(Some(format!("job-{id}"))),
+        add_time: Set(None),
+        update_time: Set(None),
+        author: Set(Some("admin".into())),
+        alarm_email: Set(None),
+        schedule_type: Set("CRON".into()),
+        schedule_conf: Set(Some("0 0 * * * ?".into())),
+        misfire_strategy: Set("DO_NOTHING".into()),
+        executor_route_strategy: Set(Some("FIRST".into())),
+        executor_handler: Set(Some("demoJobHandler".into())),
+        executor_param: Set(None),
+        executor_block_strategy: Set(Some("SERIAL_EXECUTION".into())),
+        executor_timeout: Set(0),
+        executor_fail_retry_count: Set(0),
+        glue_type: Set("BEAN".into()),
+        glue_source: Set(None),
+        glue_remark: Set(None),
+        glue_updatetime: Set(None),
+        child_jobid: Set(None),
+        cloned_from: Set(None),
+        trigger_status: Set(0),
+        trigger_last_time: Set(0),
+        trigger_next_time: Set(0),
+    })
+    .exec_with_returning(state.db())
+    .await
+    .expect("写入任务失败")
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use sha1::Sha1;
+
+use crate::auth::UserRole;
+use crate::entities::{job_user, job_user_recovery_code, job_user_totp};
+use crate::error::{AppError, AppResult};
+use crate::session::hash_token;
+use crate::state::AppState;
+
+const SECRET_BYTES: usize = 20;
+const DIGITS: u32 = 6;
+const PERIOD_SECONDS: i64 = 30;
+/// 允许前后各一个时间步的时钟偏差。
+const ALLOWED_SKEW_STEPS: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+const BASE32: base32::Alphabet = base32::Alphabet::Rfc4648 { padding: false };
+
+/// 两步登录中第一步通过后，等待提交验证码的最长时间。
+const CHALLENGE_TTL: Duration = Duration::from_secs(300);
+/// 同一挑战允许的验证码错误次数，超过后需重新输入密码。
+const CHALLENGE_MAX_ATTEMPTS: u32 = 5;
+
+/// 生成新的 TOTP 密钥（Base32 编码）。
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(BASE32, &bytes)
+}
+
+/// 生成身份验证器应用可识别的 `otpauth://` 地址。
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let encode = |value: &str| {
+        url::form_urlencoded::byte_serialize(value.as_bytes())
+            .collect::<String>()
+            .replace('+', "%20")
+    };
+    format!(
+        "otpauth://totp/{}:{}?secret={secret}&issuer={}&algorithm=SHA1&digits={DIGITS}&period={PERIOD_SECONDS}",
+        encode(issuer),
+        encode(account),
+        encode(issuer),
+    )
+}
+
+/// 把 `otpauth://` 地址渲染为二维码 SVG，供页面直接展示。
+pub fn qr_svg(uri: &str) -> anyhow::Result<String> {
+    let code = QrCode::new(uri.as_bytes()).map_err(|err| anyhow!("生成二维码失败: {err}"))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .quiet_zone(true)
+        .build())
+}
+
+fn code_at(key: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// 按 RFC 6238 校验验证码，返回匹配的时间步。
+///
+/// 不接受小于等于 `last_used_step` 的时间步，同一验证码不能重复使用。
+pub fn verify_code(
+    secret: &str,
+    code: &str,
+    unix_time: i64,
+    last_used_step: Option<i64>,
+) -> Option<i64> {
+    let code = normalize_code(code);
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let expected: u32 = code.parse().ok()?;
+    let key = base32::decode(BASE32, secret.trim())?;
+    let current = unix_time.div_euclid(PERIOD_SECONDS);
+    (current - ALLOWED_SKEW_STEPS..=current + ALLOWED_SKEW_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(&key, *step) == expected)
+}
+
+/// 生成一组一次性恢复码，形如 `a1b2c-d3e4f`。
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 5];
+            OsRng.fill_bytes(&mut bytes);
+            let raw = hex::encode(bytes);
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect()
+}
+
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// 当前策略下该用户是否必须启用两步验证。
+pub fn is_required(state: &AppState, user: &job_user::Model) -> bool {
+    state.settings().security.require_admin_totp && user.role == UserRole::Admin as i8
+}
+
+pub async fn find(state: &AppState, user_id: i32) -> AppResult<Option<job_user_totp::Model>> {
+    Ok(job_user_totp::Entity::find_by_id(user_id)
+        .one(state.db())
+        .await?)
+}
+
+pub async fn is_enabled(state: &AppState, user_id: i32) -> AppResult<bool> {
+    Ok(find(state, user_id)
+        .await?
+        .is_some_and(|totp| totp.enabled != 0))
+}
+
+pub async fn remaining_recovery_codes(state: &AppState, user_id: i32) -> AppResult<u64> {
+    Ok(job_user_recovery_code::Entity::find()
+        .filter(job_user_recovery_code::Column::UserId.eq(user_id))
+        .filter(job_user_recovery_code::Column::UsedAt.is_null())
+        .count(state.db())
+        .await?)
+}
+
+/// 保存待确认的密钥，用户输入正确验证码后才会真正启用。
+pub async fn save_pending_secret(state: &AppState, user_id: i32, secret: &str) -> AppResult<()> {
+    let now = Local::now().naive_local();
+    let active = job_user_totp::ActiveModel {
+        user_id: Set(user_id),
+        secret: Set(secret.to_string()),
+        enabled: Set(0),
+        last_used_step: Set(None),
+        add_time: Set(Some(now)),
+        update_time: Set(Some(now)),
+    };
+    let txn = state.db().begin().await?;
+    job_user_totp::Entity::delete_by_id(user_id)
+        .exec(&txn)
+        .await?;
+    job_user_totp::Entity::insert(active).exec(&txn).await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+/// 启用两步验证并生成恢复码，返回恢复码明文（仅此一次）。
+pub async fn enable(
+    state: &AppState,
+    user_id: i32,
+    secret: &str,
+    verified_step: i64,
+) -> AppResult<Vec<String>> {
+    let now = Local::now().naive_local();
+    let txn = state.db().begin().await?;
+    job_user_totp::Entity::delete_by_id(user_id)
+        .exec(&txn)
+        .await?;
+    job_user_totp::Entity::insert(job_user_totp::ActiveModel {
+        user_id: Set(user_id),
+        secret: Set(secret.to_string()),
+        enabled: Set(1),
+        last_used_step: Set(Some(verified_step)),
+        add_time: Set(Some(now)),
+        update_time: Set(Some(now)),
+    })
+    .exec(&txn)
+    .await?;
+    let codes = replace_recovery_codes(&txn, user_id).await?;
+    txn.commit().await?;
+    Ok(codes)
+}
+
+/// 作废旧的恢复码并生成一组新的。
+pub async fn replace_recovery_codes<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+) -> AppResult<Vec<String>> {
+    job_user_recovery_code::Entity::delete_many()
+        .filter(job_user_recovery_code::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    let now = Local::now().naive_local();
+    let codes = generate_recovery_codes();
+    let models = codes
+        .iter()
+        .map(|code| job_user_recovery_code::ActiveModel {
+            user_id: Set(user_id),
+            code_hash: Set(hash_token(&normalize_code(code))),
+            used_at: Set(None),
+            add_time: Set(Some(now)),
+            ..Default::default()
+        });
+    job_user_recovery_code::Entity::insert_many(models)
+        .exec(conn)
+        .await?;
+    Ok(codes)
+}
+
+/// 关闭两步验证，同时删除恢复码。
+pub async fn disable<C: ConnectionTrait + TransactionTrait>(
+    conn: &C,
+    user_id: i32,
+) -> AppResult<bool> {
+    let txn = conn.begin().await?;
+    let deleted = job_user_totp::Entity::delete_by_id(user_id)
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    job_user_recovery_code::Entity::delete_many()
+        .filter(job_user_recovery_code::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(deleted > 0)
+}
+
+/// 校验已启用两步验证用户提交的验证码或恢复码，恢复码校验通过后即作废。
+pub async fn verify_user_code(state: &AppState, user_id: i32, code: &str) -> AppResult<bool> {
+    let Some(totp) = find(state, user_id).await?.filter(|totp| totp.enabled != 0) else {
+        return Err(AppError::BadRequest("尚未启用两步验证".into()));
+    };
+
+    let now = Local::now().timestamp();
+    if let Some(step) = verify_code(&totp.secret, code, now, totp.last_used_step) {
+        let mut active: job_user_totp::ActiveModel = totp.into();
+        active.last_used_step = Set(Some(step));
+        active.update_time = Set(Some(Local::now().naive_local()));
+        active.update(state.db()).await?;
+        return Ok(true);
+    }
+
+    let used = job_user_recovery_code::Entity::update_many()
+        .col_expr(
+            job_user_recovery_code::Column::UsedAt,
+            sea_orm::sea_query::Expr::value(Local::now().naive_local()),
+        )
+        .filter(job_user_recovery_code::Column::UserId.eq(user_id))
+        .filter(job_user_recovery_code::Column::CodeHash.eq(hash_token(&normalize_code(code))))
+        .filter(job_user_recovery_code::Column::UsedAt.is_null())
+        .exec(state.db())
+        .await?
+        .rows_affected;
+    Ok(used > 0)
+}
+
+/// 两步登录中密码校验通过、等待验证码的登录请求。
+#[derive(Debug, Clone)]
+pub struct LoginChallenge {
+    pub user_id: i32,
+    /// 策略要求但尚未绑定，需要在本次登录中完成绑定
+    pub enrollment_required: bool,
+    /// 登录过程中生成、尚未确认的密钥
+    pub enrollment_secret: Option<String>,
+    created_at: Instant,
+    attempts: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct LoginChallenges {
+    inner: Mutex<HashMap<String, LoginChallenge>>,
+}
+
+impl LoginChallenges {
+    /// 创建挑战并返回挑战令牌与有效秒数。
+    pub fn begin(&self, user_id: i32, enrollment_required: bool) -> (String, u64) {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let mut challenges = self.inner.lock().expect("两步验证挑战锁已损坏");
+        challenges.retain(|_, item| item.created_at.elapsed() < CHALLENGE_TTL);
+        challenges.insert(
+            token.clone(),
+            LoginChallenge {
+                user_id,
+                enrollment_required,
+                enrollment_secret: None,
+                created_at: Instant::now(),
+                attempts: 0,
+            },
+        );
+        (token, CHALLENGE_TTL.as_secs())
+    }
+
+    pub fn get(&self, token: &str) -> Option<LoginChallenge> {
+        let challenges = self.inner.lock().expect("两步验证挑战锁已损坏");
+        challenges
+            .get(token)
+            .filter(|item| item.created_at.elapsed() < CHALLENGE_TTL)
+            .cloned()
+    }
+
+    pub fn set_enrollment_secret(&self, token: &str, secret: &str) {
+        let mut challenges = self.inner.lock().expect("两步验证挑战锁已损坏");
+        if let Some(item) = challenges.get_mut(token) {
+            item.enrollment_secret = Some(secret.to_string());
+        }
+    }
+
+    /// 记录一次验证码错误，超过次数后作废挑战。
+    pub fn record_failure(&self, token: &str) {
+        let mut challenges = self.inner.lock().expect("两步验证挑战锁已损坏");
+        if let Some(item) = challenges.get_mut(token) {
+            item.attempts += 1;
+            if item.attempts >= CHALLENGE_MAX_ATTEMPTS {
+                challenges.remove(token);
+            }
+        }
+    }
+
+    pub fn finish(&self, token: &str) -> bool {
+        let mut challenges = self.inner.lock().expect("两步验证挑战锁已损坏");
+        challenges.remove(token).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 6238 附录 B 的 SHA-1 测试密钥 `12345678901234567890`。
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_rfc6238_vectors() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", 59, None), Some(1));
+        assert_eq!(
+            verify_code(RFC_SECRET, "081804", 1_111_111_109, None),
+            Some(37_037_036)
+        );
+        assert_eq!(
+            verify_code(RFC_SECRET, "005924", 1_234_567_890, None),
+            Some(41_152_263)
+        );
+        assert_eq!(verify_code(RFC_SECRET, "000000", 59, None), None);
+    }
+
+    #[test]
+    fn tolerates_clock_skew_and_rejects_replay() {
+        // 287082 属于第 1 个时间步，允许前后一个时间步的偏差
+        assert_eq!(verify_code(RFC_SECRET, "287 082", 89, None), Some(1));
+        assert_eq!(verify_code(RFC_SECRET, "287082", 120, None), None);
+        assert_eq!(verify_code(RFC_SECRET, "287082", 59, Some(1)), None);
+    }
+
+    #[test]
+    fn generated_secret_round_trips() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        let key = base32::decode(BASE32, &secret).unwrap();
+        let code = format!("{:06}", code_at(&key, 100));
+        assert_eq!(verify_code(&secret, &code, 100 * 30, None), Some(100));
+
+        let uri = provisioning_uri("XXL JOB", "admin", &secret);
+        assert!(uri.starts_with("otpauth://totp/XXL%20JOB:admin?secret="));
+        assert!(qr_svg(&uri).unwrap().contains("<svg"));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), codes.len());
+        assert_eq!(normalize_code(" A1B2C-D3E4F "), "a1b2cd3e4f");
+    }
+}
@@ -0,0 +1,570 @@
+//! 向执行器发起一次调度：解析执行器地址、写入调度日志并调用执行器的 `/run`。
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Local, LocalResult, TimeZone, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Method,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
+
+use crate::entities::{job_group, job_info, job_log, job_registry};
+use crate::error::{AppError, AppResult};
+use crate::request_preview::{format_executor_request_curl, to_pretty_json};
+use crate::state::AppState;
+
+const REGISTRY_DEAD_TIMEOUT_SECONDS: i64 = 90;
+
+/// 分片广播参数，对应调度日志中的 `executor_sharding_param`（`index/total`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sharding {
+    pub index: i32,
+    pub total: i32,
+}
+
+impl Sharding {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (index, total) = raw.trim().split_once('/')?;
+        let index = index.trim().parse().ok()?;
+        let total = total.trim().parse().ok()?;
+        (index >= 0 && total > 0 && index < total).then_some(Self { index, total })
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}/{}", self.index, self.total)
+    }
+}
+
+/// 一次调度的参数。
+pub struct Dispatch<'a> {
+    pub job: &'a job_info::Model,
+    pub handler: String,
+    pub executor_param: Option<String>,
+    pub sharding: Option<Sharding>,
+    /// 手动指定的执行器地址列表（逗号或换行分隔），优先于执行器分组的地址
+    pub address_list: Option<&'a str>,
+    /// 优先尝试的执行器地址，仅当它在可用地址中时生效
+    pub preferred_address: Option<&'a str>,
+    /// 重跑时指向原调度日志
+    pub rerun_of: Option<i64>,
+    /// 写在调度备注开头的说明
+    pub trigger_lines: Vec<String>,
+}
+
+/// 调度结果，`address` 为空表示所有候选执行器都调用失败。
+#[derive(Debug, Clone)]
+pub struct Dispatched {
+    pub log_id: i64,
+    pub trigger_time: DateTime<Local>,
+    pub code: i32,
+    pub msg: Option<String>,
+    pub address: Option<String>,
+}
+
+pub async fn dispatch(state: &AppState, request: Dispatch<'_>) -> AppResult<Dispatched> {
+    let Dispatch {
+        job,
+        handler,
+        executor_param,
+        sharding,
+        address_list,
+        preferred_address,
+        rerun_of,
+        mut trigger_lines,
+    } = request;
+
+    let group = job_group::Entity::find_by_id(job.job_group)
+        .one(state.db())
+        .await?
+        .ok_or_else(|| AppError::NotFound("执行器分组不存在".into()))?;
+
+    debug!(
+        job_id = job.id,
+        job_group = group.id,
+        "已加载执行器分组信息"
+    );
+
+    let mut addresses = resolve_executor_addresses(state, &group, address_list).await?;
+
+    if addresses.is_empty() {
+        return Err(AppError::BadRequest("未找到可用的执行器地址".into()));
+    }
+    if let Some(preferred) = preferred_address.and_then(normalize_executor_address) {
+        if let Some(pos) = addresses.iter().position(|address| *address == preferred) {
+            let address = addresses.remove(pos);
+            addresses.insert(0, address);
+        }
+    }
+
+    info!(
+        job_id = job.id,
+        address_count = addresses.len(),
+        "已确定执行器地址列表"
+    );
+    debug!(job_id = job.id, addresses = %addresses.join(","), "执行器地址详情");
+
+    let now = Local::now();
+    let log_active = job_log::ActiveModel {
+        job_group: Set(job.job_group),
+        job_id: Set(job.id),
+        executor_address: Set(None),
+        executor_handler: Set(if handler.trim().is_empty() {
+            None
+        } else {
+            Some(handler.clone())
+        }),
+        executor_param: Set(executor_param.clone()),
+        executor_sharding_param: Set(sharding.map(|sharding| sharding.encode())),
+        executor_fail_retry_count: Set(job.executor_fail_retry_count),
+        trigger_time: Set(Some(now.naive_local())),
+        trigger_code: Set(0),
+        trigger_msg: Set(None),
+        handle_time: Set(None),
+        handle_code: Set(0),
+        handle_msg: Set(None),
+        alarm_status: Set(0),
+        rerun_of: Set(rerun_of),
+        ..Default::default()
+    };
+
+    debug!(job_id = job.id, "准备插入新的任务日志");
+
+    let inserted = job_log::Entity::insert(log_active).exec(state.db()).await?;
+    let log_id = inserted.last_insert_id;
+
+    info!(job_id = job.id, log_id, "已创建任务日志");
+
+    let trigger_param = build_trigger_param(
+        job,
+        log_id,
+        now.timestamp_millis(),
+        handler,
+        executor_param,
+        sharding,
+    );
+    debug!(job_id = job.id, log_id, "已构建执行器触发参数");
+    trigger_lines.push(format!("候选执行器地址: {}", addresses.join(", ")));
+
+    info!(job_id = job.id, log_id, "准备触发执行器");
+
+    let mut final_code = 500;
+    let mut final_msg: Option<String> = None;
+    let mut final_address: Option<String> = None;
+
+    for address in &addresses {
+        info!(
+            job_id = job.id,
+            log_id,
+            executor_address = address.as_str(),
+            "尝试触发执行器"
+        );
+        match trigger_executor(
+            state.http_client(),
+            address.as_str(),
+            state.settings().executor.access_token(),
+            &trigger_param,
+        )
+        .await
+        {
+            Ok(result) => {
+                info!(
+                    job_id = job.id,
+                    log_id,
+                    executor_address = address.as_str(),
+                    code = result.code,
+                    "执行器返回结果"
+                );
+                final_code = result.code;
+                final_msg = result.msg.clone();
+                final_address = Some(address.clone());
+
+                let mut line = format!("执行器 `{}` 返回 code = {}", address, result.code);
+                if let Some(msg) = result.msg.as_ref().filter(|m| !m.trim().is_empty()) {
+                    line.push_str(&format!(", msg = {}", msg));
+                }
+                if let Some(content) = result.content.as_ref().filter(|c| !c.trim().is_empty()) {
+                    line.push_str(&format!(", content = {}", content));
+                }
+                trigger_lines.push(line);
+
+                if result.code == 200 {
+                    info!(
+                        job_id = job.id,
+                        log_id,
+                        executor_address = address.as_str(),
+                        "执行器触发成功"
+                    );
+                    break;
+                }
+            }
+            Err(err) => {
+                warn!(
+                    job_id = job.id,
+                    log_id,
+                    executor_address = address.as_str(),
+                    error = %err,
+                    "调用执行器失败"
+                );
+                trigger_lines.push(format!("调用执行器 `{}` 失败: {}", address, err));
+            }
+        }
+    }
+
+    if final_address.is_none() {
+        error!(job_id = job.id, log_id, "所有候选执行器触发失败");
+        trigger_lines.push("所有可用执行器均触发失败".into());
+    }
+
+    let trigger_msg = trigger_lines.join("<br>");
+
+    let update_log = job_log::ActiveModel {
+        id: Set(log_id),
+        executor_address: Set(final_address.clone()),
+        trigger_code: Set(final_code),
+        trigger_msg: Set(Some(trigger_msg)),
+        ..Default::default()
+    };
+    job_log::Entity::update(update_log).exec(state.db()).await?;
+
+    debug!(job_id = job.id, log_id, "已更新任务日志触发结果");
+
+    Ok(Dispatched {
+        log_id,
+        trigger_time: now,
+        code: final_code,
+        msg: final_msg,
+        address: final_address,
+    })
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TriggerParamPayload {
+    job_id: i32,
+    executor_handler: String,
+    executor_params: String,
+    executor_block_strategy: String,
+    executor_timeout: i32,
+    log_id: i64,
+    log_date_time: i64,
+    glue_type: String,
+    glue_source: String,
+    glue_updatetime: i64,
+    broadcast_index: i32,
+    broadcast_total: i32,
+}
+
+fn build_trigger_param(
+    job: &job_info::Model,
+    log_id: i64,
+    log_time: i64,
+    handler: String,
+    executor_param: Option<String>,
+    sharding: Option<Sharding>,
+) -> TriggerParamPayload {
+    let executor_params = executor_param.unwrap_or_default();
+    let block_strategy = job
+        .executor_block_strategy
+        .clone()
+        .unwrap_or_else(|| "SERIAL_EXECUTION".to_string());
+    let glue_source = job.glue_source.clone().unwrap_or_default();
+    let glue_updatetime = job
+        .glue_updatetime
+        .map(timestamp_millis_from_naive_local)
+        .unwrap_or_default();
+
+    TriggerParamPayload {
+        job_id: job.id,
+        executor_handler: handler,
+        executor_params,
+        executor_block_strategy: block_strategy,
+        executor_timeout: job.executor_timeout,
+        log_id,
+        log_date_time: log_time,
+        glue_type: job.glue_type.clone(),
+        glue_source,
+        glue_updatetime,
+        broadcast_index: sharding.map_or(0, |sharding| sharding.index),
+        broadcast_total: sharding.map_or(1, |sharding| sharding.total),
+    }
+}
+
+fn timestamp_millis_from_naive_local(dt: chrono::NaiveDateTime) -> i64 {
+    match Local.from_local_datetime(&dt) {
+        LocalResult::Single(value) => value.timestamp_millis(),
+        LocalResult::Ambiguous(first, second) => {
+            first.timestamp_millis().min(second.timestamp_millis())
+        }
+        LocalResult::None => Utc.from_utc_datetime(&dt).timestamp_millis(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecutorReturn<T> {
+    code: i32,
+    msg: Option<T>,
+    content: Option<T>,
+}
+
+async fn trigger_executor(
+    client: &reqwest::Client,
+    raw_address: &str,
+    access_token: Option<&str>,
+    payload: &TriggerParamPayload,
+) -> anyhow::Result<ExecutorReturn<String>> {
+    let mut address = raw_address.trim().to_string();
+    if !address.ends_with('/') {
+        address.push('/');
+    }
+    address.push_str("run");
+
+    let request_body = match serde_json::to_string(payload) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(
+                executor_address = raw_address,
+                error = %err,
+                "序列化执行器触发请求体失败，使用 Debug 输出"
+            );
+            format!("{:?}", payload)
+        }
+    };
+    let json_value: serde_json::Value = match serde_json::from_str(&request_body) {
+        Ok(value) => value,
+        Err(err) => {
+            warn!(
+                executor_address = raw_address,
+                error = %err,
+                "解析执行器触发请求体失败，继续使用结构体序列化结果"
+            );
+            serde_json::to_value(payload).unwrap_or(serde_json::Value::Null)
+        }
+    };
+    let curl_preview = format_executor_request_curl(address.as_str(), access_token, &request_body);
+    let pretty_body = to_pretty_json(payload).unwrap_or_else(|| request_body.clone());
+
+    info!(
+        executor_address = raw_address,
+        url = address.as_str(),
+        request_body = %request_body,
+        pretty_request_body = %pretty_body,
+        curl = %curl_preview,
+        "发送执行器触发请求",
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    if let Some(token) = access_token {
+        let token_value = HeaderValue::from_str(token)
+            .map_err(|err| anyhow::anyhow!("访问令牌包含非法字符，无法写入请求头: {err}"))?;
+        headers.insert(HeaderName::from_static("xxl-job-access-token"), token_value);
+    }
+
+    let request_builder = client
+        .request(Method::POST, address.clone())
+        .headers(headers)
+        .json(&json_value);
+
+    if let Some(preview_builder) = request_builder.try_clone() {
+        if let Ok(preview_request) = preview_builder.build() {
+            info!(
+                executor_address = raw_address,
+                request_url = %preview_request.url(),
+                "执行器请求已构建"
+            );
+        }
+    }
+
+    let response = request_builder.send().await.map_err(|err| {
+        if err.is_connect() {
+            anyhow::anyhow!("无法连接到执行器，请确认网络和端口是否可达: {err}")
+        } else if err.is_timeout() {
+            anyhow::anyhow!("请求执行器超时，请检查执行器负载或网络状况: {err}")
+        } else {
+            anyhow::anyhow!("调用执行器发生错误: {err}")
+        }
+    })?;
+    debug!(executor_address = raw_address, status = %response.status(), "收到执行器响应");
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "执行器返回非成功状态码: {}",
+            response.status()
+        ));
+    }
+
+    Ok(response.json::<ExecutorReturn<String>>().await?)
+}
+
+pub async fn resolve_executor_addresses(
+    state: &AppState,
+    group: &job_group::Model,
+    override_address: Option<&str>,
+) -> AppResult<Vec<String>> {
+    if let Some(raw) = override_address {
+        debug!(job_group = group.id, "收到手动指定执行器地址");
+        let list = parse_address_list(raw);
+        if !list.is_empty() {
+            info!(
+                job_group = group.id,
+                address_count = list.len(),
+                "使用手动指定的执行器地址"
+            );
+            return Ok(list);
+        }
+        warn!(
+            job_group = group.id,
+            "手动指定的执行器地址为空或无效，继续尝试其它来源"
+        );
+    }
+
+    if group.address_type == 1 {
+        if let Some(raw) = group.address_list.as_deref() {
+            debug!(job_group = group.id, "尝试使用执行器分组静态地址");
+            let list = parse_address_list(raw);
+            if !list.is_empty() {
+                info!(
+                    job_group = group.id,
+                    address_count = list.len(),
+                    "使用执行器分组的静态地址"
+                );
+                return Ok(list);
+            }
+            warn!(
+                job_group = group.id,
+                "执行器分组静态地址列表为空，尝试从注册中心获取"
+            );
+        }
+    }
+
+    info!(
+        job_group = group.id,
+        app_name = group.app_name.as_str(),
+        "从注册中心查询执行器地址"
+    );
+    let cutoff = Local::now()
+        .checked_sub_signed(Duration::seconds(REGISTRY_DEAD_TIMEOUT_SECONDS))
+        .unwrap_or_else(Local::now)
+        .naive_local();
+
+    let registries = job_registry::Entity::find()
+        .filter(job_registry::Column::RegistryGroup.eq("EXECUTOR"))
+        .filter(job_registry::Column::RegistryKey.eq(group.app_name.as_str()))
+        .all(state.db())
+        .await?;
+
+    let mut unique = HashSet::new();
+    let mut alive: Vec<String> = Vec::new();
+    let mut stale: Vec<(String, Option<chrono::NaiveDateTime>)> = Vec::new();
+
+    for item in registries {
+        let Some(address) = normalize_executor_address(&item.registry_value) else {
+            continue;
+        };
+
+        if !unique.insert(address.clone()) {
+            continue;
+        }
+
+        if item.update_time.map(|t| t >= cutoff).unwrap_or(false) {
+            alive.push(address);
+        } else {
+            stale.push((address, item.update_time));
+        }
+    }
+
+    if !stale.is_empty() {
+        let last_seen = stale.iter().filter_map(|(_, ts)| *ts).max();
+        warn!(
+            job_group = group.id,
+            skipped = stale.len(),
+            cutoff = cutoff.to_string(),
+            last_seen = last_seen.map(|ts| ts.to_string()),
+            "剔除超过心跳超时时间的执行器实例"
+        );
+    }
+
+    if !alive.is_empty() {
+        info!(
+            job_group = group.id,
+            address_count = alive.len(),
+            stale_skipped = stale.len(),
+            "成功解析执行器地址"
+        );
+        return Ok(alive);
+    }
+
+    if !stale.is_empty() {
+        let fallback: Vec<String> = stale.into_iter().map(|(addr, _)| addr).collect();
+        warn!(
+            job_group = group.id,
+            address_count = fallback.len(),
+            cutoff = cutoff.to_string(),
+            "所有实例心跳超时，回退返回最近一次上报的执行器地址"
+        );
+        return Ok(fallback);
+    }
+
+    error!(job_group = group.id, "未检测到可用执行器实例");
+    Err(AppError::BadRequest(
+        "未检测到可用的执行器实例，请确认执行器是否注册成功并保持心跳".into(),
+    ))
+}
+
+fn parse_address_list(input: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    for value in input.split([',', '\n']) {
+        if let Some(address) = normalize_executor_address(value) {
+            if !values.contains(&address) {
+                values.push(address);
+            }
+        }
+    }
+    values
+}
+
+fn normalize_executor_address(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let normalized = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("http://{}", trimmed)
+    };
+
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sharding_param() {
+        assert_eq!(
+            Sharding::parse(" 2/5 "),
+            Some(Sharding { index: 2, total: 5 })
+        );
+        assert_eq!(Sharding::parse("2/5").unwrap().encode(), "2/5");
+        assert_eq!(Sharding::parse("5/5"), None);
+        assert_eq!(Sharding::parse("0/0"), None);
+        assert_eq!(Sharding::parse("-1/2"), None);
+        assert_eq!(Sharding::parse("abc"), None);
+    }
+
+    #[test]
+    fn normalizes_address_list() {
+        assert_eq!(
+            parse_address_list("127.0.0.1:9999, http://10.0.0.2:9999/\n127.0.0.1:9999"),
+            vec![
+                "http://127.0.0.1:9999".to_string(),
+                "http://10.0.0.2:9999/".to_string()
+            ]
+        );
+    }
+}